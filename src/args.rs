@@ -1,17 +1,124 @@
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pseudo_rs::{lexer::dialect::Dialect, lint::Lint, trace_table::TraceFormat, Options};
 
 #[derive(Parser)]
 #[command(name = "pseudo_rs", author = "slopey", version)]
 #[command(about = "Compiler and VM for Cambridge's A-Level pseudocode specification")]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     /// Debugging options such as printing the AST or dumping the tokens
     #[arg(short, long, value_enum)]
     pub debug: Option<DebugMode>,
     /// Compile the source file to C instead of interpreting it
     #[arg(long)]
     pub compile_to_c: bool,
+    #[command(flatten)]
+    pub run: RunArgs,
+    #[command(flatten)]
+    pub trace: TraceArgs,
     /// Path to the pseudocode source file
-    pub source_path: String,
+    pub source_path: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DialectArgs {
+    /// Pseudocode dialect of source files without a `// dialect: <id>` pragma: `strict`
+    /// (9618), `lenient` (keywords and names in any case) or `igcse` (0478)
+    #[arg(long, default_value = "strict", value_parser = parse_dialect)]
+    pub dialect: Dialect,
+}
+
+/// How a program is compiled and run, for the commands that run it.
+#[derive(Args)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub dialect: DialectArgs,
+    /// Let INTEGER arithmetic wrap around on overflow instead of stopping with a runtime error
+    #[arg(long)]
+    pub wrapping: bool,
+    /// Most calls that can be nested, such as by a recursive FUNCTION, before stopping
+    /// with a stack overflow
+    #[arg(long, default_value_t = pseudo_rs::vm::DEFAULT_MAX_CALL_DEPTH)]
+    pub max_call_depth: usize,
+}
+
+impl RunArgs {
+    pub fn options(&self) -> Options {
+        Options {
+            dialect: self.dialect.dialect,
+            wrapping: self.wrapping,
+            max_call_depth: self.max_call_depth,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct TraceArgs {
+    /// Print a trace table of every assignment and OUTPUT once the program finishes
+    #[arg(long)]
+    pub trace_table: bool,
+    /// Comma separated variables to include in the trace table, defaults to all of them
+    #[arg(long, value_delimiter = ',', requires = "trace_table")]
+    pub trace_vars: Vec<String>,
+    /// Layout of the trace table
+    #[arg(long, value_enum, default_value = "table", requires = "trace_table")]
+    pub trace_format: TraceFormat,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Compile a source file to a `.pbc` bytecode file
+    Build {
+        /// Path to the pseudocode source file
+        source_path: String,
+        /// Path of the bytecode file to write, defaults to the source path with a `.pbc` extension
+        #[arg(short, long)]
+        output: Option<String>,
+        #[command(flatten)]
+        dialect: DialectArgs,
+    },
+    /// Format pseudocode source files in place
+    Fmt {
+        /// Paths to the pseudocode source files
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Only check that the files are formatted, exiting with a non-zero status if not
+        #[arg(long)]
+        check: bool,
+        #[command(flatten)]
+        dialect: DialectArgs,
+    },
+    /// Step through a pseudocode source file with breakpoints and variable inspection
+    Debug {
+        /// Path to the pseudocode source file
+        source_path: String,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Warn about likely mistakes the type checker doesn't reject
+    Lint {
+        /// Paths to the pseudocode source files
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Comma separated lints not to report, e.g. `unused-variable,shadowed-name`
+        #[arg(long, value_delimiter = ',', value_parser = parse_lint)]
+        allow: Vec<Lint>,
+        #[command(flatten)]
+        dialect: DialectArgs,
+    },
+    /// Start a Language Server Protocol server speaking over stdio, for editor integration
+    Lsp,
+    /// Run a pseudocode source file or a compiled `.pbc` bytecode file
+    Run {
+        /// Path to the source or bytecode file
+        path: String,
+        #[command(flatten)]
+        run: RunArgs,
+        #[command(flatten)]
+        trace: TraceArgs,
+    },
 }
 
 #[derive(ValueEnum, Clone)]
@@ -19,5 +126,19 @@ pub enum DebugMode {
     PrintTokens,
     PrintAst,
     PrintBytecode,
+    PrintCst,
 }
 
+fn parse_dialect(id: &str) -> Result<Dialect, String> {
+    Dialect::from_id(id).ok_or_else(|| {
+        let ids: Vec<&str> = Dialect::ALL.iter().map(|dialect| dialect.id()).collect();
+        format!("unknown dialect, expected one of: {}", ids.join(", "))
+    })
+}
+
+fn parse_lint(id: &str) -> Result<Lint, String> {
+    Lint::from_id(id).ok_or_else(|| {
+        let ids: Vec<&str> = Lint::ALL.iter().map(|lint| lint.id()).collect();
+        format!("unknown lint, expected one of: {}", ids.join(", "))
+    })
+}
@@ -0,0 +1,232 @@
+use super::{Cfg, Node};
+use crate::{
+    ir::hlir::{Expr, ExprKind, StmtKind},
+    lexer::token::Token,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Whether some path through the body reaches its end without a RETURN.
+pub fn falls_through(cfg: &Cfg) -> bool {
+    cfg.reachable()[Cfg::END]
+}
+
+/// What calling a PROCEDURE or FUNCTION may assign.
+#[derive(Default)]
+pub struct Calls {
+    /// For every PROCEDURE/FUNCTION, whether each parameter is passed BYREF.
+    pub byref_params: HashMap<String, Vec<bool>>,
+    /// Global variables assigned somewhere in a PROCEDURE/FUNCTION, and so possibly by
+    /// any call.
+    pub assigned_globals: HashSet<String>,
+}
+
+/// A read of a variable that isn't assigned on every path leading to it.
+#[derive(Debug)]
+pub struct UnassignedUse {
+    pub name: String,
+    /// The identifier being read.
+    pub token: Token,
+}
+
+/// Definite assignment: finds reads of variables DECLAREd in the body that some path
+/// reaches before any assignment. Parameters and variables declared outside of the body
+/// are taken to be assigned. Reads in unreachable code are not reported.
+pub fn unassigned_uses(cfg: &Cfg, calls: &Calls) -> Vec<UnassignedUse> {
+    let declared: HashSet<String> = cfg
+        .blocks
+        .iter()
+        .flat_map(|block| block.nodes.iter())
+        .filter_map(|node| match node {
+            Node::Stmt(stmt) => match &stmt.stmt_kind {
+                StmtKind::VarDecl { name, .. } => Some(name.clone()),
+                _ => None,
+            },
+            Node::Condition(_) => None,
+        })
+        .collect();
+    let transfer = Transfer {
+        declared: &declared,
+        calls,
+    };
+
+    // Variables definitely assigned when leaving each block. Starting from every variable
+    // and only removing, the analysis settles on the largest sets consistent with all paths.
+    let predecessors = cfg.predecessors();
+    let mut assigned_out = vec![declared.clone(); cfg.blocks.len()];
+    let assigned_in = |id: usize, assigned_out: &Vec<HashSet<String>>| -> HashSet<String> {
+        match id {
+            Cfg::ENTRY => HashSet::new(),
+            _ => predecessors[id]
+                .iter()
+                .map(|predecessor| assigned_out[*predecessor].clone())
+                .reduce(|acc, set| acc.intersection(&set).cloned().collect())
+                .unwrap_or_else(|| declared.clone()),
+        }
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (id, block) in cfg.blocks.iter().enumerate() {
+            let mut assigned = assigned_in(id, &assigned_out);
+            block
+                .nodes
+                .iter()
+                .for_each(|node| transfer.node(node, &mut assigned, &mut |_| ()));
+            if assigned != assigned_out[id] {
+                assigned_out[id] = assigned;
+                changed = true;
+            }
+        }
+    }
+
+    let reachable = cfg.reachable();
+    let mut uses = Vec::new();
+    for (id, block) in cfg.blocks.iter().enumerate() {
+        if !reachable[id] {
+            continue;
+        }
+        let mut assigned = assigned_in(id, &assigned_out);
+        for node in block.nodes.iter() {
+            transfer.node(node, &mut assigned, &mut |unassigned| uses.push(unassigned));
+        }
+    }
+    uses.sort_by_key(|unassigned| (unassigned.token.line, unassigned.token.col));
+    uses
+}
+
+struct Transfer<'a> {
+    /// The variables being tracked.
+    declared: &'a HashSet<String>,
+    calls: &'a Calls,
+}
+
+impl Transfer<'_> {
+    fn node(
+        &self,
+        node: &Node,
+        assigned: &mut HashSet<String>,
+        report: &mut dyn FnMut(UnassignedUse),
+    ) {
+        let stmt = match node {
+            Node::Stmt(stmt) => stmt,
+            Node::Condition(condition) => return self.expr(condition, assigned, report),
+        };
+        match &stmt.stmt_kind {
+            // A variable declared in a loop starts out unassigned on every iteration.
+            StmtKind::VarDecl { name, .. } => {
+                assigned.remove(name);
+            }
+            // Like DECLARE, but the set starts out holding its values.
+            StmtKind::Define { name, values, .. } => {
+                values
+                    .iter()
+                    .for_each(|value| self.expr(value, assigned, report));
+                assigned.insert(name.clone());
+            }
+            StmtKind::Call { name, args } => self.call(name, args, assigned, report),
+            StmtKind::CallMethod {
+                object,
+                method,
+                args,
+            } => {
+                self.expr(object, assigned, report);
+                self.call(&method.name, args, assigned, report);
+            }
+            StmtKind::Return(expr) | StmtKind::Expr(expr) => self.expr(expr, assigned, report),
+            StmtKind::Output(exprs) => exprs
+                .iter()
+                .for_each(|expr| self.expr(expr, assigned, report)),
+            StmtKind::If { .. }
+            | StmtKind::Case { .. }
+            | StmtKind::Repeat { .. }
+            | StmtKind::While { .. }
+            | StmtKind::Try { .. }
+            | StmtKind::Block(_) => unreachable!("control flow is split into basic blocks"),
+        }
+    }
+
+    fn expr(
+        &self,
+        expr: &Expr,
+        assigned: &mut HashSet<String>,
+        report: &mut dyn FnMut(UnassignedUse),
+    ) {
+        match &expr.expr_kind {
+            ExprKind::Variable { name, token } => {
+                if self.declared.contains(name) && !assigned.contains(name) {
+                    report(UnassignedUse {
+                        name: name.clone(),
+                        token: token.clone(),
+                    });
+                    // Only the first read before an assignment is reported.
+                    assigned.insert(name.clone());
+                }
+            }
+            ExprKind::Assignment { target, value } => {
+                self.expr(value, assigned, report);
+                assigned.insert(target.clone());
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.expr(lhs, assigned, report);
+                self.expr(rhs, assigned, report);
+            }
+            // The right-hand side might not be evaluated, so what it assigns doesn't count.
+            ExprKind::Logical { lhs, rhs, .. } => {
+                self.expr(lhs, assigned, report);
+                self.expr(rhs, &mut assigned.clone(), report);
+            }
+            ExprKind::Unary { expr, .. } => self.expr(expr, assigned, report),
+            ExprKind::Call { callee, args } => self.call(callee, args, assigned, report),
+            ExprKind::MethodCall {
+                object,
+                method,
+                args,
+            } => {
+                self.expr(object, assigned, report);
+                self.call(&method.name, args, assigned, report);
+            }
+            ExprKind::Construct {
+                constructor, args, ..
+            } => match constructor {
+                Some(constructor) => self.call(&constructor.name, args, assigned, report),
+                None => args.iter().for_each(|arg| self.expr(arg, assigned, report)),
+            },
+            ExprKind::Field { object, .. } => self.expr(object, assigned, report),
+            ExprKind::FieldAssignment { object, value, .. } => {
+                self.expr(object, assigned, report);
+                self.expr(value, assigned, report);
+            }
+            ExprKind::Deref { pointer } => self.expr(pointer, assigned, report),
+            ExprKind::DerefAssignment { pointer, value } => {
+                self.expr(pointer, assigned, report);
+                self.expr(value, assigned, report);
+            }
+            // As with BYREF arguments, the variable may be assigned through the pointer.
+            ExprKind::AddressOf { name, .. } => {
+                assigned.insert(name.clone());
+            }
+            ExprKind::Literal(_) | ExprKind::New { .. } | ExprKind::Input => (),
+        }
+    }
+
+    fn call(
+        &self,
+        callee: &str,
+        args: &[Expr],
+        assigned: &mut HashSet<String>,
+        report: &mut dyn FnMut(UnassignedUse),
+    ) {
+        let byref_params = self.calls.byref_params.get(callee);
+        let mut byref_args = Vec::new();
+        for (idx, arg) in args.iter().enumerate() {
+            let is_byref = byref_params.is_some_and(|params| params.get(idx) == Some(&true));
+            match &arg.expr_kind {
+                // The callee may only assign it, so it needn't be assigned beforehand.
+                ExprKind::Variable { name, .. } if is_byref => byref_args.push(name.clone()),
+                _ => self.expr(arg, assigned, report),
+            }
+        }
+        assigned.extend(byref_args);
+        assigned.extend(self.calls.assigned_globals.iter().cloned());
+    }
+}
@@ -0,0 +1,211 @@
+//! Control-flow graphs over the bodies of `ir::hlir` PROCEDUREs, FUNCTIONs and scripts,
+//! and the dataflow analyses `naive_tc` runs on them.
+//!
+//! Statements without control flow of their own are kept whole in basic blocks, so the
+//! analyses walk their expressions directly. Every RETURN jumps to `Cfg::EXIT`, while
+//! falling off the end of the body reaches `Cfg::END`.
+
+mod dataflow;
+#[cfg(test)]
+mod tests;
+
+pub use self::dataflow::{falls_through, unassigned_uses, Calls, UnassignedUse};
+
+use crate::ir::hlir::{Expr, Stmt, StmtKind};
+
+pub type BlockId = usize;
+
+#[derive(Debug)]
+pub enum Node<'a> {
+    /// A statement without control flow of its own.
+    Stmt(&'a Stmt),
+    /// The condition an IF or a loop branches on, or a CASE selector or value.
+    Condition(&'a Expr),
+}
+
+#[derive(Debug, Default)]
+pub struct BasicBlock<'a> {
+    pub nodes: Vec<Node<'a>>,
+    pub successors: Vec<BlockId>,
+}
+
+#[derive(Debug)]
+pub struct Cfg<'a> {
+    pub blocks: Vec<BasicBlock<'a>>,
+}
+
+impl<'a> Cfg<'a> {
+    pub const ENTRY: BlockId = 0;
+    /// Reached by every RETURN.
+    pub const EXIT: BlockId = 1;
+    /// Reached by falling off the end of the body.
+    pub const END: BlockId = 2;
+
+    pub fn build(body: impl IntoIterator<Item = &'a Stmt>) -> Self {
+        let mut builder = Builder {
+            cfg: Cfg {
+                blocks: (0..3).map(|_| BasicBlock::default()).collect(),
+            },
+            current: Cfg::ENTRY,
+        };
+        body.into_iter().for_each(|stmt| builder.stmt(stmt));
+        builder.edge(builder.current, Cfg::END);
+        builder.cfg
+    }
+
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut predecessors = vec![Vec::new(); self.blocks.len()];
+        for (id, block) in self.blocks.iter().enumerate() {
+            block
+                .successors
+                .iter()
+                .for_each(|successor| predecessors[*successor].push(id));
+        }
+        predecessors
+    }
+
+    /// Which blocks can be reached from `Cfg::ENTRY`.
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut worklist = vec![Cfg::ENTRY];
+        while let Some(id) = worklist.pop() {
+            if reachable[id] {
+                continue;
+            }
+            reachable[id] = true;
+            worklist.extend(self.blocks[id].successors.iter());
+        }
+        reachable
+    }
+}
+
+struct Builder<'a> {
+    cfg: Cfg<'a>,
+    /// The block statements are currently added to.
+    current: BlockId,
+}
+
+impl<'a> Builder<'a> {
+    fn new_block(&mut self) -> BlockId {
+        self.cfg.blocks.push(BasicBlock::default());
+        self.cfg.blocks.len() - 1
+    }
+
+    fn edge(&mut self, from: BlockId, to: BlockId) {
+        self.cfg.blocks[from].successors.push(to);
+    }
+
+    fn push(&mut self, node: Node<'a>) {
+        self.cfg.blocks[self.current].nodes.push(node);
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match &stmt.stmt_kind {
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.push(Node::Condition(condition));
+                let branch = self.current;
+                let join = self.new_block();
+
+                self.current = self.new_block();
+                self.edge(branch, self.current);
+                self.stmt(then_branch);
+                self.edge(self.current, join);
+
+                match else_branch {
+                    Some(else_branch) => {
+                        self.current = self.new_block();
+                        self.edge(branch, self.current);
+                        self.stmt(else_branch);
+                        self.edge(self.current, join);
+                    }
+                    None => self.edge(branch, join),
+                }
+                self.current = join;
+            }
+            StmtKind::Case {
+                selector,
+                arms,
+                otherwise,
+            } => {
+                // The values are compared in turn, but are taken to all be evaluated before
+                // branching to any arm.
+                self.push(Node::Condition(selector));
+                for arm in arms.iter() {
+                    self.push(Node::Condition(&arm.value));
+                    if let Some(to) = &arm.to {
+                        self.push(Node::Condition(to));
+                    }
+                }
+                let branch = self.current;
+                let join = self.new_block();
+
+                for body in arms.iter().map(|arm| &arm.body).chain(otherwise.as_deref()) {
+                    self.current = self.new_block();
+                    self.edge(branch, self.current);
+                    self.stmt(body);
+                    self.edge(self.current, join);
+                }
+                if otherwise.is_none() {
+                    self.edge(branch, join);
+                }
+                self.current = join;
+            }
+            StmtKind::While { body, condition } => {
+                let header = self.new_block();
+                self.edge(self.current, header);
+                self.current = header;
+                self.push(Node::Condition(condition));
+
+                let after = self.new_block();
+                self.current = self.new_block();
+                self.edge(header, self.current);
+                self.edge(header, after);
+                self.stmt(body);
+                self.edge(self.current, header);
+                self.current = after;
+            }
+            StmtKind::Repeat { body, until } => {
+                let start = self.new_block();
+                self.edge(self.current, start);
+                self.current = start;
+                self.stmt(body);
+                self.push(Node::Condition(until));
+
+                let after = self.new_block();
+                self.edge(self.current, start);
+                self.edge(self.current, after);
+                self.current = after;
+            }
+            // A runtime error can stop the TRY block anywhere, so the EXCEPT block only counts
+            // on what was assigned before it.
+            StmtKind::Try { body, handler } => {
+                let before = self.current;
+                let join = self.new_block();
+                for block in [body, handler] {
+                    self.current = self.new_block();
+                    self.edge(before, self.current);
+                    self.stmt(block);
+                    self.edge(self.current, join);
+                }
+                self.current = join;
+            }
+            StmtKind::Block(stmts) => stmts.iter().for_each(|stmt| self.stmt(stmt)),
+            StmtKind::Return(_) => {
+                self.push(Node::Stmt(stmt));
+                self.edge(self.current, Cfg::EXIT);
+                // Anything after the RETURN goes in a block nothing jumps to.
+                self.current = self.new_block();
+            }
+            StmtKind::Call { .. }
+            | StmtKind::CallMethod { .. }
+            | StmtKind::VarDecl { .. }
+            | StmtKind::Define { .. }
+            | StmtKind::Expr(_)
+            | StmtKind::Output(_) => self.push(Node::Stmt(stmt)),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use super::{falls_through, Cfg};
+use crate::{
+    ir::hlir::Decl,
+    naive_tc::test_support::{check_error, typecheck},
+};
+
+#[test]
+fn graph() {
+    let decls = typecheck(
+        "FUNCTION Count(N : INTEGER) RETURNS INTEGER
+    DECLARE I : INTEGER
+    I <- 0
+    WHILE I < N
+        I <- I + 1
+        IF I = 2 THEN
+            RETURN I
+        ENDIF
+    ENDWHILE
+    RETURN 0
+    OUTPUT \"unreachable\"
+ENDFUNCTION
+",
+    )
+    .unwrap();
+    let body = match &decls[0] {
+        Decl::Function { body, .. } => body,
+        _ => unreachable!(),
+    };
+    let cfg = Cfg::build([body]);
+    let reachable = cfg.reachable();
+    assert!(reachable[Cfg::EXIT]);
+    assert!(!reachable[Cfg::END]);
+    assert!(!falls_through(&cfg));
+    // The OUTPUT after the last RETURN sits alone in a block nothing jumps to.
+    let unreachable: Vec<usize> = (0..cfg.blocks.len())
+        .filter(|id| !reachable[*id] && !cfg.blocks[*id].nodes.is_empty())
+        .collect();
+    assert_eq!(unreachable.len(), 1);
+    // The end of the loop body jumps back to the loop header, created before it.
+    let predecessors = cfg.predecessors();
+    assert!((Cfg::END + 1..cfg.blocks.len())
+        .any(|id| predecessors[id].iter().any(|predecessor| *predecessor > id)));
+}
+
+#[test]
+fn assigned_in_one_branch() {
+    check_error(
+        "DECLARE X : INTEGER
+DECLARE Y : INTEGER
+INPUT Y
+IF Y > 0 THEN
+    X <- 1
+ENDIF
+OUTPUT X
+",
+        "`X` might be used before it is assigned a value.",
+        7,
+        Some(8),
+    );
+}
+
+#[test]
+fn assigned_in_every_branch() {
+    assert!(typecheck(
+        "DECLARE X : INTEGER
+DECLARE Y : INTEGER
+INPUT Y
+IF Y > 0 THEN
+    X <- 1
+ELSE
+    X <- 2
+ENDIF
+OUTPUT X
+",
+    )
+    .is_ok());
+}
+
+#[test]
+fn loops() {
+    // A WHILE body might never run, a REPEAT body always runs at least once.
+    check_error(
+        "DECLARE X : INTEGER
+DECLARE I : INTEGER
+I <- 0
+WHILE I < 1
+    X <- I
+    I <- I + 1
+ENDWHILE
+OUTPUT X
+",
+        "`X` might be used before it is assigned a value.",
+        8,
+        Some(8),
+    );
+    assert!(typecheck(
+        "DECLARE X : INTEGER
+REPEAT
+    X <- 1
+UNTIL X > 0
+OUTPUT X
+",
+    )
+    .is_ok());
+}
+
+#[test]
+fn calls_assign() {
+    assert!(typecheck(
+        "DECLARE Total : INTEGER
+DECLARE Value : INTEGER
+PROCEDURE Reset
+    Total <- 0
+ENDPROCEDURE
+PROCEDURE Fill(BYREF Out : INTEGER)
+    Out <- 1
+ENDPROCEDURE
+CALL Reset
+CALL Fill(Value)
+OUTPUT Total, Value
+",
+    )
+    .is_ok());
+}
+
+#[test]
+fn locals_and_parameters() {
+    check_error(
+        "FUNCTION Twice(N : INTEGER) RETURNS INTEGER
+    DECLARE Result : INTEGER
+    IF N > 0 THEN
+        Result <- N * 2
+    ENDIF
+    RETURN Result
+ENDFUNCTION
+OUTPUT Twice(2)
+",
+        "`Result` might be used before it is assigned a value.",
+        6,
+        Some(12),
+    );
+}
+
+#[test]
+fn missing_return() {
+    check_error(
+        "FUNCTION Sign(N : INTEGER) RETURNS INTEGER
+    IF N > 0 THEN
+        RETURN 1
+    ENDIF
+ENDFUNCTION
+OUTPUT Sign(2)
+",
+        "FUNCTION `Sign` can reach ENDFUNCTION without a RETURN.",
+        1,
+        None,
+    );
+    assert!(typecheck(
+        "FUNCTION Sign(N : INTEGER) RETURNS INTEGER
+    IF N > 0 THEN
+        RETURN 1
+    ELSE
+        RETURN 0
+    ENDIF
+ENDFUNCTION
+FUNCTION First RETURNS INTEGER
+    REPEAT
+        RETURN 1
+    UNTIL FALSE
+ENDFUNCTION
+OUTPUT Sign(2) + First()
+",
+    )
+    .is_ok());
+}
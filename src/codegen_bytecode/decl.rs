@@ -1,11 +1,12 @@
 use crate::{
     codegen_bytecode::Generator,
-    ir::hlir::{Decl, Param},
+    ir::hlir::{Decl, Param, Stmt, StmtKind, Type},
     vm::{
         chunk::Chunk,
+        class::ObjClass,
         instr::Instr,
-        obj::{allocate_string, store_function, Obj, ObjFn, ObjKind},
-        value::Value,
+        obj::{allocate_class, allocate_string, store_function, Obj, ObjFn, ObjKind},
+        value::{Value, ValueKind},
     },
 };
 
@@ -13,11 +14,47 @@ impl Generator<'_> {
     pub fn define_decl(&mut self, decl: &Decl) {
         match decl {
             Decl::Procedure { name, .. } => {
-                self.declare_global(name.clone());
+                self.declare_global(name.clone(), None);
             }
             Decl::Function { name, .. } => {
-                self.declare_global(name.clone());
+                self.declare_global(name.clone(), None);
             }
+            Decl::Stmt(Stmt {
+                stmt_kind: StmtKind::VarDecl { name, pseudo_type },
+                ..
+            }) => {
+                self.declare_global(name.clone(), Some(*pseudo_type));
+            }
+            Decl::Stmt(Stmt {
+                stmt_kind: StmtKind::Define {
+                    name, pseudo_type, ..
+                },
+                ..
+            }) => {
+                self.declare_global(name.clone(), Some(*pseudo_type));
+            }
+            Decl::Stmt(_) | Decl::Class { .. } => (),
+        }
+    }
+
+    pub fn define_class(&mut self, decl: &Decl) {
+        if let Decl::Class {
+            name,
+            class_type,
+            attributes,
+            methods,
+        } = decl
+        {
+            let methods = methods
+                .iter()
+                .map(|method| self.resolve_global(method))
+                .collect();
+            let class = ObjClass::new(class_type, attributes.clone(), methods);
+            let class = allocate_class(self.vm, class);
+            self.classes.insert(name.clone(), class);
+            // Every CLASS is a constant of the script, so that the verifier knows the
+            // attributes and methods of those that are never made with NEW too.
+            self.target().add_constant(Value { obj: class }, ValueKind::Obj);
         }
     }
 
@@ -31,6 +68,8 @@ impl Generator<'_> {
                     },
                     chunk: Chunk::new(),
                     name: allocate_string(self.vm, name.to_string()),
+                    arity: params.len(),
+                    ret_type: Type::Null,
                 };
 
                 let previous_function = std::mem::replace(&mut self.function, Some(function));
@@ -40,21 +79,24 @@ impl Generator<'_> {
                 self.stmt(body);
                 self.emit(Instr::Null);
                 self.emit(Instr::Ret(params.len()));
+                self.close_locals();
 
                 let function = std::mem::replace(&mut self.function, previous_function);
                 self.locals = previous_locals;
 
                 let function = store_function(self.vm, function.unwrap());
+                self.line = body.line;
 
-                self.emit_constant(Value { obj: function });
-                self.emit(Instr::StoreGlobal(self.resolve_global(name)));
+                self.emit_constant(Value { obj: function }, ValueKind::Obj);
+                let idx = self.resolve_global(name);
+                self.emit(Instr::StoreGlobal(idx));
                 self.emit(Instr::Pop);
             }
             Decl::Function {
                 name,
                 params,
                 body,
-                ..
+                return_type,
             } => {
                 let function = ObjFn {
                     obj: Obj {
@@ -63,6 +105,8 @@ impl Generator<'_> {
                     },
                     chunk: Chunk::new(),
                     name: allocate_string(self.vm, name.to_string()),
+                    arity: params.len(),
+                    ret_type: *return_type,
                 };
 
                 let previous_function = std::mem::replace(&mut self.function, Some(function));
@@ -71,23 +115,29 @@ impl Generator<'_> {
 
                 self.params(params);
                 self.stmt(body);
+                self.close_locals();
 
                 let function = std::mem::replace(&mut self.function, previous_function);
                 self.locals = previous_locals;
                 self.current_function_args = previous_args_len;
 
                 let function = store_function(self.vm, function.unwrap());
+                self.line = body.line;
 
-                self.emit_constant(Value { obj: function });
-                self.emit(Instr::StoreGlobal(self.resolve_global(name)));
+                self.emit_constant(Value { obj: function }, ValueKind::Obj);
+                let idx = self.resolve_global(name);
+                self.emit(Instr::StoreGlobal(idx));
                 self.emit(Instr::Pop);
             }
+            Decl::Stmt(stmt) => self.stmt(stmt),
+            // Made by `define_class`, the methods are declared on their own.
+            Decl::Class { .. } => (),
         }
     }
 
     fn params(&mut self, params: &Vec<Param>) {
         params.iter().for_each(|param| {
-            self.add_local(param.name.clone());
+            self.add_local(param.name.clone(), param.pseudo_type);
         })
     }
 }
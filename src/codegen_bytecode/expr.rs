@@ -1,19 +1,32 @@
 use super::Generator;
 use crate::{
     ir::ast::LiteralKind,
-    ir::hlir::{Expr, ExprKind},
+    ir::hlir::{Expr, ExprKind, MethodRef, Type},
     lexer::token::{KeywordKind, TokenKind::*},
-    vm::{instr::Instr, obj::allocate_string, value::Value},
+    vm::{
+        instr::Instr,
+        obj::allocate_string,
+        value::{Value, ValueKind},
+    },
 };
 
 impl Generator<'_> {
     pub fn expr(&mut self, expr: &Expr) {
         match &expr.expr_kind {
+            // CHARs are concatenated as STRINGs of one character.
+            ExprKind::Binary { lhs, op, rhs } if op.kind == Ampersand => {
+                for operand in [lhs, rhs] {
+                    self.expr(operand);
+                    if operand.pseudo_type == Type::Char {
+                        self.emit(Instr::CharToString);
+                    }
+                }
+                self.emit(Instr::Concat);
+            }
             ExprKind::Binary { lhs, op, rhs } => {
                 self.expr(&lhs);
                 self.expr(&rhs);
                 match &op.kind {
-                    Ampersand => self.emit(Instr::Concat),
                     Plus => self.emit(Instr::Add(lhs.pseudo_type)),
                     Minus => self.emit(Instr::Sub(lhs.pseudo_type)),
                     Star => self.emit(Instr::Mul(lhs.pseudo_type)),
@@ -36,6 +49,7 @@ impl Generator<'_> {
                     Keyword(keyword_kind) => match keyword_kind {
                         KeywordKind::Div => self.emit(Instr::Div(lhs.pseudo_type)),
                         KeywordKind::Mod => self.emit(Instr::Mod(lhs.pseudo_type)),
+                        KeywordKind::In => self.emit(Instr::In),
                         _ => unreachable!()
                     }
                     _ => unreachable!(),
@@ -50,14 +64,21 @@ impl Generator<'_> {
                 }
             }
             ExprKind::Literal(literal) => match literal {
-                LiteralKind::Integer(i) => self.emit_constant(Value { integer: *i }),
-                LiteralKind::Real(f) => self.emit_constant(Value { real: *f }),
-                LiteralKind::Boolean(b) => self.emit_constant(Value { boolean: *b }),
+                LiteralKind::Integer(i) => {
+                    self.emit_constant(Value { integer: *i }, ValueKind::Integer)
+                }
+                LiteralKind::Real(f) => self.emit_constant(Value { real: *f }, ValueKind::Real),
+                LiteralKind::Boolean(b) => {
+                    self.emit_constant(Value { boolean: *b }, ValueKind::Boolean)
+                }
                 LiteralKind::String(string) => {
                     let obj = allocate_string(self.vm, string.clone());
-                    self.emit_constant(Value { obj });
+                    self.emit_constant(Value { obj }, ValueKind::Obj);
                 }
-                LiteralKind::Character(ch) => self.emit_constant(Value { char: *ch }),
+                LiteralKind::Character(ch) => {
+                    self.emit_constant(Value { char: *ch }, ValueKind::Char)
+                }
+                LiteralKind::Null => self.emit(Instr::Null),
             },
             ExprKind::Call { callee, args } => {
                 let function_idx = self.resolve_global(callee);
@@ -65,11 +86,12 @@ impl Generator<'_> {
                 args.iter().for_each(|arg| self.expr(arg));
                 self.emit(Instr::Call(args.len()));
             }
-            ExprKind::Variable(name) => {
+            ExprKind::Variable { name, .. } => {
                 if let Some(arg) = self.resolve_local(name) {
                     self.emit(Instr::LoadLocal(arg));
                 } else {
-                    self.emit(Instr::LoadGlobal(self.resolve_global(name)));
+                    let idx = self.resolve_global(name);
+                    self.emit(Instr::LoadGlobal(idx));
                 }
             }
             ExprKind::Assignment { target, value } => {
@@ -77,7 +99,68 @@ impl Generator<'_> {
                 if let Some(arg) = self.resolve_local(target) {
                     self.emit(Instr::StoreLocal(arg));
                 } else {
-                    self.emit(Instr::StoreGlobal(self.resolve_global(target)));
+                    let idx = self.resolve_global(target);
+                    self.emit(Instr::StoreGlobal(idx));
+                }
+            }
+            ExprKind::Deref { pointer } => {
+                self.expr(pointer);
+                self.emit(Instr::Deref);
+            }
+            ExprKind::DerefAssignment { pointer, value } => {
+                self.expr(pointer);
+                self.expr(value);
+                self.emit(Instr::StoreDeref);
+            }
+            ExprKind::AddressOf { name, .. } => {
+                if let Some(arg) = self.resolve_local(name) {
+                    self.emit(Instr::AddrLocal(arg));
+                } else {
+                    let idx = self.resolve_global(name);
+                    self.emit(Instr::AddrGlobal(idx));
+                }
+            }
+            ExprKind::New { pointee } => {
+                let (value, kind) = self.vm.default_value(*pointee);
+                self.emit_constant(value, kind);
+                self.emit(Instr::Alloc(*pointee));
+            }
+            ExprKind::Input => self.emit(Instr::Input(expr.pseudo_type)),
+            ExprKind::Field { object, index } => {
+                self.expr(object);
+                self.emit(Instr::GetField(*index));
+            }
+            ExprKind::FieldAssignment {
+                object,
+                index,
+                value,
+            } => {
+                self.expr(object);
+                self.expr(value);
+                self.emit(Instr::SetField(*index));
+            }
+            ExprKind::MethodCall {
+                object,
+                method,
+                args,
+            } => self.method_call(object, method, args),
+            ExprKind::Construct {
+                class,
+                constructor,
+                args,
+            } => {
+                let class = Value {
+                    obj: self.classes[class],
+                };
+                let class_idx = self.target().add_constant(class, ValueKind::Obj);
+                self.emit(Instr::Instantiate(class_idx));
+                // The constructor is called on a copy, leaving the object once it returns.
+                if let Some(constructor) = constructor {
+                    self.emit(Instr::Dup);
+                    self.emit(Instr::Method(constructor.slot));
+                    args.iter().for_each(|arg| self.expr(arg));
+                    self.emit(Instr::Call(args.len() + 1));
+                    self.emit(Instr::Pop);
                 }
             }
             ExprKind::Logical { lhs, op, rhs } => {
@@ -108,4 +191,13 @@ impl Generator<'_> {
             }
         }
     }
+
+    /// Calls the version of `method` for the class of `object`, which is passed to it as
+    /// the first argument.
+    pub fn method_call(&mut self, object: &Expr, method: &MethodRef, args: &[Expr]) {
+        self.expr(object);
+        self.emit(Instr::Method(method.slot));
+        args.iter().for_each(|arg| self.expr(arg));
+        self.emit(Instr::Call(args.len() + 1));
+    }
 }
@@ -1,11 +1,42 @@
 use super::Generator;
+use crate::{
+    error::CodegenError,
+    ir::hlir::Type,
+    vm::value::{Value, ValueKind},
+};
 
 impl Generator<'_> {
-    pub fn declare_global(&mut self, name: String,) {
-        self.globals.insert(name, self.globals.len());
+    /// Reserves a slot in the script's globals table for a global, holding the default
+    /// value of `pseudo_type` until the global is first stored to. PROCEDUREs and
+    /// FUNCTIONs are declared without a type and start out as NULL.
+    pub fn declare_global(&mut self, name: String, pseudo_type: Option<Type>) {
+        let (value, kind) = match pseudo_type {
+            Some(pseudo_type) => self.vm.default_value(pseudo_type),
+            None => (
+                Value {
+                    obj: std::ptr::null_mut(),
+                },
+                ValueKind::Obj,
+            ),
+        };
+        let idx = self.target().add_global(name.clone(), value, kind, pseudo_type);
+        self.globals.insert(name, idx);
     }
 
-    pub fn resolve_global(&self, name: &str) -> usize {
-        *self.globals.get(name).unwrap()
+    /// The slot of the global `name`. A name that was never declared is recorded as the
+    /// error `emit` returns, and slot 0 is used in its place.
+    pub fn resolve_global(&mut self, name: &str) -> usize {
+        match self.globals.get(name) {
+            Some(idx) => *idx,
+            None => {
+                if self.error.is_none() {
+                    self.error = Some(CodegenError {
+                        msg: format!("Undefined global `{}`.", name),
+                        line: self.line,
+                    });
+                }
+                0
+            }
+        }
     }
 }
@@ -1,9 +1,15 @@
 use super::Generator;
-use crate::vm::instr::Instr;
+use crate::{
+    ir::hlir::Type,
+    vm::{chunk::LocalInfo, instr::Instr},
+};
 
 pub struct Local {
     name: String,
     depth: u8,
+    pseudo_type: Type,
+    /// Index of the first instruction at which the local's slot is initialised.
+    start: usize,
 }
 
 impl Generator<'_> {
@@ -18,15 +24,38 @@ impl Generator<'_> {
             .last()
             .is_some_and(|local| local.depth > self.scope_depth)
         {
+            self.close_local();
             self.emit(Instr::Pop);
-            self.locals.pop();
         }
     }
 
-    pub fn add_local(&mut self, name: String) {
+    /// Records debug info for every local still in scope at the end of a function.
+    pub fn close_locals(&mut self) {
+        while !self.locals.is_empty() {
+            self.close_local();
+        }
+    }
+
+    fn close_local(&mut self) {
+        let local = self.locals.pop().unwrap();
+        let slot = self.locals.len();
+        let end = self.target().instructions.len();
+        self.target().locals.push(LocalInfo {
+            name: local.name,
+            slot,
+            pseudo_type: local.pseudo_type,
+            start: local.start,
+            end,
+        });
+    }
+
+    pub fn add_local(&mut self, name: String, pseudo_type: Type) {
+        let start = self.target().instructions.len();
         self.locals.push(Local {
             name,
             depth: self.scope_depth,
+            pseudo_type,
+            start,
         });
     }
 
@@ -7,12 +7,13 @@ mod global;
 use std::collections::HashMap;
 
 use self::local::Local;
-use crate::ir::hlir::Decl;
+use crate::error::CodegenError;
+use crate::ir::hlir::{Decl, Type};
 use crate::vm::Vm;
 use crate::vm::chunk::Chunk;
 use crate::vm::instr::Instr;
 use crate::vm::obj::{ObjFn, Obj, ObjKind};
-use crate::vm::value::Value;
+use crate::vm::value::{Value, ValueKind};
 
 struct Generator<'a> {
     function: Option<ObjFn>,
@@ -20,14 +21,23 @@ struct Generator<'a> {
     vm: &'a mut Vm,
     scope_depth: u8,
     locals: Vec<Local>,
+    /// Slot in the script's globals table of each global, by name.
     globals: HashMap<String, usize>,
+    /// The first error found, returned by `emit` once generation finishes.
+    error: Option<CodegenError>,
+    /// The `ObjClass` of each CLASS, by name.
+    classes: HashMap<String, *mut Obj>,
+    /// Source line of the statement currently being emitted.
+    line: usize,
 }
 
-pub fn emit<'a>(program: Vec<Decl>, vm: &'a mut Vm) -> ObjFn {
+pub fn emit<'a>(program: Vec<Decl>, vm: &'a mut Vm) -> Result<ObjFn, CodegenError> {
     let script = ObjFn {
         obj: Obj { kind: ObjKind::Fn, next: std::ptr::null_mut() },
         chunk: Chunk::new(),
         name: std::ptr::null_mut(), // todo: add a name to top-level function.
+        arity: 0,
+        ret_type: Type::Null,
     };
 
     let mut generator = Generator {
@@ -37,21 +47,42 @@ pub fn emit<'a>(program: Vec<Decl>, vm: &'a mut Vm) -> ObjFn {
         scope_depth: 0,
         locals: Vec::new(),
         globals: HashMap::new(),
+        error: None,
+        classes: HashMap::new(),
+        line: 0,
     };
 
     // decalre each declaration
     program.iter().for_each(|decl| generator.define_decl(decl));     
+    // then make each CLASS, whose table of methods refers to the globals of their methods.
+    program.iter().for_each(|decl| generator.define_class(decl));
 
-    // emit bytecode for each declaration
-    program.iter().for_each(|decl| generator.decl(decl)); 
+    // emit bytecode for each PROCEDURE/FUNCTION, then for the top-level statements
+    program
+        .iter()
+        .filter(|decl| !matches!(decl, Decl::Stmt(_)))
+        .for_each(|decl| generator.decl(decl));
+    program
+        .iter()
+        .filter(|decl| matches!(decl, Decl::Stmt(_)))
+        .for_each(|decl| generator.decl(decl));
 
-    // Call main procedure at top-level script.
-    let main_procedure = generator.resolve_global("Main");
-    generator.emit(Instr::LoadGlobal(main_procedure));
-    generator.emit(Instr::Call(0)); // todo: allow args passed to main proc
+    // Call the main procedure, if there is one, after the top-level statements.
+    if let Some(Decl::Procedure { body, .. }) = program
+        .iter()
+        .find(|decl| matches!(decl, Decl::Procedure { name, .. } if name == "Main"))
+    {
+        generator.line = body.line;
+        let main_procedure = generator.resolve_global("Main");
+        generator.emit(Instr::LoadGlobal(main_procedure));
+        generator.emit(Instr::Call(0)); // todo: allow args passed to main proc
+        generator.emit(Instr::Pop);
+    }
 
-    let script = generator.function.unwrap();
-    script
+    match generator.error {
+        Some(error) => Err(error),
+        None => Ok(generator.function.unwrap()),
+    }
 }
 
 impl Generator<'_> {
@@ -63,11 +94,12 @@ impl Generator<'_> {
     }
 
     fn emit(&mut self, instr: Instr) {
-        self.target().instructions.push(instr);
+        let line = self.line;
+        self.target().write(instr, line);
     }
 
-    fn emit_constant(&mut self, value: Value) {
-        let instr = Instr::Const(self.target().add_constant(value));
+    fn emit_constant(&mut self, value: Value, kind: ValueKind) {
+        let instr = Instr::Const(self.target().add_constant(value, kind));
         self.emit(instr);
     }
 }
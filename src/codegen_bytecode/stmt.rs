@@ -1,10 +1,17 @@
 use super::Generator;
-use crate::{ir::hlir::Stmt, vm::instr::Instr};
+use crate::{ir::hlir::{Stmt, StmtKind, Type}, vm::instr::Instr};
 
 impl Generator<'_> {
     pub fn stmt(&mut self, stmt: &Stmt) {
-        match stmt {
-            Stmt::Output(exprs) => {
+        self.line = stmt.line;
+        // Global DECLAREs emit no code, their slot is reserved by `declare_global`.
+        let is_global_decl = self.scope_depth == 0 && matches!(stmt.stmt_kind, StmtKind::VarDecl { .. });
+        if !matches!(stmt.stmt_kind, StmtKind::Block(_)) && !is_global_decl {
+            let start = self.target().instructions.len();
+            self.target().stmt_starts.push(start);
+        }
+        match &stmt.stmt_kind {
+            StmtKind::Output(exprs) => {
                 exprs.iter().for_each(|expr| {
                     self.expr(expr);
                     self.emit(Instr::Output(expr.pseudo_type));
@@ -12,7 +19,7 @@ impl Generator<'_> {
                 });
                 self.emit(Instr::OutputLn);
             }
-            Stmt::If {
+            StmtKind::If {
                 condition,
                 then_branch,
                 else_branch,
@@ -28,12 +35,14 @@ impl Generator<'_> {
 
                 let jmp_else_idx = self.target().instructions.len();
                 self.emit(Instr::Jump(0));
-                // todo: ELSE branching
 
                 // patch jump instruction (now that its known where to jump to)
                 self.target().instructions[jmp_false_idx] =
                     Instr::JumpFalse(self.target().instructions.len());
 
+                // pop the condition left on the stack by the false branch.
+                self.emit(Instr::Pop);
+
                 if let Some(else_branch) = else_branch {
                     self.stmt(&else_branch);
                 }
@@ -41,36 +50,105 @@ impl Generator<'_> {
                 self.target().instructions[jmp_else_idx] =
                     Instr::Jump(self.target().instructions.len());
             }
-            Stmt::Expr(expr) => {
+            StmtKind::Case {
+                selector,
+                arms,
+                otherwise,
+            } => {
+                // The selector stays on the stack while the values are compared with it, and
+                // is popped before running an arm so locals keep their slots.
+                self.expr(selector);
+                let pseudo_type = selector.pseudo_type;
+                let mut jmp_end_idxs = Vec::new();
+                for arm in arms.iter() {
+                    let mut jmp_false_idxs = Vec::new();
+                    self.emit(Instr::Dup);
+                    self.expr(&arm.value);
+                    match &arm.to {
+                        Some(to) => {
+                            self.emit(Instr::GtEq(pseudo_type));
+                            jmp_false_idxs.push(self.target().instructions.len());
+                            self.emit(Instr::JumpFalse(0));
+                            self.emit(Instr::Pop);
+                            self.emit(Instr::Dup);
+                            self.expr(to);
+                            self.emit(Instr::Gt(pseudo_type));
+                            self.emit(Instr::Not);
+                        }
+                        None => self.emit(Instr::Eq(pseudo_type)),
+                    }
+                    jmp_false_idxs.push(self.target().instructions.len());
+                    self.emit(Instr::JumpFalse(0));
+
+                    self.emit(Instr::Pop);
+                    self.emit(Instr::Pop);
+                    self.stmt(&arm.body);
+                    jmp_end_idxs.push(self.target().instructions.len());
+                    self.emit(Instr::Jump(0));
+
+                    let next_arm_idx = self.target().instructions.len();
+                    for idx in jmp_false_idxs {
+                        self.target().instructions[idx] = Instr::JumpFalse(next_arm_idx);
+                    }
+                    // pop the comparison left on the stack when the arm doesn't match.
+                    self.emit(Instr::Pop);
+                }
+
+                self.emit(Instr::Pop);
+                if let Some(otherwise) = otherwise {
+                    self.stmt(otherwise);
+                }
+                let end_idx = self.target().instructions.len();
+                for idx in jmp_end_idxs {
+                    self.target().instructions[idx] = Instr::Jump(end_idx);
+                }
+            }
+            StmtKind::Try { body, handler } => {
+                let try_idx = self.target().instructions.len();
+                self.emit(Instr::Try(0));
+                self.stmt(body);
+                self.emit(Instr::EndTry);
+
+                let jmp_end_idx = self.target().instructions.len();
+                self.emit(Instr::Jump(0));
+
+                // A runtime error in the TRY block continues here, after the VM has
+                // unwound the stack back to its height at the `Try`.
+                self.target().instructions[try_idx] = Instr::Try(self.target().instructions.len());
+                self.stmt(handler);
+
+                self.target().instructions[jmp_end_idx] =
+                    Instr::Jump(self.target().instructions.len());
+            }
+            StmtKind::Expr(expr) => {
                 self.expr(expr);
                 self.emit(Instr::Pop);
             }
-            Stmt::Call { name, args } => {
+            StmtKind::Call { name, args } => {
                 let procedure_idx = self.resolve_global(name);
                 self.emit(Instr::LoadGlobal(procedure_idx));
                 args.iter().for_each(|arg| self.expr(arg));
                 self.emit(Instr::Call(args.len()));
                 self.emit(Instr::Pop); // pop null value which is returned by procedure.
             },
-            Stmt::Return(expr) => {
+            StmtKind::CallMethod {
+                object,
+                method,
+                args,
+            } => {
+                self.method_call(object, method, args);
+                self.emit(Instr::Pop);
+            }
+            StmtKind::Return(expr) => {
                 self.expr(expr);
                 self.emit(Instr::Ret(self.current_function_args.unwrap()));
             }
-            Stmt::Input(holder) => {
-                self.emit(Instr::Input);
-                if let Some(var_idx) = self.resolve_local(holder) {
-                    self.emit(Instr::StoreLocal(var_idx));
-                } else {
-                    self.emit(Instr::StoreGlobal(self.resolve_global(holder)));
-                }
-                self.emit(Instr::Pop);
-            }
-            Stmt::Block(stmts) => {
+            StmtKind::Block(stmts) => {
                 self.enter_scope();
                 stmts.into_iter().for_each(|stmt| self.stmt(stmt));
                 self.exit_scope();
             }
-            Stmt::While { body, condition } => {
+            StmtKind::While { body, condition } => {
                 let loop_start_idx = self.target().instructions.len();
                 self.expr(condition);
                 let conditional_jmp_idx = self.target().instructions.len();
@@ -80,8 +158,9 @@ impl Generator<'_> {
                 self.emit(Instr::Jump(loop_start_idx));
                 self.target().instructions[conditional_jmp_idx] =
                     Instr::JumpFalse(self.target().instructions.len());
+                self.emit(Instr::Pop);
             }
-            Stmt::Repeat { body, until } => {
+            StmtKind::Repeat { body, until } => {
                 // Skip pop instruction on first iteration.
                 let jmp_idx = self.target().instructions.len();
                 self.emit(Instr::Jump(0));
@@ -94,10 +173,37 @@ impl Generator<'_> {
                 self.stmt(&body);
                 self.expr(until);
                 self.emit(Instr::JumpFalse(loop_start_idx));
+                self.emit(Instr::Pop);
+            }
+            StmtKind::VarDecl { .. } if is_global_decl => (),
+            StmtKind::VarDecl { name, pseudo_type } => {
+                // Like globals, a local holds a value of its type from the start.
+                let (value, kind) = self.vm.default_value(*pseudo_type);
+                self.emit_constant(value, kind);
+                self.add_local(name.clone(), *pseudo_type);
             }
-            Stmt::VarDecl { name } => {
-                self.emit(Instr::Null);
-                self.add_local(name.clone());
+            StmtKind::Define {
+                name,
+                pseudo_type,
+                values,
+            } => {
+                let element_type = match pseudo_type {
+                    Type::Set(element_type) => **element_type,
+                    _ => unreachable!("DEFINE is only type checked with a set type"),
+                };
+                self.emit(Instr::EmptySet(element_type));
+                for value in values.iter() {
+                    self.expr(value);
+                    self.emit(Instr::SetInsert);
+                }
+                match self.scope_depth {
+                    0 => {
+                        let idx = self.resolve_global(name);
+                        self.emit(Instr::StoreGlobal(idx));
+                        self.emit(Instr::Pop);
+                    }
+                    _ => self.add_local(name.clone(), *pseudo_type),
+                }
             }
         }
     }
@@ -1,5 +1,5 @@
 use crate::codegen_c::{identifier, Generator};
-use crate::ir::ast::{Decl, Param};
+use crate::ir::ast::{Decl, Param, StmtKind};
 
 impl Generator {
     pub fn decl(&mut self, decl: Decl) {
@@ -11,7 +11,22 @@ impl Generator {
                 self.target.push(')');
                 self.stmt(&body);
             },
-            Decl::Function { .. } => unimplemented!()
+            Decl::Function { .. } => unimplemented!(),
+            Decl::Type { name, type_name, line } => {
+                self.line = line;
+                self.target.push_str("typedef ");
+                self.type_name(&type_name);
+                self.target.push_str(&format!("{};", identifier(&name)));
+            }
+            Decl::Class { line, .. } => {
+                self.line = line;
+                self.unsupported("CLASSes");
+            }
+            Decl::Stmt(stmt) => match stmt.stmt_kind {
+                // Global variables become file scope C variables.
+                StmtKind::VarDecl { .. } | StmtKind::Define { .. } => self.stmt(&stmt),
+                _ => unimplemented!(),
+            },
         }
     }
 
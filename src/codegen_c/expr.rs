@@ -1,19 +1,34 @@
 use crate::codegen_c::identifier;
 use crate::codegen_c::Generator;
 use crate::ir::ast::{ExprKind, LiteralKind};
-use crate::lexer::token::{KeywordKind, TokenKind};
+use crate::lexer::token::{KeywordKind, Token, TokenKind};
 
 impl Generator {
     pub fn expr(&mut self, expr: &ExprKind) {
         match expr {
             ExprKind::Binary { lhs, op, rhs } => {
+                // Arithmetic that can overflow goes through the checks in `RUNTIME`.
+                let checked = match &op.kind {
+                    TokenKind::Plus => Some("PSEUDO_ARITHMETIC(+, pseudo_add, "),
+                    TokenKind::Minus => Some("PSEUDO_ARITHMETIC(-, pseudo_sub, "),
+                    TokenKind::Star => Some("PSEUDO_ARITHMETIC(*, pseudo_mul, "),
+                    TokenKind::Keyword(KeywordKind::Div) => Some("pseudo_div("),
+                    TokenKind::Keyword(KeywordKind::Mod) => Some("pseudo_mod("),
+                    _ => None,
+                };
+                if let Some(checked) = checked {
+                    self.target.push_str(checked);
+                    self.expr(lhs);
+                    self.target.push_str(", ");
+                    self.expr(rhs);
+                    self.target.push_str(&format!(", {}) ", op.line + 1));
+                    return;
+                }
                 self.expr(lhs);
                 match &op.kind {
+                    TokenKind::Keyword(KeywordKind::In) => self.unsupported("SETs"),
                     TokenKind::Keyword(_) => todo!(),
-                    TokenKind::Plus => self.target.push_str("+ "),
-                    TokenKind::Minus => self.target.push_str("- "),
                     TokenKind::Slash => self.target.push_str("/ "),
-                    TokenKind::Star => self.target.push_str("* "),
                     TokenKind::Less => self.target.push_str("< "),
                     TokenKind::Greater => self.target.push_str("> "),
                     TokenKind::LessEqual => self.target.push_str("<= "),
@@ -38,20 +53,22 @@ impl Generator {
             }
             ExprKind::Unary { op, expr } => {
                 match op.kind {
-                    TokenKind::Minus => self.target.push_str("-"),
+                    TokenKind::Minus => self.target.push_str("PSEUDO_NEG("),
                     _ => unreachable!(),
                 }
                 self.expr(expr);
+                self.target.push_str(&format!(", {}) ", op.line + 1));
             }
             ExprKind::Assignment { target, value } => {
-                self.target.push_str(target);
+                self.target.push_str(&identifier(target));
                 self.target.push_str("= ");
                 self.expr(value);
             }
             ExprKind::Call { .. } => unimplemented!(),
             ExprKind::Literal(literal) => match literal {
                 LiteralKind::Integer(i) => self.target.push_str(&format!("{} ", i.to_string())),
-                LiteralKind::Real(_) => unimplemented!(),
+                // Rust's `{:?}` keeps a `.0` on whole numbers, so the literal is a double.
+                LiteralKind::Real(real) => self.target.push_str(&format!("{:?} ", real)),
                 LiteralKind::Character(ch) => self.target.push_str(&format!("(char)'{}'", *ch)),
                 LiteralKind::String(_) => todo!(),
                 LiteralKind::Boolean(boolean) => {
@@ -61,8 +78,34 @@ impl Generator {
                         false => self.target.push_str("false"),
                     };
                 }
+                LiteralKind::Null => self.target.push_str("NULL "),
             },
-            ExprKind::Variable(name) => self.target.push_str(&format!("{} ", identifier(name))),
+            ExprKind::Variable { name, .. } => self.target.push_str(&format!("{} ", identifier(name))),
+            ExprKind::Deref { pointer, op } => self.deref(pointer, op),
+            ExprKind::DerefAssignment { pointer, op, value } => {
+                self.deref(pointer, op);
+                self.target.push_str("= ");
+                self.expr(value);
+            }
+            ExprKind::AddressOf { name, .. } => self.target.push_str(&format!("&{} ", identifier(name))),
+            ExprKind::New { args, .. } if !args.is_empty() => self.unsupported("CLASSes"),
+            ExprKind::New { type_name, .. } => {
+                self.target.push('(');
+                self.type_name(type_name);
+                self.target.pop();
+                self.target.push_str("*)pseudo_new(sizeof(");
+                self.type_name(type_name);
+                self.target.push_str(")) ");
+            }
+            ExprKind::Field { .. } | ExprKind::FieldAssignment { .. } | ExprKind::Super { .. } => {
+                self.unsupported("CLASSes")
+            }
         }
     }
+
+    fn deref(&mut self, pointer: &ExprKind, op: &Token) {
+        self.target.push_str("PSEUDO_DEREF(");
+        self.expr(pointer);
+        self.target.push_str(&format!(", {}) ", op.line + 1));
+    }
 }
@@ -2,35 +2,172 @@ mod decl;
 mod stmt;
 mod type_name;
 mod expr;
+#[cfg(test)]
+mod tests;
 
+use crate::error::CodegenError;
 use crate::ir::ast::Decl;
 
 struct Generator {
     target: String,
+    /// The first error found, returned by `generate` once generation finishes.
+    error: Option<CodegenError>,
+    /// Source line of the statement currently being generated.
+    line: usize,
 }
 
-pub fn generate(decls: Vec<Decl>) -> String {
+/// INTEGERs are 64-bit and, as in the VM, arithmetic that overflows them stops the
+/// program unless `pseudo_wrapping` is set. REALs skip the checks through `_Generic`.
+/// Dereferencing NULL stops the program too. OUTPUT prints values as the VM does, REALs
+/// through `pseudo_real_to_str`, which matches `vm::value::real_to_str`.
+const RUNTIME: &str = r#"#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+#include <math.h>
+static inline void pseudo_error(const char *msg, int line) {
+    printf("runtime error on line %d: %s\n", line, msg);
+    exit(1);
+}
+static inline int64_t pseudo_checked(bool overflowed, int64_t result, int line) {
+    if (overflowed && !pseudo_wrapping) pseudo_error("INTEGER overflow.", line);
+    return result;
+}
+static inline int64_t pseudo_add(int64_t a, int64_t b, int line) {
+    int64_t result;
+    bool overflowed = __builtin_add_overflow(a, b, &result);
+    return pseudo_checked(overflowed, result, line);
+}
+static inline int64_t pseudo_sub(int64_t a, int64_t b, int line) {
+    int64_t result;
+    bool overflowed = __builtin_sub_overflow(a, b, &result);
+    return pseudo_checked(overflowed, result, line);
+}
+static inline int64_t pseudo_mul(int64_t a, int64_t b, int line) {
+    int64_t result;
+    bool overflowed = __builtin_mul_overflow(a, b, &result);
+    return pseudo_checked(overflowed, result, line);
+}
+static inline int64_t pseudo_neg(int64_t a, int line) {
+    int64_t result;
+    bool overflowed = __builtin_sub_overflow((int64_t)0, a, &result);
+    return pseudo_checked(overflowed, result, line);
+}
+static inline int64_t pseudo_div(int64_t a, int64_t b, int line) {
+    if (b == 0) pseudo_error("Division by zero.", line);
+    if (a == INT64_MIN && b == -1) return pseudo_checked(true, INT64_MIN, line);
+    return a / b;
+}
+static inline int64_t pseudo_mod(int64_t a, int64_t b, int line) {
+    if (b == 0) pseudo_error("Division by zero.", line);
+    return b == -1 ? 0 : a % b;
+}
+static inline void *pseudo_non_null(void *pointer, int line) {
+    if (pointer == NULL) pseudo_error("Dereferencing a NULL pointer.", line);
+    return pointer;
+}
+static inline void *pseudo_new(size_t size) {
+    void *pointer = calloc(1, size);
+    if (pointer == NULL) pseudo_error("Out of memory.", 0);
+    return pointer;
+}
+#define PSEUDO_DEREF(pointer, line) (*(__typeof__(pointer))pseudo_non_null((pointer), (line)))
+#define PSEUDO_ARITHMETIC(op, checked, a, b, line) _Generic((a) op (b), \
+    float: (a) op (b), \
+    double: (a) op (b), \
+    default: checked((a), (b), (line)))
+#define PSEUDO_NEG(a, line) _Generic((a), \
+    float: -(a), \
+    double: -(a), \
+    default: pseudo_neg((a), (line)))
+/* Room for the longest REAL: a sign, "0.", 323 zeros and 17 digits, and the terminator. */
+#define PSEUDO_REAL_LEN 352
+/* The fewest digits that read back as `real`, never in scientific notation and with a
+   `.0` on whole numbers. */
+static void pseudo_real_to_str(double real, char *out) {
+    if (isnan(real)) {
+        strcpy(out, "NaN");
+        return;
+    }
+    if (isinf(real)) {
+        strcpy(out, real < 0 ? "-inf" : "inf");
+        return;
+    }
+    char scientific[32];
+    for (int precision = 0; precision < 17; precision++) {
+        snprintf(scientific, sizeof scientific, "%.*e", precision, real);
+        if (strtod(scientific, NULL) == real) break;
+    }
+    /* Split `-d.ddde+xx` into its digits and exponent. */
+    char digits[18];
+    int len = 0;
+    const char *c = scientific;
+    if (*c == '-') *out++ = *c++;
+    for (; *c != 'e'; c++) {
+        if (*c != '.') digits[len++] = *c;
+    }
+    int exponent = atoi(c + 1);
+    if (exponent < 0) {
+        *out++ = '0';
+        *out++ = '.';
+        for (int i = -1; i > exponent; i--) *out++ = '0';
+        for (int i = 0; i < len; i++) *out++ = digits[i];
+    } else {
+        for (int i = 0; i <= exponent; i++) *out++ = i < len ? digits[i] : '0';
+        *out++ = '.';
+        if (len <= exponent + 1) *out++ = '0';
+        for (int i = exponent + 1; i < len; i++) *out++ = digits[i];
+    }
+    *out = '\0';
+}
+static inline void pseudo_output_real(double real) {
+    char text[PSEUDO_REAL_LEN];
+    pseudo_real_to_str(real, text);
+    printf("%s ", text);
+}
+/* Each value is followed by a space, as in the VM. */
+#define PSEUDO_OUTPUT(x) _Generic((x), \
+    char: printf("%c ", (x)), \
+    bool: printf("%s ", (x) ? "TRUE" : "FALSE"), \
+    float: pseudo_output_real((x)), \
+    double: pseudo_output_real((x)), \
+    default: printf("%lld ", (long long)(x)))
+"#;
+
+pub fn generate(decls: Vec<Decl>, wrapping: bool) -> Result<String, CodegenError> {
     let mut generator = Generator {
-        target: String::new()
+        target: String::new(),
+        error: None,
+        line: 0,
     };
 
     generator.target.push_str("#include <stdio.h>\n");
     generator.target.push_str("#include <stdbool.h>\n");
-    generator.target.push_str("#define print(x) _Generic((x), \\\n");
-    generator.target.push_str("    char: printf(\"%c\\n\", (x)), \\\n");
-    generator.target.push_str("    bool: printf(\"%s\\n\", (x) ? \"TRUE\" : \"FALSE\"), \\\n");
-    generator.target.push_str("    int: printf(\"%d\\n\", (x)), \\\n");
-    generator.target.push_str("    long: printf(\"%ld\\n\", (x)), \\\n");
-    generator.target.push_str("    float: printf(\"%f\\n\", (x)), \\\n");
-    generator.target.push_str("    double: printf(\"%lf\\n\", (x)), \\\n");
-    generator.target.push_str("    default: printf(\"Unknown type\\n\") \\\n");
-    generator.target.push_str(")\n");
+    generator
+        .target
+        .push_str(&format!("static const bool pseudo_wrapping = {};\n", wrapping));
+    generator.target.push_str(RUNTIME);
 
     for decl in decls {
         generator.decl(decl);
     }
 
-    generator.target
+    match generator.error {
+        Some(error) => Err(error),
+        None => Ok(generator.target),
+    }
+}
+
+impl Generator {
+    /// Records that `feature`, which C can't be generated for, is used on the current line.
+    /// Only the first is kept, as the error `generate` returns.
+    fn unsupported(&mut self, feature: &str) {
+        if self.error.is_none() {
+            self.error = Some(CodegenError {
+                msg: format!("{} are not supported by the C backend.", feature),
+                line: self.line,
+            });
+        }
+    }
 }
 
 fn identifier(name: &str) -> String {
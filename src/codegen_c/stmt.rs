@@ -1,10 +1,11 @@
 use crate::codegen_c::{Generator, identifier};
-use crate::ir::ast::Stmt;
+use crate::ir::ast::{Stmt, StmtKind};
 
 impl Generator {
     pub fn stmt(&mut self, stmt: &Stmt) {
-        match stmt {
-            Stmt::If { condition, then_branch, else_branch } => {
+        self.line = stmt.line;
+        match &stmt.stmt_kind {
+            StmtKind::If { condition, then_branch, else_branch } => {
                 self.target.push_str("if(");
                 self.expr(condition);
                 self.target.push(')');
@@ -19,20 +20,50 @@ impl Generator {
                 
             }
 
-            Stmt::Repeat { body, until } => {
+            StmtKind::Repeat { body, until } => {
                 self.target.push_str("do ");
                 self.stmt(body);
                 self.target.push_str("while(!(");
                 self.expr(until);
                 self.target.push_str("));");
             },
-            Stmt::While { body, condition } => {
+            StmtKind::While { body, condition } => {
                 self.target.push_str("while(");
                 self.expr(condition);
                 self.target.push(')');
                 self.stmt(body);
             },
-            Stmt::Call { name, args } => {
+            StmtKind::Case { selector, arms, otherwise } => {
+                self.target.push_str("{__typeof__(");
+                self.expr(selector);
+                self.target.push_str(") pseudo_case = ");
+                self.expr(selector);
+                self.target.push(';');
+                for arm in arms {
+                    self.target.push_str("if(pseudo_case");
+                    match &arm.to {
+                        Some(to) => {
+                            self.target.push_str(">=");
+                            self.expr(&arm.value);
+                            self.target.push_str("&&pseudo_case<=");
+                            self.expr(to);
+                        }
+                        None => {
+                            self.target.push_str("==");
+                            self.expr(&arm.value);
+                        }
+                    }
+                    self.target.push(')');
+                    self.stmt(&arm.body);
+                    self.target.push_str("else ");
+                }
+                match otherwise {
+                    Some(otherwise) => self.stmt(otherwise),
+                    None => self.target.push(';'),
+                }
+                self.target.push('}');
+            },
+            StmtKind::Call { name, args } => {
                 self.target.push_str(&identifier(name));
                 self.target.push('(');
                 if args.len() > 0 {
@@ -44,19 +75,29 @@ impl Generator {
                 }
                 self.target.push_str(");");
             },
-            Stmt::Return(_) => unimplemented!(),
-            Stmt::VarDecl { name, type_name } => {
+            StmtKind::Return(_) => unimplemented!(),
+            StmtKind::Define { .. } => self.unsupported("SETs"),
+            StmtKind::CallMethod { .. } => self.unsupported("CLASSes"),
+            StmtKind::Try { .. } => self.unsupported("TRY statements"),
+            StmtKind::VarDecl { name, type_name } => {
                 self.type_name(type_name);
                 self.target.push_str(&identifier(name));
                 self.target.push(';');
             },
-            Stmt::Expr(expr) => {
+            StmtKind::Expr(expr) => {
                 self.expr(expr);
                 self.target.push(';');
             }
-            Stmt::Output(_expr) => unimplemented!(),
-            Stmt::Input(_) => unimplemented!(),
-            Stmt::Block(stmts) => {
+            StmtKind::Output(exprs) => {
+                for expr in exprs {
+                    self.target.push_str("PSEUDO_OUTPUT(");
+                    self.expr(expr);
+                    self.target.push_str(");");
+                }
+                self.target.push_str("printf(\"\\n\");");
+            }
+            StmtKind::Input(_) => unimplemented!(),
+            StmtKind::Block(stmts) => {
                 self.target.push('{');
                 for stmt in stmts {
                     self.stmt(stmt);
@@ -0,0 +1,65 @@
+use super::generate;
+use crate::{error::CodegenError, lexer::Lexer, parser::program};
+
+fn c(src: &str) -> Result<String, CodegenError> {
+    generate(program(Lexer::new(src).peekable()).unwrap(), false)
+}
+
+/// Checks the message and the one-based line of the error reported for `src`.
+fn check_unsupported(src: &str, msg: &str, line: usize) {
+    let error = c(src).unwrap_err();
+    assert_eq!(error.msg, msg);
+    assert_eq!(error.line + 1, line);
+}
+
+#[test]
+fn output() {
+    let src = "PROCEDURE Main
+    OUTPUT 1, 'a'
+ENDPROCEDURE";
+    let c_src = c(src).unwrap();
+    assert!(c_src.ends_with(
+        "void main(void){PSEUDO_OUTPUT(1 );PSEUDO_OUTPUT((char)'a');printf(\"\\n\");}"
+    ));
+}
+
+#[test]
+fn enums() {
+    let src = "
+TYPE Season = (Spring, Summer, Autumn, Winter)";
+    check_unsupported(src, "Enumerated types are not supported by the C backend.", 2);
+}
+
+#[test]
+fn sets() {
+    let src = "TYPE Letters = SET OF CHAR";
+    check_unsupported(src, "SETs are not supported by the C backend.", 1);
+    let src = "
+DEFINE Vowels ('A', 'E', 'I', 'O', 'U') : SET OF CHAR
+";
+    check_unsupported(src, "SETs are not supported by the C backend.", 2);
+}
+
+#[test]
+fn classes() {
+    let src = "
+CLASS Counter
+    PUBLIC Count : INTEGER
+ENDCLASS
+";
+    check_unsupported(src, "CLASSes are not supported by the C backend.", 2);
+}
+
+#[test]
+fn try_except() {
+    let src = "PROCEDURE Main
+    OUTPUT 1
+    TRY
+        OUTPUT 1 DIV 0
+    EXCEPT
+        OUTPUT 0
+    ENDTRY
+ENDPROCEDURE
+";
+    check_unsupported(src, "TRY statements are not supported by the C backend.", 3);
+}
@@ -6,10 +6,10 @@ impl Generator {
         match type_name {
             TypeName::BaseTypeName(base_type_name) => match base_type_name  {
                 BaseTypeName::Integer => {
-                    self.target.push_str("int ");
+                    self.target.push_str("int64_t ");
                 },
                 BaseTypeName::Real => {
-                    self.target.push_str("float ");
+                    self.target.push_str("double ");
                 },
                 BaseTypeName::String => unimplemented!(),
 
@@ -25,6 +25,14 @@ impl Generator {
                     self.target.push_str(&format!("{} ", identifier(name)));
                 },
             }
+            TypeName::Pointer(pointee) => {
+                self.type_name(pointee);
+                self.target.pop(); // remove trailing space after the pointed to type.
+                self.target.push_str("* ");
+            }
+            // OUTPUT prints the names of enumerated values, which C enums don't keep.
+            TypeName::Enum(_) => self.unsupported("Enumerated types"),
+            TypeName::Set(_) => self.unsupported("SETs"),
         }
     }
 }
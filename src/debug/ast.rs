@@ -1,12 +1,12 @@
 use pseudo_rs::{
-    ir::ast::{BaseTypeName, ExprKind, Stmt, TypeName, Decl, LiteralKind},
-    lexer::{token::Token, token::TokenKind, Lexer},
+    ir::ast::{Access, BaseTypeName, ExprKind, MemberKind, Stmt, StmtKind, TypeName, Decl, LiteralKind},
+    lexer::{dialect::Dialect, token::Token, token::TokenKind, Lexer},
     parser::program,
     error,
 };
 
-pub fn print_ast(src: &str) {
-    let program = program(Lexer::new(src).peekable());
+pub fn print_ast(src: &str, dialect: Dialect) {
+    let program = program(Lexer::with_dialect(src, dialect).peekable());
     match program {
         Ok(decls) => decls.iter().for_each(|decl| print_decl(decl)),
         Err(errors) => {
@@ -26,13 +26,37 @@ fn print_decl(decl: &Decl) {
             println!("fn {} ({:?}) -> {:?}", name, params, return_type_name);
             print_stmt(body, 1);
         }
+        Decl::Type { name, type_name, .. } => {
+            print!("type {} = ", name);
+            print_type_name(type_name);
+            println!();
+        }
+        Decl::Class { name, parent, members, .. } => {
+            match parent {
+                Some(parent) => println!("class {} inherits {}", name, parent),
+                None => println!("class {}", name),
+            }
+            for member in members.iter() {
+                print_depth(1);
+                print!("{} ", if member.access == Access::Public { "public" } else { "private" });
+                match &member.kind {
+                    MemberKind::Attribute { name, type_name, .. } => {
+                        print!("{}: ", name);
+                        print_type_name(type_name);
+                        println!();
+                    }
+                    MemberKind::Method(decl) => print_decl(decl),
+                }
+            }
+        }
+        Decl::Stmt(stmt) => print_stmt(stmt, 0),
     }
 }
 
 fn print_stmt(stmt: &Stmt, depth: u32) {
     print_depth(depth);
-    match stmt {
-        Stmt::If {
+    match &stmt.stmt_kind {
+        StmtKind::If {
             condition,
             then_branch,
             else_branch,
@@ -45,46 +69,81 @@ fn print_stmt(stmt: &Stmt, depth: u32) {
             }
         }
 
-        Stmt::Call { name, args } => {
+        StmtKind::Call { name, args } => {
             println!("call {}", name);
             args.iter()
                 .enumerate()
                 .for_each(|(_index, arg)| print_expr(arg, depth + 1));
         }
-        Stmt::Block(stmts) => {
+        StmtKind::CallMethod { object, method, args, .. } => {
+            println!("call method {}", method);
+            print_expr(object, depth + 1);
+            args.iter().for_each(|arg| print_expr(arg, depth + 1));
+        }
+        StmtKind::Block(stmts) => {
             println!("block");
             stmts.iter().for_each(|stmt| print_stmt(stmt, depth + 1));
         }
-        Stmt::While { body, condition } => {
+        StmtKind::While { body, condition } => {
             println!("while");
             print_expr(condition, depth + 1);
             print_stmt(body, depth + 1);
         }
-        Stmt::Repeat { body, until } => {
+        StmtKind::Repeat { body, until } => {
             println!("repeat");
             print_expr(until, depth + 1);
             print_stmt(body, depth + 1);
         }
-        Stmt::VarDecl { name, type_name } => {
+        StmtKind::VarDecl { name, type_name } => {
             print!("var {}: ", name);
             print_type_name(type_name);
             println!();
         }
-        Stmt::Return(expr) => {
+        StmtKind::Define { name, values, type_name } => {
+            print!("define {}: ", name);
+            print_type_name(type_name);
+            println!();
+            values.iter().for_each(|value| print_expr(value, depth + 1));
+        }
+        StmtKind::Case { selector, arms, otherwise } => {
+            println!("case");
+            print_expr(selector, depth + 1);
+            for arm in arms.iter() {
+                print_depth(depth + 1);
+                println!("arm");
+                print_expr(&arm.value, depth + 2);
+                if let Some(to) = &arm.to {
+                    print_expr(to, depth + 2);
+                }
+                print_stmt(&arm.body, depth + 2);
+            }
+            if let Some(otherwise) = otherwise {
+                print_depth(depth + 1);
+                println!("otherwise");
+                print_stmt(otherwise, depth + 2);
+            }
+        }
+        StmtKind::Try { body, handler } => {
+            println!("try");
+            print_stmt(body, depth + 1);
+            print_depth(depth + 1);
+            println!("except");
+            print_stmt(handler, depth + 2);
+        }
+        StmtKind::Return(expr) => {
             println!("return");
             print_expr(expr, depth + 1);
 
         }
-        Stmt::Input(target) => {
+        StmtKind::Input(target) => {
             println!("input");
-            print_depth(depth + 1);
-            print!("{}", target);
+            print_expr(target, depth + 1);
         }
-        Stmt::Expr(expr) => {
+        StmtKind::Expr(expr) => {
             println!("expr stmt");
             print_expr(expr, depth + 1);
         }
-        Stmt::Output(exprs) => {
+        StmtKind::Output(exprs) => {
             println!("output");
             exprs.iter().for_each(|expr| {
                 print_expr(expr, depth + 1);
@@ -127,7 +186,7 @@ fn print_expr(expr: &ExprKind, depth: u32) {
             println!("args:");
             args.iter().for_each(|arg| print_expr(arg, depth + 2));
         }
-        ExprKind::Variable(name) => {
+        ExprKind::Variable { name, .. } => {
             println!("var {}", name);
         }
         ExprKind::Literal(literal) => match literal {
@@ -136,7 +195,36 @@ fn print_expr(expr: &ExprKind, depth: u32) {
             LiteralKind::Character(ch) => println!("'{}'", ch),
             LiteralKind::String(string) => println!("\"{}\"", string),
             LiteralKind::Boolean(b) => println!("{}", b),
+            LiteralKind::Null => println!("null"),
         },
+        ExprKind::Deref { pointer, .. } => {
+            println!("deref");
+            print_expr(pointer, depth + 1);
+        }
+        ExprKind::DerefAssignment { pointer, value, .. } => {
+            println!("x^ <- y");
+            print_expr(pointer, depth + 1);
+            print_expr(value, depth + 1);
+        }
+        ExprKind::AddressOf { name, .. } => {
+            println!("address of {}", name);
+        }
+        ExprKind::New { type_name, args } => {
+            print!("new ");
+            print_type_name(type_name);
+            println!();
+            args.iter().for_each(|arg| print_expr(arg, depth + 1));
+        }
+        ExprKind::Field { object, name, .. } => {
+            println!("field {}", name);
+            print_expr(object, depth + 1);
+        }
+        ExprKind::FieldAssignment { object, name, value, .. } => {
+            println!("x.{} <- y", name);
+            print_expr(object, depth + 1);
+            print_expr(value, depth + 1);
+        }
+        ExprKind::Super { .. } => println!("super"),
     }
 }
 
@@ -155,8 +243,17 @@ fn print_type_name(type_name: &TypeName) {
             BaseTypeName::Char => print!("ch"),
             BaseTypeName::Boolean => print!("bool"),
             BaseTypeName::Date => print!("date"),
-            BaseTypeName::Identifier(_name) => todo!(),
+            BaseTypeName::Identifier(name) => print!("{}", name),
         },
+        TypeName::Pointer(pointee) => {
+            print!("^");
+            print_type_name(pointee);
+        }
+        TypeName::Enum(values) => print!("({})", values.join(", ")),
+        TypeName::Set(element) => {
+            print!("set of ");
+            print_type_name(element);
+        }
     }
 }
 
@@ -169,6 +266,7 @@ fn print_operator(op: &Token) {
                 pseudo_rs::lexer::token::KeywordKind::Or => "or",
                 pseudo_rs::lexer::token::KeywordKind::And => "and",
                 pseudo_rs::lexer::token::KeywordKind::Not => "not",
+                pseudo_rs::lexer::token::KeywordKind::In => "in",
                 _ => unreachable!(),
             },
 
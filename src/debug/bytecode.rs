@@ -1,26 +1,35 @@
 use pseudo_rs::{
     codegen_bytecode::emit,
-    lexer::Lexer,
+    lexer::{dialect::Dialect, Lexer},
     naive_tc,
     parser::program,
-    vm::Vm,
+    vm::{disassembler::disassemble, Vm},
     error
 };
 
-pub fn print_bytecode(src: &str) {
-    emit(
-        naive_tc::typecheck(match program(Lexer::new(src).peekable()) {
-            Ok(decls) => decls,
-            Err(errors) => {
-                error::print_parse_errors(src, errors);
-                std::process::exit(0);
-            }
-        }),
-        &mut Vm::new(),
-    )
-    .chunk
-    .instructions
-    .iter()
-    .enumerate()
-    .for_each(|(idx, instr)| println!("{idx}\t{:?}", instr));
+pub fn print_bytecode(src: &str, dialect: Dialect) {
+    let mut vm = Vm::new();
+    let decls = match program(Lexer::with_dialect(src, dialect).peekable()) {
+        Ok(decls) => decls,
+        Err(errors) => {
+            error::print_parse_errors(src, errors);
+            std::process::exit(0);
+        }
+    };
+    let hlir = match naive_tc::typecheck(decls) {
+        Ok(hlir) => hlir,
+        Err(type_error) => {
+            error::print_type_error(src, type_error);
+            std::process::exit(0);
+        }
+    };
+    let script = match emit(hlir, &mut vm) {
+        Ok(script) => script,
+        Err(error) => {
+            error::print_codegen_error(error);
+            std::process::exit(0);
+        }
+    };
+    print!("{}", disassemble(&script, Some(src)));
+    vm.free_objects();
 }
@@ -0,0 +1,10 @@
+use pseudo_rs::{error, lexer::dialect::Dialect, syntax::parse};
+
+pub fn print_cst(src: &str, dialect: Dialect) {
+    let parse = parse(src, dialect);
+    print!("{}", parse.root.debug_dump());
+    if !parse.errors.is_empty() {
+        println!();
+        error::print_parse_errors(src, parse.errors);
+    }
+}
@@ -1,6 +1,8 @@
 pub mod tokens;
 pub mod ast;
 pub mod bytecode;
+pub mod cst;
 pub use tokens::*;
 pub use ast::*;
 pub use bytecode::*;
+pub use cst::*;
@@ -1,5 +1,5 @@
-use pseudo_rs::lexer::Lexer; 
+use pseudo_rs::lexer::{dialect::Dialect, Lexer};
 
-pub fn print_tokens(src: &str) {
-    Lexer::new(src).for_each(|token| println!("{:?}", token.kind));
+pub fn print_tokens(src: &str, dialect: Dialect) {
+    Lexer::with_dialect(src, dialect).for_each(|token| println!("{:?}", token.kind));
 }
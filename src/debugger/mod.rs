@@ -0,0 +1,321 @@
+//! Interactive step debugger, driven by a `vm::Hook` and the debug info that
+//! `codegen_bytecode` records in each chunk.
+
+use crate::vm::{
+    disassembler::{format_variable, function_name},
+    obj::{ObjFn, ObjKind},
+    value::ValueKind,
+    Frame, Hook, Vm,
+};
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
+
+const HELP: &str = "\
+commands:
+  s, step            run to the next statement, entering calls
+  n, next            run to the next statement in this or an outer call
+  f, finish          run until the current call returns
+  c, continue        run until a breakpoint is hit
+  b, break <line>    set a breakpoint on a line
+  d, delete <line>   remove the breakpoint on a line
+  breakpoints        list breakpoints
+  l, list            show the source around the current line
+  locals             print the variables of the current call
+  globals            print the global variables
+  p, print <name>    print a variable
+  bt, backtrace      show the call stack
+  q, quit            stop the program";
+
+enum StepMode {
+    Step,
+    /// Stop at the next statement whose call depth is at most the given depth.
+    Next(usize),
+    /// Stop at the next statement whose call depth is below the given depth.
+    Finish(usize),
+    Continue,
+}
+
+/// Writes a line to the debugger's output. The debugger has nothing better to do when it
+/// can't, so errors are ignored.
+macro_rules! say {
+    ($debugger:expr, $($arg:tt)*) => {{
+        let _ = writeln!($debugger.output, $($arg)*);
+    }};
+}
+
+/// Reads commands from `input` and writes what they show to `output`, such as stdin and
+/// stdout.
+pub struct Debugger<'a, R: BufRead, W: Write> {
+    src_lines: Vec<&'a str>,
+    /// Lines (zero-based) that start at least one statement.
+    stmt_lines: BTreeSet<usize>,
+    breakpoints: BTreeSet<usize>,
+    mode: StepMode,
+    /// Set by `quit`, which stops the program.
+    quit: bool,
+    input: R,
+    output: W,
+}
+
+impl<'a, R: BufRead, W: Write> Debugger<'a, R, W> {
+    pub fn new(script: &ObjFn, src: &'a str, input: R, output: W) -> Self {
+        let mut stmt_lines = BTreeSet::new();
+        let mut functions = vec![script as *const ObjFn];
+        while let Some(function) = functions.pop() {
+            let chunk = unsafe { &(*function).chunk };
+            for start in chunk.stmt_starts.iter() {
+                stmt_lines.insert(chunk.lines[*start]);
+            }
+            for (value, kind) in chunk.constants.iter().zip(chunk.constant_kinds.iter()) {
+                unsafe {
+                    if *kind == ValueKind::Obj
+                        && !value.obj.is_null()
+                        && matches!((*value.obj).kind, ObjKind::Fn)
+                    {
+                        functions.push(value.obj as *const ObjFn);
+                    }
+                }
+            }
+        }
+
+        Self {
+            src_lines: src.lines().collect(),
+            stmt_lines,
+            breakpoints: BTreeSet::new(),
+            mode: StepMode::Step,
+            quit: false,
+            input,
+            output,
+        }
+    }
+
+    fn should_stop(&self, depth: usize, line: usize) -> bool {
+        if self.breakpoints.contains(&line) {
+            return true;
+        }
+        match self.mode {
+            StepMode::Step => true,
+            StepMode::Next(stop_depth) => depth <= stop_depth,
+            StepMode::Finish(stop_depth) => depth < stop_depth,
+            StepMode::Continue => false,
+        }
+    }
+
+    fn prompt(&mut self, vm: &Vm, frames: &[Frame]) {
+        let depth = frames.len();
+        loop {
+            let _ = write!(self.output, "(debug) ");
+            let _ = self.output.flush();
+
+            let mut command = String::new();
+            match self.input.read_line(&mut command) {
+                Ok(0) | Err(_) => {
+                    // No more commands, let the program run to completion.
+                    self.breakpoints.clear();
+                    self.mode = StepMode::Continue;
+                    return;
+                }
+                Ok(_) => (),
+            }
+            let mut words = command.split_whitespace();
+            let argument = words.clone().nth(1);
+            match words.next() {
+                Some("s") | Some("step") => self.mode = StepMode::Step,
+                Some("n") | Some("next") => self.mode = StepMode::Next(depth),
+                Some("f") | Some("finish") => self.mode = StepMode::Finish(depth),
+                Some("c") | Some("continue") => self.mode = StepMode::Continue,
+                Some("b") | Some("break") => {
+                    self.break_command(argument);
+                    continue;
+                }
+                Some("d") | Some("delete") => {
+                    match argument.and_then(|line| line.parse::<usize>().ok()) {
+                        Some(line) if self.breakpoints.remove(&line.wrapping_sub(1)) => {
+                            say!(self, "Removed breakpoint on line {}.", line)
+                        }
+                        _ => say!(self, "No breakpoint on that line."),
+                    }
+                    continue;
+                }
+                Some("breakpoints") => {
+                    match self.breakpoints.is_empty() {
+                        true => say!(self, "No breakpoints."),
+                        false => self
+                            .breakpoints
+                            .iter()
+                            .for_each(|line| say!(self, "line {}", line + 1)),
+                    }
+                    continue;
+                }
+                Some("l") | Some("list") => {
+                    self.list(frames.last().unwrap());
+                    continue;
+                }
+                Some("locals") => {
+                    let frame = frames.last().unwrap();
+                    let locals = live_locals(frame);
+                    if locals.is_empty() {
+                        say!(self, "No locals.");
+                    }
+                    locals
+                        .into_iter()
+                        .for_each(|(name, value)| say!(self, "{} = {}", name, value));
+                    continue;
+                }
+                Some("globals") => {
+                    let globals = globals(vm, frames);
+                    if globals.is_empty() {
+                        say!(self, "No globals.");
+                    }
+                    globals
+                        .into_iter()
+                        .for_each(|(name, value)| say!(self, "{} = {}", name, value));
+                    continue;
+                }
+                Some("p") | Some("print") => {
+                    match argument {
+                        Some(name) => match lookup(vm, frames, name) {
+                            Some(value) => say!(self, "{} = {}", name, value),
+                            None => say!(self, "No variable named `{}` in scope.", name),
+                        },
+                        None => say!(self, "usage: print <name>"),
+                    }
+                    continue;
+                }
+                Some("bt") | Some("backtrace") => {
+                    frames.iter().rev().enumerate().for_each(|(idx, frame)| {
+                        say!(
+                            self,
+                            "#{} {} line {}",
+                            idx,
+                            function_name(frame.function),
+                            frame.function.chunk.lines[frame.instr_idx] + 1
+                        )
+                    });
+                    continue;
+                }
+                Some("h") | Some("help") => {
+                    say!(self, "{}", HELP);
+                    continue;
+                }
+                Some("q") | Some("quit") => self.quit = true,
+                Some(other) => {
+                    say!(self, "Unknown command `{}`, type `help` for a list.", other);
+                    continue;
+                }
+                None => continue,
+            }
+            return;
+        }
+    }
+
+    fn break_command(&mut self, argument: Option<&str>) {
+        let line = match argument.and_then(|line| line.parse::<usize>().ok()) {
+            Some(line) if line > 0 => line,
+            _ => {
+                say!(self, "usage: break <line>");
+                return;
+            }
+        };
+        // Move the breakpoint forward to the next line that starts a statement.
+        match self.stmt_lines.range(line - 1..).next() {
+            Some(&stmt_line) => {
+                self.breakpoints.insert(stmt_line);
+                say!(self, "Breakpoint set on line {}.", stmt_line + 1);
+            }
+            None => say!(self, "No statement on or after line {}.", line),
+        }
+    }
+
+    fn list(&mut self, frame: &Frame) {
+        let current = frame.function.chunk.lines[frame.instr_idx];
+        let start = current.saturating_sub(3);
+        let end = (current + 4).min(self.src_lines.len());
+        for line in start..end {
+            say!(
+                self,
+                "{} {:>4} | {}",
+                if line == current { ">" } else { " " },
+                line + 1,
+                self.src_lines[line]
+            );
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Hook for Debugger<'_, R, W> {
+    fn before_instr(&mut self, vm: &Vm, instr_idx: usize) {
+        let frames = vm.frames(instr_idx);
+        let chunk = &frames.last().unwrap().function.chunk;
+        if chunk.stmt_starts.binary_search(&instr_idx).is_err() {
+            return;
+        }
+        let line = chunk.lines[instr_idx];
+        if !self.should_stop(frames.len(), line) {
+            return;
+        }
+
+        say!(
+            self,
+            "{} line {}: {}",
+            function_name(frames.last().unwrap().function),
+            line + 1,
+            self.src_lines.get(line).map_or("", |line| line.trim())
+        );
+        self.prompt(vm, &frames);
+    }
+
+    fn stopped(&self) -> bool {
+        self.quit
+    }
+}
+
+/// Names and formatted values of the locals live in `frame`.
+fn live_locals(frame: &Frame) -> Vec<(String, String)> {
+    let chunk = &frame.function.chunk;
+    let mut locals: Vec<_> = chunk
+        .locals
+        .iter()
+        .filter(|local| local.start <= frame.instr_idx && frame.instr_idx < local.end)
+        .filter_map(|local| {
+            frame.slots.get(local.slot).map(|value| {
+                (
+                    local.slot,
+                    local.name.clone(),
+                    format_variable(value, local.pseudo_type),
+                )
+            })
+        })
+        .collect();
+    locals.sort_by_key(|(slot, ..)| *slot);
+    locals
+        .into_iter()
+        .map(|(_, name, value)| (name, value))
+        .collect()
+}
+
+fn globals(vm: &Vm, frames: &[Frame]) -> Vec<(String, String)> {
+    let script = &frames.first().unwrap().function.chunk;
+    script
+        .global_names
+        .iter()
+        .zip(vm.globals().iter())
+        .zip(script.global_types.iter())
+        // PROCEDUREs and FUNCTIONs are stored in globals too, without a type.
+        .filter_map(|((name, value), pseudo_type)| {
+            pseudo_type.map(|pseudo_type| (name.clone(), format_variable(value, pseudo_type)))
+        })
+        .collect()
+}
+
+fn lookup(vm: &Vm, frames: &[Frame], name: &str) -> Option<String> {
+    live_locals(frames.last().unwrap())
+        .into_iter()
+        .rev()
+        .chain(globals(vm, frames))
+        .find(|(local, _)| local == name)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests;
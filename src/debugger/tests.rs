@@ -0,0 +1,113 @@
+use super::Debugger;
+use crate::{
+    codegen_bytecode::emit,
+    lexer::Lexer,
+    naive_tc,
+    parser::program,
+    vm::{ExecuteError, Vm},
+};
+
+const SRC: &str = "DECLARE Total : INTEGER
+PROCEDURE Add(N : INTEGER)
+    DECLARE Twice : INTEGER
+    Twice <- N * 2
+    Total <- Total + Twice
+ENDPROCEDURE
+Total <- 0
+CALL Add(1)
+CALL Add(2)
+OUTPUT Total
+";
+
+/// Runs `src` under the debugger with `commands` as its input, returning what it wrote.
+fn session(src: &str, commands: &str) -> (Result<(), ExecuteError>, String) {
+    let mut vm = Vm::new();
+    let decls = program(Lexer::new(src).peekable()).unwrap();
+    let script = emit(naive_tc::typecheck(decls).unwrap(), &mut vm).unwrap();
+    let mut output = Vec::new();
+    let mut debugger = Debugger::new(&script, src, commands.as_bytes(), &mut output);
+    let result = vm.execute_with_hook(script, &mut debugger);
+    (result, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn breakpoints_print_and_quit() {
+    let commands = "break 4\ncontinue\nlocals\nprint Total\ncontinue\nprint N\nquit\n";
+    let (result, output) = session(SRC, commands);
+    assert!(matches!(result, Err(ExecuteError::Stopped)));
+    assert_eq!(
+        output,
+        "\
+<script> line 7: Total <- 0
+(debug) Breakpoint set on line 4.
+(debug) Add line 4: Twice <- N * 2
+(debug) N = 1
+Twice = 0
+(debug) Total = 0
+(debug) Add line 4: Twice <- N * 2
+(debug) N = 2
+(debug) "
+    );
+}
+
+#[test]
+fn step_and_finish() {
+    let commands = "step\nstep\nstep\nstep\nstep\nstep\nfinish\nbt\nglobals\n";
+    let (result, output) = session(SRC, commands);
+    assert!(result.is_ok());
+    assert_eq!(
+        output,
+        "\
+<script> line 7: Total <- 0
+(debug) <script> line 8: CALL Add(1)
+(debug) Add line 3: DECLARE Twice : INTEGER
+(debug) Add line 4: Twice <- N * 2
+(debug) Add line 5: Total <- Total + Twice
+(debug) <script> line 9: CALL Add(2)
+(debug) Add line 3: DECLARE Twice : INTEGER
+(debug) <script> line 10: OUTPUT Total
+(debug) #0 <script> line 10
+(debug) Total = 6
+(debug) "
+    );
+}
+
+#[test]
+fn next_steps_over_calls() {
+    let (result, output) = session(SRC, "next\nnext\nnext\nbreakpoints\nquit\n");
+    assert!(matches!(result, Err(ExecuteError::Stopped)));
+    assert_eq!(
+        output,
+        "\
+<script> line 7: Total <- 0
+(debug) <script> line 8: CALL Add(1)
+(debug) <script> line 9: CALL Add(2)
+(debug) <script> line 10: OUTPUT Total
+(debug) No breakpoints.
+(debug) "
+    );
+}
+
+#[test]
+fn enumerated_values_by_name() {
+    let src = "TYPE Season = (Spring, Summer, Autumn)
+DECLARE Now : Season
+PROCEDURE Show(Then : Season)
+    OUTPUT Then
+ENDPROCEDURE
+Now <- Autumn
+CALL Show(Summer)
+";
+    let (result, output) = session(src, "break 4\ncontinue\nlocals\nglobals\n");
+    assert!(result.is_ok());
+    assert_eq!(
+        output,
+        "\
+<script> line 6: Now <- Autumn
+(debug) Breakpoint set on line 4.
+(debug) Show line 4: OUTPUT Then
+(debug) Then = Summer
+(debug) Now = Autumn
+(debug) "
+    );
+}
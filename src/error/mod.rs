@@ -1,7 +1,10 @@
-use crate::lexer::token::Token;
+use crate::{lexer::token::Token, vm::Limit};
+use unicode_width::UnicodeWidthChar;
 
 pub type ParseResult<T> = Result<T, ParseError>;
 
+pub type TypeResult<T> = Result<T, TypeError>;
+
 #[derive(Debug)]
 pub struct ParseError {
     pub msg: &'static str,
@@ -26,12 +29,7 @@ fn print_error(src: &str, error: ParseError) {
             println!("{}:{}", line_number, token.col);
             println!("{} |", " ".repeat(line_number_len));
             println!("{} |\t{}", line_number, line);
-            println!(
-                "{} | \t{}{}",
-                " ".repeat(line_number_len),
-                " ".repeat(token.col - 1),
-                "^".repeat(token.len)
-            );
+            println!("{} | \t{}", " ".repeat(line_number_len), underline(line, token));
             println!("error: {}", error.msg);
             println!("got `{:?}`", token)
         }
@@ -39,8 +37,128 @@ fn print_error(src: &str, error: ParseError) {
     }
 }
 
+/// Carets under `token`, to print below its source `line`. Tabs before the token are
+/// kept and other characters are replaced by as many spaces as the terminal draws them
+/// with, so the carets line up with wide characters and tab stops too.
+pub fn underline(line: &str, token: &Token) -> String {
+    let mut chars = line.chars();
+    let mut underline: String = chars
+        .by_ref()
+        .take(token.col.saturating_sub(1))
+        .map(|ch| match ch {
+            '\t' => String::from('\t'),
+            _ => " ".repeat(ch.width().unwrap_or(0)),
+        })
+        .collect();
+    let width: usize = chars.take(token.len).map(|ch| ch.width().unwrap_or(0)).sum();
+    underline.push_str(&"^".repeat(width.max(1)));
+    underline
+}
+
+/// An error found by `naive_tc`. Most of the IR carries no token positions, so often only
+/// the line of the offending statement is known.
+#[derive(Debug)]
+pub struct TypeError {
+    pub msg: String,
+    /// Zero-based source line of the statement the error was found in.
+    pub line: usize,
+    /// The token the error is about, when known.
+    pub token: Option<Token>,
+}
+
+pub fn print_type_error(src: &str, error: TypeError) {
+    match src.lines().nth(error.line) {
+        Some(line) => {
+            let line_number = error.line + 1;
+            let line_number_len = line_number.to_string().len();
+            match &error.token {
+                Some(token) => println!("{}:{}", line_number, token.col),
+                None => println!("{}", line_number),
+            }
+            println!("{} |", " ".repeat(line_number_len));
+            println!("{} |\t{}", line_number, line);
+            match &error.token {
+                Some(token) => {
+                    println!("{} | \t{}", " ".repeat(line_number_len), underline(line, token))
+                }
+                None => println!("{} |", " ".repeat(line_number_len)),
+            }
+            println!("error: {}", error.msg);
+        }
+        None => println!("error: {}", error.msg),
+    }
+}
+
+/// An error that stops a running program, such as INTEGER overflow.
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub msg: String,
+    /// Zero-based source line of the instruction that failed.
+    pub line: usize,
+    /// The bound of the VM's `VmLimits` the program ran into, if that is what stopped it.
+    pub limit: Option<Limit>,
+}
+
+/// A program the type checker accepted that code can't be generated for, such as one
+/// using a global that was never declared, or one using a feature the C backend lacks.
+#[derive(Debug)]
+pub struct CodegenError {
+    pub msg: String,
+    /// Zero-based source line of the statement being generated.
+    pub line: usize,
+}
+
+pub fn print_codegen_error(error: CodegenError) {
+    println!("error on line {}: {}", error.line + 1, error.msg);
+}
+
+/// Prints `error`, quoting the line it happened on when the source is known.
+pub fn print_runtime_error(src: Option<&str>, error: RuntimeError) {
+    let line_number = error.line + 1;
+    match src.and_then(|src| src.lines().nth(error.line)) {
+        Some(line) => {
+            let line_number_len = line_number.to_string().len();
+            println!("{}", line_number);
+            println!("{} |", " ".repeat(line_number_len));
+            println!("{} |\t{}", line_number, line);
+            println!("{} |", " ".repeat(line_number_len));
+            println!("runtime error: {}", error.msg);
+        }
+        None => println!("runtime error on line {}: {}", line_number, error.msg),
+    }
+}
+
 impl ParseError {
     pub fn new(msg: &'static str, token: Option<Token>) -> Self {
         Self { msg, token }
     }
-}
\ No newline at end of file
+}
+
+impl TypeError {
+    pub fn new(msg: impl Into<String>, line: usize) -> Self {
+        Self {
+            msg: msg.into(),
+            line,
+            token: None,
+        }
+    }
+
+    /// An error pointing at `token`.
+    pub fn at(msg: impl Into<String>, token: Token) -> Self {
+        Self {
+            msg: msg.into(),
+            line: token.line,
+            token: Some(token),
+        }
+    }
+}
+
+impl RuntimeError {
+    pub fn new(msg: String, line: usize) -> Self {
+        Self {
+            msg,
+            line,
+            limit: None,
+        }
+    }
+}
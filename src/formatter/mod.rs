@@ -0,0 +1,107 @@
+//! Opinionated source formatter, built on the lossless tree from `syntax` so comments
+//! survive. Blocks are indented by four spaces per level, tokens are separated by single
+//! spaces (none inside brackets, before `,` or between a name and its arguments) and runs
+//! of blank lines are collapsed into one. Keywords are written in upper case and
+//! identifiers as first spelled.
+
+use crate::{
+    error::ParseError,
+    lexer::{
+        dialect::Dialect,
+        token::{KeywordKind, TokenKind::*},
+    },
+    syntax::{parse, NodeKind, SyntaxToken},
+};
+
+const INDENT: &str = "    ";
+
+/// Formats `src`, or returns its syntax errors; source that doesn't parse is left alone.
+pub fn format(src: &str, dialect: Dialect) -> Result<String, Vec<ParseError>> {
+    let parse = parse(src, dialect);
+    if !parse.errors.is_empty() {
+        return Err(parse.errors);
+    }
+
+    let mut out = String::new();
+    let mut line = Vec::new();
+    let mut blank_line = false;
+    for token in parse.root.tokens() {
+        match token.kind() {
+            NewLine => {
+                if line.is_empty() {
+                    // Blank lines at the start of the file are dropped.
+                    blank_line = !out.is_empty();
+                } else {
+                    if blank_line {
+                        out.push('\n');
+                        blank_line = false;
+                    }
+                    write_line(&mut out, &line);
+                    line.clear();
+                }
+            }
+            Whitespace => (),
+            _ => line.push(token),
+        }
+    }
+    if !line.is_empty() {
+        if blank_line {
+            out.push('\n');
+        }
+        write_line(&mut out, &line);
+    }
+    Ok(out)
+}
+
+fn write_line(out: &mut String, line: &[SyntaxToken]) {
+    out.push_str(&INDENT.repeat(indent_level(&line[0])));
+    out.push_str(&spelling(&line[0]));
+    for pair in line.windows(2) {
+        if needs_space(&pair[0], &pair[1]) {
+            out.push(' ');
+        }
+        out.push_str(spelling(&pair[1]).trim_end());
+    }
+    out.push('\n');
+}
+
+/// Keywords in upper case and identifiers as first spelled, which only differ from the
+/// source in `Dialect::Lenient`.
+fn spelling(token: &SyntaxToken) -> String {
+    match token.kind() {
+        Keyword(_) => token.text().to_uppercase(),
+        Identifier(name) => name.clone(),
+        _ => token.text().to_string(),
+    }
+}
+
+/// Number of blocks (and CASE arms) the token is nested in.
+fn indent_level(token: &SyntaxToken) -> usize {
+    let parent = token.parent();
+    std::iter::once(parent.clone())
+        .chain(parent.ancestors())
+        .filter(|node| matches!(node.kind(), NodeKind::Block | NodeKind::CaseArm))
+        .count()
+}
+
+fn needs_space(prev: &SyntaxToken, token: &SyntaxToken) -> bool {
+    match (prev.kind(), token.kind()) {
+        (_, Comment) => true,
+        (OpenParen | OpenSqrBracket | Dot, _) => false,
+        (_, CloseParen | CloseSqrBracket | Comma | Dot) => false,
+        // `DEFINE Vowels ('A', 'E')` isn't a call.
+        (Identifier(_), OpenParen) if token.parent().kind() == NodeKind::Define => true,
+        // Calls and array indexing, including of the constructor NEW.
+        (Identifier(_) | CloseParen | CloseSqrBracket, OpenParen | OpenSqrBracket) => false,
+        (Keyword(KeywordKind::New), OpenParen) => false,
+        (Minus, _) => prev.parent().kind() != NodeKind::Unary,
+        // `P^`, `^INTEGER` and `@Number`.
+        (Caret, _) if prev.parent().kind() == NodeKind::TypeName => false,
+        (_, Caret) => token.parent().kind() != NodeKind::Deref,
+        (At, _) => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,140 @@
+use super::format;
+use crate::{
+    lexer::{dialect::Dialect, Lexer},
+    parser::program,
+};
+
+const MESSY: &str = "
+
+// Sums the doubled numbers.
+DECLARE   Total:INTEGER
+FUNCTION Double ( N : INTEGER ) RETURNS INTEGER
+RETURN N*2 // trailing comment
+  ENDFUNCTION
+
+
+
+Total<-0
+FOR I<-1 TO 3
+        // inside the loop
+ IF Total>=5 AND NOT(I=2) THEN
+Total <- Total + Double(I)
+    ELSE
+  Total <- - Total
+        ENDIF
+NEXT I
+CASE OF Total
+1 : OUTPUT \"one\" , Total
+OUTPUT \"still one\"
+OTHERWISE : OUTPUT \"other\"
+ENDCASE
+REPEAT
+Total<-Total-1
+UNTIL Total<0";
+
+const FORMATTED: &str = "// Sums the doubled numbers.
+DECLARE Total : INTEGER
+FUNCTION Double(N : INTEGER) RETURNS INTEGER
+    RETURN N * 2 // trailing comment
+ENDFUNCTION
+
+Total <- 0
+FOR I <- 1 TO 3
+    // inside the loop
+    IF Total >= 5 AND NOT (I = 2) THEN
+        Total <- Total + Double(I)
+    ELSE
+        Total <- -Total
+    ENDIF
+NEXT I
+CASE OF Total
+    1 : OUTPUT \"one\", Total
+        OUTPUT \"still one\"
+    OTHERWISE : OUTPUT \"other\"
+ENDCASE
+REPEAT
+    Total <- Total - 1
+UNTIL Total < 0
+";
+
+#[test]
+fn formats_messy_source() {
+    assert_eq!(format(MESSY, Dialect::Strict).unwrap(), FORMATTED);
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    assert_eq!(format(FORMATTED, Dialect::Strict).unwrap(), FORMATTED);
+}
+
+#[test]
+fn formatting_keeps_the_program() {
+    let src = "PROCEDURE Main
+DECLARE X:INTEGER
+X<-1
+  WHILE X<10
+X<-X*2
+ ENDWHILE
+OUTPUT X
+ENDPROCEDURE
+";
+    let formatted = format(src, Dialect::Strict).unwrap();
+    let before = program(Lexer::new(src).peekable()).unwrap();
+    let after = program(Lexer::new(&formatted).peekable()).unwrap();
+    // Operator tokens move, so only their columns may differ.
+    assert_eq!(
+        without_columns(&format!("{:?}", before)),
+        without_columns(&format!("{:?}", after))
+    );
+}
+
+fn without_columns(ast: &str) -> String {
+    ast.split("col: ")
+        .enumerate()
+        .map(|(idx, part)| match idx {
+            0 => part,
+            _ => part.trim_start_matches(|ch: char| ch.is_ascii_digit()),
+        })
+        .collect()
+}
+
+#[test]
+fn refuses_invalid_source() {
+    assert!(format("IF X THEN\nOUTPUT \"unterminated\n", Dialect::Strict).is_err());
+}
+
+#[test]
+fn normalises_lenient_spelling() {
+    let src = "declare Total : integer\ntotal <- 1\nwhile TOTAL < 4\ntotal <- total * 2\nendwhile\n";
+    assert_eq!(
+        format(src, Dialect::Lenient).unwrap(),
+        "DECLARE Total : INTEGER\nTotal <- 1\nWHILE Total < 4\n    Total <- Total * 2\nENDWHILE\n"
+    );
+}
+
+#[test]
+fn pointer_spacing() {
+    let src = "TYPE TPtr=^ ^INTEGER\nDECLARE P:TPtr\nP<-NEW ^INTEGER\nP ^ ^<- @ X\n";
+    assert_eq!(
+        format(src, Dialect::Strict).unwrap(),
+        "TYPE TPtr = ^^INTEGER\nDECLARE P : TPtr\nP <- NEW ^INTEGER\nP^^ <- @X\n"
+    );
+}
+
+#[test]
+fn define_spacing() {
+    let src = "DEFINE Vowels('A','E'):SET OF CHAR\nOUTPUT Count(Vowels)\n";
+    assert_eq!(
+        format(src, Dialect::Strict).unwrap(),
+        "DEFINE Vowels ('A', 'E') : SET OF CHAR\nOUTPUT Count(Vowels)\n"
+    );
+}
+
+#[test]
+fn class_spacing() {
+    let src = "CLASS Cat INHERITS Pet\nPUBLIC PROCEDURE NEW (N:STRING)\nSUPER . NEW (N)\nENDPROCEDURE\nENDCLASS\nT <- NEW Cat (\"Tom\")\n";
+    assert_eq!(
+        format(src, Dialect::Strict).unwrap(),
+        "CLASS Cat INHERITS Pet\n    PUBLIC PROCEDURE NEW(N : STRING)\n        SUPER.NEW(N)\n    ENDPROCEDURE\nENDCLASS\nT <- NEW Cat(\"Tom\")\n"
+    );
+}
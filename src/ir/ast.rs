@@ -3,6 +3,12 @@ use crate::lexer::token::Token;
 #[derive(Debug, Clone)]
 pub enum TypeName {
     BaseTypeName(BaseTypeName),
+    /// `^<type>`, a pointer to a value of the type.
+    Pointer(Box<TypeName>),
+    /// `(<value>, ...)`, an enumerated type, only declared with TYPE.
+    Enum(Vec<String>),
+    /// `SET OF <type>`, a set of values of the type.
+    Set(Box<TypeName>),
 }
 
 #[derive(Debug, Clone)]
@@ -41,7 +47,52 @@ pub enum ExprKind {
         args: Vec<ExprKind>,
     },
     Literal(LiteralKind),
-    Variable(String),
+    Variable {
+        name: String,
+        /// The identifier, for pointing at the variable in errors.
+        token: Token,
+    },
+    /// `<pointer>^`, the value a pointer points to.
+    Deref {
+        pointer: Box<ExprKind>,
+        /// The `^`.
+        op: Token,
+    },
+    /// `<pointer>^ <- <value>`, storing through a pointer.
+    DerefAssignment {
+        pointer: Box<ExprKind>,
+        op: Token,
+        value: Box<ExprKind>,
+    },
+    /// `@<variable>`, a pointer to a variable.
+    AddressOf {
+        name: String,
+        token: Token,
+    },
+    /// `NEW <type>`, a pointer to a newly allocated value of the type, or
+    /// `NEW <class>(<arg>, ...)`, a new object of the class.
+    New {
+        type_name: TypeName,
+        args: Vec<ExprKind>,
+    },
+    /// `<object>.<name>`, an attribute or method of an object.
+    Field {
+        object: Box<ExprKind>,
+        name: String,
+        /// The name, for pointing at the member in errors.
+        token: Token,
+    },
+    /// `<object>.<name> <- <value>`, storing in an attribute.
+    FieldAssignment {
+        object: Box<ExprKind>,
+        name: String,
+        token: Token,
+        value: Box<ExprKind>,
+    },
+    /// `SUPER`, only valid before `.` in a method, to call the parent CLASS's version.
+    Super {
+        token: Token,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -51,6 +102,7 @@ pub enum LiteralKind {
     Character(char),
     String(String),
     Boolean(bool),
+    Null,
 }
 
 
@@ -66,11 +118,62 @@ pub enum Decl {
         params: Vec<Param>,
         body: Stmt,
         return_type_name: TypeName,
-    }
+    },
+    /// `TYPE <name> = <type>`, only allowed at the top level.
+    Type {
+        name: String,
+        type_name: TypeName,
+        /// Zero-based line of the declaration.
+        line: usize,
+    },
+    /// `CLASS <name> [INHERITS <parent>] ... ENDCLASS`, only allowed at the top level.
+    Class {
+        name: String,
+        parent: Option<String>,
+        members: Vec<Member>,
+        /// Zero-based line of the header.
+        line: usize,
+    },
+    /// A statement at the top level of the script, outside of any PROCEDURE or FUNCTION.
+    Stmt(Stmt),
+}
+
+/// An attribute or method of a CLASS.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub access: Access,
+    pub kind: MemberKind,
 }
 
 #[derive(Debug, Clone)]
-pub enum Stmt {
+pub enum MemberKind {
+    /// `<access> <name> : <type>`
+    Attribute {
+        name: String,
+        type_name: TypeName,
+        /// Zero-based line of the declaration.
+        line: usize,
+    },
+    /// A PROCEDURE or FUNCTION, `PROCEDURE NEW` being the constructor.
+    Method(Box<Decl>),
+}
+
+/// Methods are PUBLIC unless declared PRIVATE, attributes say which they are.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Access {
+    Public,
+    Private,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stmt {
+    pub stmt_kind: StmtKind,
+    /// Zero-based source line the statement starts on.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind {
     If {
         condition: ExprKind,
         then_branch: Box<Stmt>,
@@ -92,18 +195,59 @@ pub enum Stmt {
         args: Vec<ExprKind>
     },
 
+    /// `CALL <object>.<method>(<arg>, ...)`, `<object>` being `SUPER` for the parent
+    /// CLASS's version.
+    CallMethod {
+        object: ExprKind,
+        method: String,
+        /// The method name, for pointing at it in errors.
+        token: Token,
+        args: Vec<ExprKind>,
+    },
+
     VarDecl {
         name: String,
         type_name: TypeName,
     },
 
+    /// `DEFINE <name> (<value>, ...) : <set type>`, a set variable holding the values.
+    Define {
+        name: String,
+        values: Vec<ExprKind>,
+        type_name: TypeName,
+    },
+
+    /// `CASE OF <selector>`, running the first arm whose value matches the selector.
+    Case {
+        selector: ExprKind,
+        arms: Vec<CaseArm>,
+        otherwise: Option<Box<Stmt>>,
+    },
+
+    /// `TRY <stmts> EXCEPT <stmts> ENDTRY`, running the EXCEPT block instead of stopping
+    /// when the TRY block hits a runtime error.
+    Try {
+        body: Box<Stmt>,
+        handler: Box<Stmt>,
+    },
+
     Return(ExprKind),
     Expr(ExprKind),
     Output(Vec<ExprKind>),
-    Input(String),
+    /// `INPUT <target>`, the target being a variable, an attribute or `<pointer>^`.
+    Input(ExprKind),
     Block(Vec<Stmt>),
 }
 
+/// `<value> : <stmts>` or `<value> TO <to> : <stmts>` in a CASE statement.
+#[derive(Debug, Clone)]
+pub struct CaseArm {
+    pub value: ExprKind,
+    /// The upper end of the range, when the arm has one.
+    pub to: Option<ExprKind>,
+    pub body: Stmt,
+}
+
 #[derive(Debug, Clone)]
 pub struct Param {
     pub name: String,
@@ -1,5 +1,6 @@
 use super::ast;
 use crate::lexer::token::Token;
+use std::{fmt, sync::Mutex};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Type {
@@ -8,6 +9,148 @@ pub enum Type {
     Char,
     Boolean,
     String,
+    /// Made with `Type::pointer`.
+    Pointer(&'static Type),
+    /// The type of `NULL`, which converts to any pointer type.
+    Null,
+    /// Made with `Type::enumeration`. Values are stored as their position in the list.
+    Enum(&'static EnumType),
+    /// Made with `Type::set`, holding values of the element type.
+    Set(&'static Type),
+    /// Made with `Type::class`. Values are objects made with NEW, or nothing before then.
+    Class(&'static ClassType),
+}
+
+/// A TYPE declared as a list of values, like `TYPE Season = (Spring, Summer)`.
+#[derive(PartialEq)]
+pub struct EnumType {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+impl EnumType {
+    /// The name of the value stored as `position`.
+    pub fn value(&self, position: i64) -> Option<&str> {
+        let position = usize::try_from(position).ok()?;
+        self.values.get(position).map(String::as_str)
+    }
+}
+
+impl fmt::Debug for EnumType {
+    /// Just the name, which is enough to tell types apart in bytecode listings.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// A CLASS, which is all the VM needs to know about it. Its members are only known to the
+/// type checker.
+#[derive(PartialEq)]
+pub struct ClassType {
+    pub name: String,
+    pub parent: Option<&'static ClassType>,
+}
+
+impl ClassType {
+    /// Whether the class is `ancestor` or inherits from it, directly or not.
+    pub fn inherits(&self, ancestor: &ClassType) -> bool {
+        self == ancestor || self.parent.is_some_and(|parent| parent.inherits(ancestor))
+    }
+}
+
+impl fmt::Debug for ClassType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Every type pointed to or held in a set so far, leaked once each so that `Type` can
+/// stay `Copy`.
+static TYPES: Mutex<Vec<&'static Type>> = Mutex::new(Vec::new());
+/// Every enumerated type so far, leaked like `TYPES`.
+static ENUMS: Mutex<Vec<&'static EnumType>> = Mutex::new(Vec::new());
+/// Every CLASS so far, leaked like `TYPES`.
+static CLASSES: Mutex<Vec<&'static ClassType>> = Mutex::new(Vec::new());
+
+impl Type {
+    fn intern(pseudo_type: Type) -> &'static Type {
+        let mut types = TYPES.lock().unwrap();
+        match types.iter().find(|interned| ***interned == pseudo_type) {
+            Some(interned) => interned,
+            None => {
+                let interned: &'static Type = Box::leak(Box::new(pseudo_type));
+                types.push(interned);
+                interned
+            }
+        }
+    }
+
+    pub fn pointer(pointee: Type) -> Type {
+        Type::Pointer(Type::intern(pointee))
+    }
+
+    pub fn set(element: Type) -> Type {
+        Type::Set(Type::intern(element))
+    }
+
+    pub fn enumeration(name: String, values: Vec<String>) -> Type {
+        let enum_type = EnumType { name, values };
+        let mut enums = ENUMS.lock().unwrap();
+        match enums.iter().find(|interned| ***interned == enum_type) {
+            Some(interned) => Type::Enum(interned),
+            None => {
+                let interned: &'static EnumType = Box::leak(Box::new(enum_type));
+                enums.push(interned);
+                Type::Enum(interned)
+            }
+        }
+    }
+
+    pub fn class(name: String, parent: Option<&'static ClassType>) -> Type {
+        let class_type = ClassType { name, parent };
+        let mut classes = CLASSES.lock().unwrap();
+        match classes.iter().find(|interned| ***interned == class_type) {
+            Some(interned) => Type::Class(interned),
+            None => {
+                let interned: &'static ClassType = Box::leak(Box::new(class_type));
+                classes.push(interned);
+                Type::Class(interned)
+            }
+        }
+    }
+
+    pub fn is_pointer(self) -> bool {
+        matches!(self, Type::Pointer(_) | Type::Null)
+    }
+
+    /// Whether a value of type `from` can be stored where a `self` is expected. An object
+    /// can be used as one of any class it inherits from.
+    pub fn accepts(self, from: Type) -> bool {
+        match (self, from) {
+            (Type::Pointer(_), Type::Null) => true,
+            (Type::Class(to), Type::Class(from)) => from.inherits(to),
+            _ => self == from,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    /// Writes the type as it is spelled in pseudocode.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Integer => "INTEGER",
+            Type::Real => "REAL",
+            Type::Char => "CHAR",
+            Type::Boolean => "BOOLEAN",
+            Type::String => "STRING",
+            Type::Pointer(pointee) => return write!(f, "^{}", pointee),
+            Type::Null => "NULL",
+            Type::Enum(enum_type) => &enum_type.name,
+            Type::Set(element) => return write!(f, "SET OF {}", element),
+            Type::Class(class_type) => &class_type.name,
+        };
+        f.write_str(name)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -41,11 +184,78 @@ pub enum ExprKind {
         args: Vec<Expr>
     },
     Literal(ast::LiteralKind),
-    Variable(String),
+    Variable {
+        name: String,
+        /// The identifier, for pointing at the variable in errors.
+        token: Token,
+    },
+    Deref {
+        pointer: Box<Expr>,
+    },
+    DerefAssignment {
+        pointer: Box<Expr>,
+        value: Box<Expr>,
+    },
+    AddressOf {
+        name: String,
+        token: Token,
+    },
+    New {
+        pointee: Type,
+    },
+    /// Reads the attribute stored at `index` in an object.
+    Field {
+        object: Box<Expr>,
+        index: usize,
+    },
+    FieldAssignment {
+        object: Box<Expr>,
+        index: usize,
+        value: Box<Expr>,
+    },
+    /// Calls whichever version of the method the object's class has. Calls that don't
+    /// depend on the object's class, like those through SUPER, are plain `Call`s of the
+    /// method's PROCEDURE/FUNCTION, with the object as its first argument.
+    MethodCall {
+        object: Box<Expr>,
+        method: MethodRef,
+        args: Vec<Expr>,
+    },
+    /// A new object of `class`, passed to its constructor along with `args` if it has one.
+    Construct {
+        class: String,
+        constructor: Option<MethodRef>,
+        args: Vec<Expr>,
+    },
+    /// A line typed in by the user, read as a value of the expression's type. INPUT is
+    /// type checked as assigning it to the target.
+    Input,
 }
 
+/// The receiving object is passed to methods as a parameter with this name, which can't
+/// be written in pseudocode.
+pub const RECEIVER: &str = "<self>";
+
+/// A method, as found in the class of the object it is called on.
 #[derive(Debug, Clone)]
-pub enum Stmt {
+pub struct MethodRef {
+    /// Name of the PROCEDURE/FUNCTION the method is compiled to, `<class>.<method>`, for the
+    /// version in the class of the object's type. Others override it with the same
+    /// parameters.
+    pub name: String,
+    /// Position in the class's table of methods, shared with the methods overriding it.
+    pub slot: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stmt {
+    pub stmt_kind: StmtKind,
+    /// Zero-based source line the statement starts on.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind {
     If {
         condition: Expr,
         then_branch: Box<Stmt>,
@@ -67,17 +277,48 @@ pub enum Stmt {
         args: Vec<Expr>
     },
 
+    CallMethod {
+        object: Expr,
+        method: MethodRef,
+        args: Vec<Expr>,
+    },
+
     VarDecl {
         name: String,
+        pseudo_type: Type,
+    },
+
+    Define {
+        name: String,
+        pseudo_type: Type,
+        values: Vec<Expr>,
+    },
+
+    Case {
+        selector: Expr,
+        arms: Vec<CaseArm>,
+        otherwise: Option<Box<Stmt>>,
+    },
+
+    Try {
+        body: Box<Stmt>,
+        handler: Box<Stmt>,
     },
 
     Return(Expr),
     Expr(Expr),
     Output(Vec<Expr>),
-    Input(String),
     Block(Vec<Stmt>),
 }
 
+#[derive(Debug, Clone)]
+pub struct CaseArm {
+    pub value: Expr,
+    /// The upper end of the range, when the arm has one.
+    pub to: Option<Expr>,
+    pub body: Stmt,
+}
+
 #[derive(Debug, Clone)]
 pub enum Decl {
     Procedure {
@@ -90,7 +331,18 @@ pub enum Decl {
         params: Vec<Param>,
         body: Stmt,
         return_type: Type,
-    }
+    },
+    /// Comes after the PROCEDUREs/FUNCTIONs its methods are compiled to.
+    Class {
+        name: String,
+        class_type: &'static ClassType,
+        /// Types of the attributes in the order they're stored, inherited ones first.
+        attributes: Vec<Type>,
+        /// PROCEDURE/FUNCTION each method slot calls.
+        methods: Vec<String>,
+    },
+    /// A statement at the top level of the script, outside of any PROCEDURE or FUNCTION.
+    Stmt(Stmt),
 }
 
 #[derive(Debug, Clone)]
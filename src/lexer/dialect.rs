@@ -0,0 +1,52 @@
+/// The flavours of pseudocode accepted, picked with the `--dialect` flag or a
+/// `// dialect: <id>` pragma among the comments at the top of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// Cambridge International AS & A Level (9618): keywords in upper case only, and
+    /// identifiers are case-sensitive.
+    #[default]
+    Strict,
+    /// Keywords and identifiers in any case, so `endif` is `ENDIF` and `total` is the
+    /// variable `Total`. Identifiers keep the spelling they first appear with.
+    Lenient,
+    /// Cambridge IGCSE (0478): an optional `DO` after a WHILE condition and `ENDFOR` in
+    /// place of `NEXT <counter>`.
+    Igcse,
+}
+
+impl Dialect {
+    pub const ALL: [Dialect; 3] = [Dialect::Strict, Dialect::Lenient, Dialect::Igcse];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Dialect::Strict => "strict",
+            Dialect::Lenient => "lenient",
+            Dialect::Igcse => "igcse",
+        }
+    }
+
+    /// Also accepts the syllabus codes, `9618` and `0478`.
+    pub fn from_id(id: &str) -> Option<Dialect> {
+        match id {
+            "9618" => Some(Dialect::Strict),
+            "0478" => Some(Dialect::Igcse),
+            _ => Dialect::ALL
+                .into_iter()
+                .find(|dialect| dialect.id().eq_ignore_ascii_case(id)),
+        }
+    }
+
+    /// The dialect named by a `// dialect: <id>` comment before the first line of code.
+    pub fn from_pragma(src: &str) -> Option<Dialect> {
+        for line in src.lines().map(str::trim) {
+            if line.is_empty() {
+                continue;
+            }
+            let comment = line.strip_prefix("//")?.trim();
+            if let Some(id) = comment.strip_prefix("dialect:") {
+                return Dialect::from_id(id.trim());
+            }
+        }
+        None
+    }
+}
@@ -1,8 +1,14 @@
+pub mod dialect;
 pub mod token;
-mod keyword;
+pub mod keyword;
 #[cfg(test)] mod tests;
 
-use crate::lexer::token::{Token, TokenKind, TokenLiteralKind};
+use crate::lexer::{
+    dialect::Dialect,
+    token::{KeywordKind, Token, TokenKind, TokenLiteralKind},
+};
+use std::collections::HashMap;
+use unicode_ident::{is_xid_continue, is_xid_start};
 use std::iter::Peekable;
 use std::str::Chars;
 use TokenKind::*;
@@ -12,30 +18,107 @@ pub struct Lexer<'a> {
     line: usize,
     col: usize,
     current: usize,
+    /// Byte offset of the next character.
+    offset: usize,
     last_token_new_line: bool,
+    /// Emit whitespace, comments and every new line as tokens instead of skipping them.
+    keep_trivia: bool,
+    dialect: Dialect,
+    /// The first spelling of every identifier by its lower-case form, in `Dialect::Lenient`.
+    spellings: HashMap<String, String>,
 }
 
 impl<'a> Lexer<'a> {
+    /// Lexes `src` in the dialect named by its pragma, or `Dialect::Strict`.
     pub fn new(src: &'a str) -> Self {
+        Self::with_dialect(src, Dialect::default())
+    }
+
+    /// Lexes `src` in `dialect`, unless a pragma at the top of `src` names another.
+    pub fn with_dialect(src: &'a str, dialect: Dialect) -> Self {
         Self {
             src: src.chars().peekable(),
             line: 0,
             col: 0,
             current: 0,
+            offset: 0,
             last_token_new_line: true,
+            keep_trivia: false,
+            dialect: Dialect::from_pragma(src).unwrap_or(dialect),
+            spellings: HashMap::new(),
         }
     }
 }
 
+/// Lexes `src` without losing anything: whitespace, comments and blank lines are kept as
+/// tokens, and every token is paired with the source text it covers, so concatenating
+/// the texts gives back `src`.
+pub fn lex_with_trivia(src: &str, dialect: Dialect) -> Vec<(Token, &str)> {
+    let mut lexer = Lexer::with_dialect(src, dialect);
+    lexer.keep_trivia = true;
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while let Some(token) = lexer.next() {
+        tokens.push((token, &src[start..lexer.offset]));
+        start = lexer.offset;
+    }
+    tokens
+}
+
 impl Iterator for Lexer<'_> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.keep_trivia {
+            return self.next_with_trivia();
+        }
+
         let first_char = match self.skip_whitespace_and_get_first_char() {
             Some(ch) => ch,
             None => return None,
         };
+        Some(self.token(first_char))
+    }
+}
+
+impl Lexer<'_> {
+    fn next_with_trivia(&mut self) -> Option<Token> {
+        let first_char = match self.src.peek() {
+            Some(&ch) if is_whitespace(ch) && !is_newline(ch) => self.advance().unwrap(),
+            Some('/') => self.advance().unwrap(),
+            Some(_) => {
+                let first_char = self.advance().unwrap();
+                return Some(self.token(first_char));
+            }
+            None => return None,
+        };
+
+        let token_start = self.current - 1;
+        let token_col = self.col;
+        let token_line = self.line;
+        let token_kind = match first_char {
+            '/' if self.src.peek() == Some(&'/') => {
+                self.advance_while(|ch| !is_newline(ch));
+                Comment
+            }
+            '/' => return Some(self.token(first_char)),
+            _ => {
+                self.advance_while(|ch| is_whitespace(ch) && !is_newline(ch));
+                Whitespace
+            }
+        };
+
+        Some(Token {
+            kind: token_kind,
+            line: token_line,
+            col: token_col,
+            len: self.current - token_start,
+        })
+    }
 
+    /// Lexes the token starting with `first_char`, which has already been consumed.
+    fn token(&mut self, first_char: char) -> Token {
         let token_start = self.current - 1;
         let token_col = self.col;
         let token_line = self.line;
@@ -55,7 +138,13 @@ impl Iterator for Lexer<'_> {
             ',' => Comma,
             ':' => Colon,
             '^' => Caret,
+            '@' => At,
             '&' => Ampersand,
+            // The symbols used on exam papers.
+            '←' => LeftArrow,
+            '≠' => NotEqual,
+            '≤' => LessEqual,
+            '≥' => GreaterEqual,
 
             '<' => match self.src.peek() {
                 Some(c) => match c {
@@ -95,25 +184,24 @@ impl Iterator for Lexer<'_> {
             '"' => self.string(),
             '\'' => self.character(),
             ch if ch.is_ascii_digit() => self.number(ch),
-            ch if ch.is_alphabetic() => self.identifier(ch),
+            ch if is_xid_start(ch) => self.identifier(ch),
 
             _ => TokenKind::Error("Invalid token"),
         };
 
-        Some(Token {
+        Token {
             kind: token_kind,
             line: token_line,
             col: token_col,
             len: self.current - token_start,
-        })
+        }
     }
-}
 
-impl Lexer<'_> {
     fn advance(&mut self) -> Option<char> {
         let ch = self.src.next();
         self.current += 1;
-        if ch.is_some_and(|ch| !ch.is_control()) {
+        self.offset += ch.map_or(0, char::len_utf8);
+        if ch.is_some_and(|ch| !is_newline(ch)) {
             self.col += 1;
         }
         ch
@@ -126,9 +214,23 @@ impl Lexer<'_> {
 
     fn identifier(&mut self, first_char: char) -> TokenKind {
         let mut value = String::from(first_char);
-        self.consume_while(|ch| ch.is_alphabetic() || ch.is_ascii_digit(), &mut value);
+        self.consume_while(is_xid_continue, &mut value);
 
-        keyword::check_keyword(value)
+        match self.dialect {
+            Dialect::Strict => keyword::check_keyword(value),
+            Dialect::Lenient => match keyword::check_keyword(value.to_uppercase()) {
+                Identifier(_) => {
+                    let spelling = self.spellings.entry(value.to_lowercase()).or_insert(value);
+                    Identifier(spelling.clone())
+                }
+                keyword => keyword,
+            },
+            Dialect::Igcse => match value.as_str() {
+                "DO" => Keyword(KeywordKind::Do),
+                "ENDFOR" => Keyword(KeywordKind::EndFor),
+                _ => keyword::check_keyword(value),
+            },
+        }
     }
 
     fn number(&mut self, first_char: char) -> TokenKind {
@@ -153,55 +255,52 @@ impl Lexer<'_> {
     fn string(&mut self) -> TokenKind {
         let mut value = String::new();
         self.consume_while(|ch| ch != '"' && !is_newline(ch), &mut value);
-        if self.src.peek().is_none()
-            || match self.src.peek() {
-                Some(&c) if c != '"' => true,
-                _ => false,
+        match self.src.peek() {
+            Some('"') => {
+                self.advance();
+                TokenKind::Literal(TokenLiteralKind::Str(value))
             }
-        {
-            todo!("Should not go to parsing stage if this happens");
-            TokenKind::Error("Unterminated STRING");
+            // The new line is left for the next token so the parser can recover there.
+            _ => TokenKind::Error("unterminated STRING literal."),
         }
-        self.advance();
-        TokenKind::Literal(TokenLiteralKind::Str(value))
     }
 
     fn character(&mut self) -> TokenKind {
-        let ch = match self.advance() {
-            Some(ch) => ch,
-            None => {
-                todo!("Should not go to parsing stage if this happens");
-                return TokenKind::Error("Expected character after `'`.");
-            }
-        };
-        match self.advance() {
-            Some('\'') => (),
-            _ => {
-                todo!("Should not go to parsing stage if this happens");
-                return TokenKind::Error("Expected delimiting `'` after character for literal.");
+        let ch = match self.src.peek() {
+            Some(&ch) if is_newline(ch) => return TokenKind::Error("unterminated CHAR literal."),
+            Some('\'') => {
+                self.advance();
+                return TokenKind::Error("empty CHAR literal.");
             }
+            Some(_) => self.advance().unwrap(),
+            None => return TokenKind::Error("unterminated CHAR literal."),
         };
+        if self.src.peek() == Some(&'\'') {
+            self.advance();
+            return TokenKind::Literal(TokenLiteralKind::Character(ch));
+        }
 
-        TokenKind::Literal(TokenLiteralKind::Character(ch))
+        // Skip the rest of the literal so lexing carries on after it.
+        self.advance_while(|ch| ch != '\'' && !is_newline(ch));
+        match self.src.peek() {
+            Some('\'') => {
+                self.advance();
+                TokenKind::Error("CHAR literal must contain exactly one character.")
+            }
+            _ => TokenKind::Error("unterminated CHAR literal."),
+        }
     }
 
     fn skip_whitespace_and_get_first_char(&mut self) -> Option<char> {
         while self.src.peek().is_some() {
             match self.src.peek().unwrap() {
-                // todo: allow comments come after a statement
-                // currently, comments have to be on their own lines
                 '/' => {
                     self.advance();
                     match self.src.peek() {
                         Some(&ch) if ch == '/' => {
-                            self.advance();
-
-                            // advance until the end of the line.
+                            // advance until the end of the line, the new line itself still
+                            // ends the statement the comment comes after.
                             self.advance_while(|ch| !is_newline(ch));
-                            self.new_line();
-
-                            // advance until a non-whitespace character is found.
-                            self.skip_whitespace()
                         }
                         _ => return Some('/'),
                     }
@@ -223,15 +322,6 @@ impl Lexer<'_> {
         None
     }
 
-    fn skip_whitespace(&mut self) {
-        while self.src.peek().is_some_and(|&ch| is_whitespace(ch)) {
-            let ch = self.advance().unwrap();
-            if is_newline(ch) {
-                self.new_line();
-            }
-        }
-    }
-
     fn advance_while<C>(&mut self, condition: C)
     where
         C: Fn(char) -> bool,
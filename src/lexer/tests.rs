@@ -1,4 +1,7 @@
-use crate::lexer::{token::KeywordKind, Lexer, TokenKind, TokenLiteralKind};
+use crate::{
+    error::underline,
+    lexer::{dialect::Dialect, token::{KeywordKind, Token}, Lexer, TokenKind, TokenLiteralKind},
+};
 use TokenKind::*;
 
 fn check_lexing(src: &str, expect: Vec<TokenKind>) {
@@ -100,7 +103,9 @@ fn keywords() {
     check_lexing("NOT", vec![Keyword(Not)]);
     check_lexing("DIV", vec![Keyword(Div)]);
     check_lexing("MOD", vec![Keyword(Mod)]);
+    check_lexing("IN", vec![Keyword(In)]);
     check_lexing("DECLARE", vec![Keyword(Declare)]);
+    check_lexing("DEFINE", vec![Keyword(Define)]);
     check_lexing("CONSTANT", vec![Keyword(Constant)]);
     check_lexing("TYPE", vec![Keyword(Type)]);
     check_lexing("ENDTYPE", vec![Keyword(EndType)]);
@@ -111,6 +116,7 @@ fn keywords() {
     check_lexing("BOOLEAN", vec![Keyword(Boolean)]);
     check_lexing("DATE", vec![Keyword(Date)]);
     check_lexing("ARRAY", vec![Keyword(Array)]);
+    check_lexing("SET", vec![Keyword(Set)]);
     check_lexing("OF", vec![Keyword(Of)]);
     check_lexing("BYREF", vec![Keyword(ByRef)]);
     check_lexing("BYVAL", vec![Keyword(ByVal)]);
@@ -150,6 +156,15 @@ fn keywords() {
         vec![Keyword(Otherwise)],
     );
     check_lexing("ENDCASE", vec![Keyword(EndCase)]);
+    check_lexing("CLASS", vec![Keyword(Class)]);
+    check_lexing("ENDCLASS", vec![Keyword(EndClass)]);
+    check_lexing("INHERITS", vec![Keyword(Inherits)]);
+    check_lexing("PUBLIC", vec![Keyword(Public)]);
+    check_lexing("PRIVATE", vec![Keyword(Private)]);
+    check_lexing("SUPER", vec![Keyword(Super)]);
+    check_lexing("TRY", vec![Keyword(Try)]);
+    check_lexing("EXCEPT", vec![Keyword(Except)]);
+    check_lexing("ENDTRY", vec![Keyword(EndTry)]);
     check_lexing("INPUT", vec![Keyword(Input)]);
     check_lexing("OUTPUT", vec![Keyword(Output)]);
     check_lexing("OPENFILE", vec![Keyword(OpenFile)]);
@@ -167,3 +182,227 @@ fn keywords() {
     check_lexing("APPEND", vec![Keyword(Append)]);
     check_lexing("RANDOM", vec![Keyword(Random)]);
 }
+
+#[test]
+fn unterminated_string() {
+    check_lexing(
+        "\"abc\nx",
+        vec![
+            Error("unterminated STRING literal."),
+            NewLine,
+            Identifier(String::from("x")),
+        ],
+    );
+    check_lexing("\"abc", vec![Error("unterminated STRING literal.")]);
+}
+
+#[test]
+fn empty_character() {
+    check_lexing(
+        "'' 'a'",
+        vec![
+            Error("empty CHAR literal."),
+            Literal(TokenLiteralKind::Character('a')),
+        ],
+    );
+}
+
+#[test]
+fn unterminated_character() {
+    check_lexing("'", vec![Error("unterminated CHAR literal.")]);
+    check_lexing(
+        "'\nx",
+        vec![
+            Error("unterminated CHAR literal."),
+            NewLine,
+            Identifier(String::from("x")),
+        ],
+    );
+    check_lexing(
+        "'ab\nx",
+        vec![
+            Error("unterminated CHAR literal."),
+            NewLine,
+            Identifier(String::from("x")),
+        ],
+    );
+}
+
+#[test]
+fn character_too_long() {
+    check_lexing(
+        "'abc' 'd'",
+        vec![
+            Error("CHAR literal must contain exactly one character."),
+            Literal(TokenLiteralKind::Character('d')),
+        ],
+    );
+}
+
+#[test]
+fn number_too_large() {
+    check_lexing(
+        "99999999999999999999 1",
+        vec![
+            Error("INTEGER literal too large"),
+            Literal(TokenLiteralKind::Integer(1)),
+        ],
+    );
+}
+
+#[test]
+fn error_positions() {
+    let tokens: Vec<(usize, usize, usize)> = Lexer::new("x <- \"ab\ny <- 'cd'")
+        .filter(|token| matches!(token.kind, Error(_)))
+        .map(|token| (token.line, token.col, token.len))
+        .collect();
+    assert_eq!(tokens, vec![(0, 6, 3), (1, 6, 4)]);
+}
+
+#[test]
+fn trailing_comments() {
+    check_lexing(
+        "x // comment\ny",
+        vec![
+            Identifier(String::from("x")),
+            NewLine,
+            Identifier(String::from("y")),
+        ],
+    );
+}
+
+#[test]
+fn trivia() {
+    let src = "x  <- 1 // one\n\n\ty";
+    let tokens = crate::lexer::lex_with_trivia(src, Dialect::Strict);
+    let kinds: Vec<TokenKind> = tokens.iter().map(|(token, _)| token.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Identifier(String::from("x")),
+            Whitespace,
+            LeftArrow,
+            Whitespace,
+            Literal(TokenLiteralKind::Integer(1)),
+            Whitespace,
+            Comment,
+            NewLine,
+            NewLine,
+            Whitespace,
+            Identifier(String::from("y")),
+        ]
+    );
+    let text: String = tokens.iter().map(|(_, text)| *text).collect();
+    assert_eq!(text, src);
+    assert_eq!(tokens.last().unwrap().0.line, 2);
+}
+
+#[test]
+fn keyword_list() {
+    for keyword in crate::lexer::keyword::KEYWORDS {
+        let kinds: Vec<TokenKind> = Lexer::new(keyword).map(|t| t.kind).collect();
+        assert!(
+            matches!(kinds.as_slice(), [Keyword(_)]),
+            "`{}` lexed as {:?}",
+            keyword,
+            kinds
+        );
+    }
+}
+
+fn lex_in(src: &str, dialect: Dialect) -> Vec<TokenKind> {
+    Lexer::with_dialect(src, dialect).map(|t| t.kind).collect()
+}
+
+#[test]
+fn dialects() {
+    use KeywordKind::*;
+    let src = "endif Total total DO ENDFOR x";
+    assert_eq!(
+        lex_in(src, Dialect::Strict),
+        vec![
+            Identifier(String::from("endif")),
+            Identifier(String::from("Total")),
+            Identifier(String::from("total")),
+            Identifier(String::from("DO")),
+            Identifier(String::from("ENDFOR")),
+            Identifier(String::from("x")),
+        ]
+    );
+    // Identifiers keep the spelling they were first seen with.
+    assert_eq!(
+        lex_in(src, Dialect::Lenient)[..3],
+        [
+            Keyword(EndIf),
+            Identifier(String::from("Total")),
+            Identifier(String::from("Total")),
+        ]
+    );
+    assert_eq!(
+        lex_in(src, Dialect::Igcse)[3..],
+        [
+            Keyword(Do),
+            Keyword(EndFor),
+            Identifier(String::from("x")),
+        ]
+    );
+}
+
+#[test]
+fn dialect_pragma() {
+    assert_eq!(Dialect::from_pragma("// dialect: lenient\nX <- 1"), Some(Dialect::Lenient));
+    assert_eq!(
+        Dialect::from_pragma("// Adds two numbers.\n\n// dialect: 0478\nX <- 1"),
+        Some(Dialect::Igcse)
+    );
+    // Only comments before the first line of code count.
+    assert_eq!(Dialect::from_pragma("X <- 1\n// dialect: lenient"), None);
+    // A pragma takes precedence over the dialect asked for.
+    assert_eq!(
+        lex_in("// dialect: strict\nendif", Dialect::Lenient),
+        vec![Identifier(String::from("endif"))]
+    );
+}
+
+#[test]
+fn unicode_operators() {
+    check_lexing(
+        "X ← 1 ≠ 2 ≤ 3 ≥ 4",
+        vec![
+            Identifier(String::from("X")),
+            LeftArrow,
+            Literal(TokenLiteralKind::Integer(1)),
+            NotEqual,
+            Literal(TokenLiteralKind::Integer(2)),
+            LessEqual,
+            Literal(TokenLiteralKind::Integer(3)),
+            GreaterEqual,
+            Literal(TokenLiteralKind::Integer(4)),
+        ],
+    );
+}
+
+#[test]
+fn unicode_identifiers() {
+    // The second `Größe` spells its `e` with a combining accent.
+    check_lexing(
+        "Größe Ünter_2 Größe\u{301} 名前",
+        vec![
+            Identifier(String::from("Größe")),
+            Identifier(String::from("Ünter_2")),
+            Identifier(String::from("Größe\u{301}")),
+            Identifier(String::from("名前")),
+        ],
+    );
+}
+
+#[test]
+fn unicode_columns() {
+    let line = "\tGröße ← '名' + ?";
+    let tokens: Vec<Token> = Lexer::new(line).collect();
+    let positions: Vec<(usize, usize)> = tokens.iter().map(|token| (token.col, token.len)).collect();
+    assert_eq!(positions, vec![(2, 5), (8, 1), (10, 3), (14, 1), (16, 1)]);
+    // Tabs are kept and the wide character takes two columns.
+    assert_eq!(underline(line, &tokens[4]), "\t               ^");
+    assert_eq!(underline(line, &tokens[2]), "\t        ^^^^");
+}
@@ -1,8 +1,11 @@
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
+    /// Zero-based.
     pub line: usize,
+    /// One-based, counted in characters rather than bytes.
     pub col: usize,
+    /// In characters rather than bytes.
     pub len: usize,
 }
 
@@ -36,6 +39,10 @@ pub enum TokenKind {
 
     NewLine,
 
+    // Only produced by `lex_with_trivia`.
+    Whitespace,
+    Comment,
+
     OpenSqrBracket,
     CloseSqrBracket,
 
@@ -45,6 +52,7 @@ pub enum TokenKind {
     Colon,
     Comma,
     Caret,
+    At,
     Dot,
     Ampersand,
 
@@ -78,9 +86,11 @@ pub enum KeywordKind {
 
     Div,
     Mod,
+    In,
 
     Declare,
     Constant,
+    Define,
 
     Type,
     EndType,
@@ -93,6 +103,7 @@ pub enum KeywordKind {
     Date,
 
     Array,
+    Set,
     Of,
 
     ByRef,
@@ -112,8 +123,12 @@ pub enum KeywordKind {
     To,
     Step,
     Next,
+    /// `Dialect::Igcse` only.
+    EndFor,
 
     While,
+    /// `Dialect::Igcse` only.
+    Do,
     EndWhile,
 
     Repeat,
@@ -127,10 +142,24 @@ pub enum KeywordKind {
     True,
     False,
 
+    Null,
+    New,
+
     Case,
     Otherwise,
     EndCase,
 
+    Class,
+    EndClass,
+    Inherits,
+    Public,
+    Private,
+    Super,
+
+    Try,
+    Except,
+    EndTry,
+
     // Built-in procedures
 
     Input,
@@ -1,18 +1,139 @@
+pub mod cfg;
 pub mod codegen_bytecode;
 pub mod error;
+pub mod formatter;
 mod codegen_c;
+pub mod debugger;
 pub mod ir;
-#[allow(unreachable_code)] pub mod lexer;
+pub mod lexer;
+pub mod lint;
+pub mod lsp;
 pub mod naive_tc;
 pub mod parser;
+pub mod syntax;
+pub mod trace_table;
 pub mod vm;
 
 use crate::codegen_c::generate;
-use crate::lexer::Lexer;
+use crate::lexer::{dialect::Dialect, Lexer};
 use crate::parser::program;
+use crate::trace_table::{TraceFormat, TraceTable};
+use crate::vm::{verifier::VerifyError, ExecuteError};
 
-pub fn interpret(src: &str) {
-    let tokens = Lexer::new(src);
+/// How programs are compiled and run, set from the command line.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Dialect of source files without a pragma.
+    pub dialect: Dialect,
+    /// Let INTEGER arithmetic wrap around on overflow instead of stopping with a runtime
+    /// error.
+    pub wrapping: bool,
+    /// Most calls that can be nested before stopping with a stack overflow.
+    pub max_call_depth: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::default(),
+            wrapping: false,
+            max_call_depth: vm::DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+}
+
+impl Options {
+    fn vm(&self) -> vm::Vm {
+        let mut vm = vm::Vm::new();
+        vm.set_wrapping(self.wrapping);
+        vm.set_max_call_depth(self.max_call_depth);
+        vm
+    }
+}
+
+pub fn interpret(src: &str, options: &Options) -> Result<(), VerifyError> {
+    let mut vm = options.vm();
+    let script = compile(src, options.dialect, &mut vm);
+    report(Some(src), vm.execute(script))
+}
+
+/// Compiles `src` to bytecode in the `.pbc` format (see `vm::serialize`).
+pub fn build(src: &str, options: &Options) -> Vec<u8> {
+    let mut vm = options.vm();
+    let script = compile(src, options.dialect, &mut vm);
+    let bytes = vm::serialize::serialize(&script);
+    vm.free_objects();
+    bytes
+}
+
+/// Loads and executes bytecode previously produced by `build`. Bytecode the verifier
+/// rejects is returned for the caller to report.
+pub fn run_bytecode(bytes: &[u8], options: &Options) -> Result<(), VerifyError> {
+    let mut vm = options.vm();
+    let script = load(bytes, &mut vm);
+    report(None, vm.execute(script))
+}
+
+/// Runs `src` under the interactive step debugger, reading commands from stdin.
+pub fn debug(src: &str, options: &Options) -> Result<(), VerifyError> {
+    let mut vm = options.vm();
+    let script = compile(src, options.dialect, &mut vm);
+    let stdin = std::io::stdin();
+    let mut debugger = debugger::Debugger::new(&script, src, stdin.lock(), std::io::stdout());
+    println!("Type `help` for a list of commands.");
+    report(Some(src), vm.execute_with_hook(script, &mut debugger))
+}
+
+/// Runs `src` or, when `is_bytecode`, a `.pbc` file and prints a trace table of the
+/// `variables` (or every variable when empty) afterwards.
+pub fn trace(
+    file: &[u8],
+    is_bytecode: bool,
+    options: &Options,
+    variables: Vec<String>,
+    format: TraceFormat,
+) -> Result<(), VerifyError> {
+    let mut vm = options.vm();
+    let src = match is_bytecode {
+        true => None,
+        false => match std::str::from_utf8(file) {
+            Ok(src) => Some(src),
+            Err(_) => {
+                println!("Source file is not valid UTF-8.");
+                std::process::exit(0);
+            }
+        },
+    };
+    let script = match src {
+        None => load(file, &mut vm),
+        Some(src) => compile(src, options.dialect, &mut vm),
+    };
+    let mut trace_table = TraceTable::new(variables);
+    // The table is still printed up to a runtime error.
+    let result = vm.execute_with_hook(script, &mut trace_table);
+    println!();
+    report(src, result)?;
+    print!("{}", trace_table.render(format));
+    Ok(())
+}
+
+/// Prints the runtime error a program stopped with, if any, handing back bytecode the
+/// verifier rejected instead.
+fn report(src: Option<&str>, result: Result<(), ExecuteError>) -> Result<(), VerifyError> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(ExecuteError::Runtime(error)) => {
+            error::print_runtime_error(src, error);
+            Ok(())
+        }
+        Err(ExecuteError::Invalid(error)) => Err(error),
+        // Quitting the debugger ends the program as if it had finished.
+        Err(ExecuteError::Stopped) => Ok(()),
+    }
+}
+
+fn compile(src: &str, dialect: Dialect, vm: &mut vm::Vm) -> vm::obj::ObjFn {
+    let tokens = Lexer::with_dialect(src, dialect);
 
     let program = match program(tokens.peekable()) {
         Ok(decls) => decls,
@@ -22,14 +143,35 @@ pub fn interpret(src: &str) {
         }
     };
 
-    let hlir = naive_tc::typecheck(program);
-    let mut vm = vm::Vm::new();
-    let script = codegen_bytecode::emit(hlir, &mut vm);
-    vm.execute(script);
+    let hlir = match naive_tc::typecheck(program) {
+        Ok(hlir) => hlir,
+        Err(error) => {
+            error::print_type_error(src, error);
+            std::process::exit(0);
+        }
+    };
+    match codegen_bytecode::emit(hlir, vm) {
+        Ok(script) => script,
+        Err(error) => {
+            error::print_codegen_error(error);
+            std::process::exit(0);
+        }
+    }
+}
+
+fn load(bytes: &[u8], vm: &mut vm::Vm) -> vm::obj::ObjFn {
+    match vm::serialize::deserialize(vm, bytes) {
+        Ok(script) => script,
+        Err(error) => {
+            vm.free_objects();
+            println!("error: malformed bytecode file: {}", error);
+            std::process::exit(0);
+        }
+    }
 }
 
-pub fn compile_to_c(src: &str) {
-    let tokens = Lexer::new(src);
+pub fn compile_to_c(src: &str, options: &Options) {
+    let tokens = Lexer::with_dialect(src, options.dialect);
 
     let program = match program(tokens.peekable()) {
         Ok(decls) => decls,
@@ -39,7 +181,13 @@ pub fn compile_to_c(src: &str) {
         }
     };
 
-    let c_src = generate(program);
+    let c_src = match generate(program, options.wrapping) {
+        Ok(c_src) => c_src,
+        Err(error) => {
+            error::print_codegen_error(error);
+            std::process::exit(0);
+        }
+    };
 
     std::fs::write("./target.c", c_src).expect("Unable to write to target C source file.");
 }
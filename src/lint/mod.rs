@@ -0,0 +1,513 @@
+//! Teaching-oriented warnings about programs the type checker accepts but which are
+//! probably not what their author meant.
+//!
+//! Lints run over the lossless `syntax` tree, which keeps the position of every name.
+//! A lint is silenced for one line with a `// lint: allow(<id>, ...)` comment, either at
+//! the end of that line or on the line before it.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    error::{underline, ParseError},
+    lexer::{
+        dialect::Dialect,
+        keyword::KEYWORDS,
+        token::{KeywordKind, Token, TokenKind},
+    },
+    syntax::{self, NodeKind, SyntaxNode, SyntaxToken},
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    UnusedVariable,
+    UnusedParameter,
+    NeverAssigned,
+    UnreachableCode,
+    MissingReturn,
+    ShadowedName,
+    InfiniteLoop,
+    LowercaseKeyword,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 8] = [
+        Lint::UnusedVariable,
+        Lint::UnusedParameter,
+        Lint::NeverAssigned,
+        Lint::UnreachableCode,
+        Lint::MissingReturn,
+        Lint::ShadowedName,
+        Lint::InfiniteLoop,
+        Lint::LowercaseKeyword,
+    ];
+
+    /// The name used to report and to allow the lint.
+    pub fn id(self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnusedParameter => "unused-parameter",
+            Lint::NeverAssigned => "never-assigned",
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::MissingReturn => "missing-return",
+            Lint::ShadowedName => "shadowed-name",
+            Lint::InfiniteLoop => "infinite-loop",
+            Lint::LowercaseKeyword => "lowercase-keyword",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.id() == id)
+    }
+}
+
+#[derive(Debug)]
+pub struct Warning {
+    pub lint: Lint,
+    pub msg: String,
+    /// The token the warning points at.
+    pub token: Token,
+}
+
+pub struct Report {
+    pub warnings: Vec<Warning>,
+    /// Syntax errors. Only `Lint::LowercaseKeyword`, which often explains them, is checked
+    /// when there are any.
+    pub errors: Vec<ParseError>,
+}
+
+/// Lints `src`, leaving out the `allowed` lints and those allowed by comments.
+pub fn lint(src: &str, allowed: &[Lint], dialect: Dialect) -> Report {
+    let parse = syntax::parse(src, dialect);
+    let mut linter = Linter {
+        root: parse.root.clone(),
+        warnings: Vec::new(),
+    };
+    linter.lowercase_keywords();
+    if parse.errors.is_empty() {
+        linter.variables();
+        linter.unreachable_code();
+        linter.missing_returns();
+        linter.infinite_loops();
+    }
+
+    let suppressed = suppressions(&parse.root);
+    let mut warnings: Vec<Warning> = linter
+        .warnings
+        .into_iter()
+        .filter(|warning| !allowed.contains(&warning.lint))
+        .filter(|warning| {
+            suppressed
+                .get(&warning.token.line)
+                .is_none_or(|lints| !lints.contains(&warning.lint))
+        })
+        .collect();
+    warnings.sort_by_key(|warning| (warning.token.line, warning.token.col));
+    Report {
+        warnings,
+        errors: parse.errors,
+    }
+}
+
+pub fn print_warnings(src: &str, warnings: &[Warning]) {
+    for warning in warnings {
+        let token = &warning.token;
+        let line = src.lines().nth(token.line).unwrap_or_default();
+        let line_number = token.line + 1;
+        let line_number_len = line_number.to_string().len();
+        println!("{}:{}", line_number, token.col);
+        println!("{} |", " ".repeat(line_number_len));
+        println!("{} |\t{}", line_number, line);
+        println!("{} | \t{}", " ".repeat(line_number_len), underline(line, token));
+        println!("warning[{}]: {}", warning.lint.id(), warning.msg);
+        println!();
+    }
+}
+
+/// Lints allowed on each line by `// lint: allow(...)` comments.
+fn suppressions(root: &SyntaxNode) -> HashMap<usize, Vec<Lint>> {
+    let mut suppressed: HashMap<usize, Vec<Lint>> = HashMap::new();
+    // Lints allowed by a comment on its own line, waiting for the next line of code.
+    let mut pending: Vec<Lint> = Vec::new();
+    let mut code_line = None;
+    for token in root.tokens() {
+        match token.kind() {
+            TokenKind::Comment => {
+                let lints = allowed_lints(token.text());
+                match code_line {
+                    Some(line) if line == token.token().line => {
+                        suppressed.entry(line).or_default().extend(lints)
+                    }
+                    _ => pending.extend(lints),
+                }
+            }
+            TokenKind::Whitespace | TokenKind::NewLine => (),
+            _ => {
+                let line = token.token().line;
+                code_line = Some(line);
+                if !pending.is_empty() {
+                    suppressed.entry(line).or_default().append(&mut pending);
+                }
+            }
+        }
+    }
+    suppressed
+}
+
+/// The lints named by a `// lint: allow(<id>, ...)` comment. Unknown ids are ignored.
+fn allowed_lints(comment: &str) -> Vec<Lint> {
+    let list = comment
+        .trim_start_matches('/')
+        .trim()
+        .strip_prefix("lint:")
+        .and_then(|rest| rest.trim().strip_prefix("allow("))
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(list, _)| list)
+        .unwrap_or_default();
+    list.split(',')
+        .filter_map(|id| Lint::from_id(id.trim()))
+        .collect()
+}
+
+struct Linter {
+    root: SyntaxNode,
+    warnings: Vec<Warning>,
+}
+
+/// How a variable is referred to by the program.
+#[derive(Default)]
+struct Usage {
+    reads: usize,
+    writes: usize,
+}
+
+impl Linter {
+    fn warn(&mut self, lint: Lint, token: &SyntaxToken, msg: String) {
+        self.warnings.push(Warning {
+            lint,
+            msg,
+            token: token.token().clone(),
+        });
+    }
+
+    /// Lower-case keywords are lexed as identifiers, usually leading to confusing syntax errors.
+    fn lowercase_keywords(&mut self) {
+        for token in self.root.tokens() {
+            let upper = token.text().to_uppercase();
+            if matches!(token.kind(), TokenKind::Identifier(_))
+                && upper != token.text()
+                && KEYWORDS.contains(&upper.as_str())
+            {
+                let msg = format!(
+                    "`{}` is not a keyword, keywords are written in upper case: `{}`.",
+                    token.text(),
+                    upper
+                );
+                self.warn(Lint::LowercaseKeyword, &token, msg);
+            }
+        }
+    }
+
+    /// Unused, never assigned and shadowed variables and parameters.
+    fn variables(&mut self) {
+        let declarations: Vec<SyntaxNode> = self
+            .root
+            .descendants()
+            .into_iter()
+            .filter(|node| {
+                matches!(node.kind(), NodeKind::Declare | NodeKind::Define | NodeKind::Param)
+            })
+            .collect();
+        let callables: Vec<SyntaxNode> = self
+            .root
+            .children()
+            .into_iter()
+            .filter(|node| matches!(node.kind(), NodeKind::Procedure | NodeKind::Function))
+            .collect();
+
+        // Uses of each declaration, keyed by where the declaration starts.
+        let mut usages: HashMap<usize, Usage> = HashMap::new();
+        for name in self
+            .root
+            .descendants()
+            .into_iter()
+            .filter(|node| node.kind() == NodeKind::Name)
+        {
+            let token = match name_token(&name) {
+                Some(token) => token,
+                None => continue,
+            };
+            let offset = token.text_range().start;
+            let declaration = declarations
+                .iter()
+                .filter(|declaration| has_name(declaration, token.text()))
+                .filter(|declaration| is_visible(declaration, offset))
+                .max_by_key(|declaration| declaration.text_range().start);
+            let declaration = match declaration {
+                Some(declaration) => declaration,
+                None => continue,
+            };
+            let usage = usages.entry(declaration.text_range().start).or_default();
+            let parent = name.parent().unwrap();
+            let is_first_child =
+                parent.children().first().map(SyntaxNode::text_range) == Some(name.text_range());
+            match parent.kind() {
+                NodeKind::Assignment if is_first_child => usage.writes += 1,
+                NodeKind::Input => usage.writes += 1,
+                // The variable after NEXT only names the loop being closed.
+                NodeKind::For => (),
+                // Arguments may be passed BYREF and assigned by the callee, and variables
+                // may be assigned through a pointer to them.
+                NodeKind::ArgList | NodeKind::AddressOf => {
+                    usage.reads += 1;
+                    usage.writes += 1;
+                }
+                _ => usage.reads += 1,
+            }
+        }
+
+        for declaration in declarations.iter() {
+            let token = match name_token(declaration) {
+                Some(token) => token,
+                None => continue,
+            };
+            let name = token.text().to_string();
+            let mut usage = usages
+                .remove(&declaration.text_range().start)
+                .unwrap_or_default();
+            // A DEFINE assigns the set its values.
+            if declaration.kind() == NodeKind::Define {
+                usage.writes += 1;
+            }
+            if declaration.kind() == NodeKind::Param {
+                let is_byref = declaration
+                    .child_tokens()
+                    .iter()
+                    .any(|token| token.kind() == &TokenKind::Keyword(KeywordKind::ByRef));
+                if usage.reads == 0 && !(is_byref && usage.writes > 0) {
+                    let msg = format!("parameter `{}` is never used.", name);
+                    self.warn(Lint::UnusedParameter, &token, msg);
+                }
+            } else if usage.reads == 0 {
+                let msg = match usage.writes {
+                    0 => format!("`{}` is never used.", name),
+                    _ => format!("`{}` is assigned to but its value is never used.", name),
+                };
+                self.warn(Lint::UnusedVariable, &token, msg);
+            } else if usage.writes == 0 {
+                let msg = format!("`{}` is used but never assigned a value.", name);
+                self.warn(Lint::NeverAssigned, &token, msg);
+            }
+
+            let offset = token.text_range().start;
+            let shadowed = declarations
+                .iter()
+                .filter(|other| other.text_range() != declaration.text_range())
+                .find(|other| has_name(other, &name) && is_visible(other, offset));
+            if let Some(shadowed) = shadowed {
+                let line = name_token(shadowed).unwrap().token().line + 1;
+                let msg = format!("`{}` shadows the variable declared on line {}.", name, line);
+                self.warn(Lint::ShadowedName, &token, msg);
+            } else if let Some(callable) =
+                callables.iter().find(|callable| has_name(callable, &name))
+            {
+                let line = name_token(callable).unwrap().token().line + 1;
+                let msg = format!(
+                    "`{}` shadows the PROCEDURE or FUNCTION declared on line {}.",
+                    name, line
+                );
+                self.warn(Lint::ShadowedName, &token, msg);
+            }
+        }
+    }
+
+    /// Statements following one that always RETURNs.
+    fn unreachable_code(&mut self) {
+        for block in self
+            .root
+            .descendants()
+            .into_iter()
+            .filter(|node| node.kind() == NodeKind::Block)
+        {
+            let stmts = block.children();
+            if let Some(idx) = stmts.iter().position(always_returns) {
+                if let Some(unreachable) = stmts.get(idx + 1).and_then(first_token) {
+                    let msg = String::from("unreachable code after RETURN.");
+                    self.warn(Lint::UnreachableCode, &unreachable, msg);
+                }
+            }
+        }
+    }
+
+    fn missing_returns(&mut self) {
+        for function in self
+            .root
+            .children()
+            .into_iter()
+            .filter(|node| node.kind() == NodeKind::Function)
+        {
+            let body = function
+                .children()
+                .into_iter()
+                .find(|node| node.kind() == NodeKind::Block);
+            if body.is_some_and(|body| always_returns(&body)) {
+                continue;
+            }
+            if let Some(name) = name_token(&function) {
+                let msg = format!(
+                    "not every path through FUNCTION `{}` RETURNs a value.",
+                    name.text()
+                );
+                self.warn(Lint::MissingReturn, &name, msg);
+            }
+        }
+    }
+
+    /// WHILE loops whose condition is always TRUE and REPEAT loops that never reach their
+    /// UNTIL condition, without a RETURN to leave them.
+    fn infinite_loops(&mut self) {
+        for node in self.root.descendants() {
+            let never_ends = match node.kind() {
+                NodeKind::While => condition(&node).and_then(|cond| constant(&cond)) == Some(true),
+                NodeKind::Repeat => {
+                    condition(&node).and_then(|cond| constant(&cond)) == Some(false)
+                }
+                _ => continue,
+            };
+            let returns = node
+                .descendants()
+                .iter()
+                .any(|node| node.kind() == NodeKind::Return);
+            if never_ends && !returns {
+                let msg = String::from("this loop never ends, its condition is always the same.");
+                self.warn(Lint::InfiniteLoop, &first_token(&node).unwrap(), msg);
+            }
+        }
+    }
+}
+
+fn first_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.tokens().into_iter().find(|token| !token.is_trivia())
+}
+
+/// The identifier a declaration introduces, or that a `Name` refers to.
+fn name_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.child_tokens()
+        .into_iter()
+        .find(|token| matches!(token.kind(), TokenKind::Identifier(_)))
+}
+
+fn has_name(node: &SyntaxNode, name: &str) -> bool {
+    name_token(node).is_some_and(|token| token.text() == name)
+}
+
+/// Whether the variable declared by `declaration` can be referred to at `offset`.
+fn is_visible(declaration: &SyntaxNode, offset: usize) -> bool {
+    let scope = match declaration.kind() {
+        // Parameters belong to the whole PROCEDURE or FUNCTION.
+        NodeKind::Param => declaration.parent().and_then(|list| list.parent()),
+        _ => declaration.parent(),
+    };
+    let scope = match scope {
+        Some(scope) => scope,
+        None => return false,
+    };
+    // Globals are visible to every PROCEDURE/FUNCTION, wherever they are declared.
+    let is_global = scope.kind() == NodeKind::Root;
+    scope.text_range().contains(&offset) && (is_global || declaration.text_range().start < offset)
+}
+
+/// Whether every path through the statement or block ends in a RETURN.
+fn always_returns(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        NodeKind::Return => true,
+        NodeKind::Block => node.children().iter().any(always_returns),
+        NodeKind::Repeat => child_block(node).is_some_and(|block| always_returns(&block)),
+        // The EXCEPT block runs instead of the rest of the TRY block, so both have to return.
+        NodeKind::If | NodeKind::Try => {
+            let else_block = node
+                .children()
+                .into_iter()
+                .find(|child| matches!(child.kind(), NodeKind::Else | NodeKind::Except))
+                .and_then(|else_node| child_block(&else_node));
+            match (child_block(node), else_block) {
+                (Some(then_block), Some(else_block)) => {
+                    always_returns(&then_block) && always_returns(&else_block)
+                }
+                _ => false,
+            }
+        }
+        NodeKind::Case => {
+            let arms: Vec<SyntaxNode> = node
+                .children()
+                .into_iter()
+                .filter(|child| child.kind() == NodeKind::CaseArm)
+                .collect();
+            let has_otherwise = arms.iter().any(|arm| {
+                arm.child_tokens()
+                    .iter()
+                    .any(|token| token.kind() == &TokenKind::Keyword(KeywordKind::Otherwise))
+            });
+            has_otherwise
+                && arms
+                    .iter()
+                    .all(|arm| child_block(arm).is_some_and(|block| always_returns(&block)))
+        }
+        _ => false,
+    }
+}
+
+fn child_block(node: &SyntaxNode) -> Option<SyntaxNode> {
+    node.children()
+        .into_iter()
+        .find(|child| child.kind() == NodeKind::Block)
+}
+
+/// The condition of a WHILE or REPEAT loop.
+fn condition(node: &SyntaxNode) -> Option<SyntaxNode> {
+    node.children()
+        .into_iter()
+        .find(|child| child.kind() != NodeKind::Block)
+}
+
+/// The value of a BOOLEAN expression made only of literals.
+fn constant(expr: &SyntaxNode) -> Option<bool> {
+    let operator = || {
+        expr.child_tokens()
+            .into_iter()
+            .map(|token| token.kind().clone())
+            .next()
+    };
+    let operands = expr.children();
+    match expr.kind() {
+        NodeKind::Literal => match operator()? {
+            TokenKind::Keyword(KeywordKind::True) => Some(true),
+            TokenKind::Keyword(KeywordKind::False) => Some(false),
+            _ => None,
+        },
+        NodeKind::Paren => constant(operands.first()?),
+        NodeKind::Unary if operator()? == TokenKind::Keyword(KeywordKind::Not) => {
+            constant(operands.first()?).map(|value| !value)
+        }
+        NodeKind::Binary => {
+            let (lhs, rhs) = (operands.first()?, operands.get(1)?);
+            match operator()? {
+                TokenKind::Keyword(KeywordKind::And) => Some(constant(lhs)? && constant(rhs)?),
+                TokenKind::Keyword(KeywordKind::Or) => Some(constant(lhs)? || constant(rhs)?),
+                TokenKind::Equal
+                    if lhs.kind() == NodeKind::Literal && rhs.kind() == NodeKind::Literal =>
+                {
+                    Some(lhs.text().trim() == rhs.text().trim())
+                }
+                TokenKind::NotEqual
+                    if lhs.kind() == NodeKind::Literal && rhs.kind() == NodeKind::Literal =>
+                {
+                    Some(lhs.text().trim() != rhs.text().trim())
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,169 @@
+use super::{lint, Lint};
+use crate::lexer::dialect::Dialect;
+
+/// Checks the ids and one-based lines of the warnings reported for `src`.
+fn check_lints(src: &str, allowed: &[Lint], expect: Vec<(&str, usize)>) {
+    let report = lint(src, allowed, Dialect::Strict);
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    let actual: Vec<(&str, usize)> = report
+        .warnings
+        .iter()
+        .map(|warning| (warning.lint.id(), warning.token.line + 1))
+        .collect();
+    assert_eq!(actual, expect);
+}
+
+#[test]
+fn variables() {
+    check_lints(
+        "DECLARE Unused : INTEGER
+DECLARE Written : INTEGER
+DECLARE Shown : INTEGER
+PROCEDURE Show(Value : INTEGER, BYREF Out : INTEGER, Ignored : INTEGER)
+    Out <- Value
+ENDPROCEDURE
+Written <- 1
+OUTPUT Shown
+",
+        &[],
+        vec![
+            ("unused-variable", 1),
+            ("unused-variable", 2),
+            ("never-assigned", 3),
+            ("unused-parameter", 4),
+        ],
+    );
+}
+
+#[test]
+fn input_and_byref_arguments_assign() {
+    check_lints(
+        "PROCEDURE Fill(BYREF Out : INTEGER)
+    INPUT Out
+ENDPROCEDURE
+DECLARE A : INTEGER
+DECLARE B : INTEGER
+INPUT A
+CALL Fill(B)
+OUTPUT A, B
+",
+        &[],
+        vec![],
+    );
+}
+
+#[test]
+fn shadowed_names() {
+    check_lints(
+        "DECLARE X : INTEGER
+PROCEDURE P(X : INTEGER)
+    OUTPUT X
+ENDPROCEDURE
+PROCEDURE Q
+    DECLARE P : INTEGER
+    P <- 1
+    OUTPUT P
+ENDPROCEDURE
+X <- 1
+OUTPUT X
+",
+        &[],
+        vec![("shadowed-name", 2), ("shadowed-name", 6)],
+    );
+}
+
+#[test]
+fn returns() {
+    check_lints(
+        "FUNCTION Max(A : INTEGER, B : INTEGER) RETURNS INTEGER
+    IF A > B THEN
+        RETURN A
+    ENDIF
+ENDFUNCTION
+FUNCTION Sign(N : INTEGER) RETURNS INTEGER
+    IF N > 0 THEN
+        RETURN 1
+    ELSE
+        RETURN 0
+    ENDIF
+    OUTPUT \"never\"
+ENDFUNCTION
+FUNCTION Name(N : INTEGER) RETURNS STRING
+    CASE OF N
+        1 : RETURN \"one\"
+        OTHERWISE RETURN \"many\"
+    ENDCASE
+ENDFUNCTION
+",
+        &[],
+        vec![("missing-return", 1), ("unreachable-code", 12)],
+    );
+}
+
+#[test]
+fn falls_off_the_end() {
+    let report = lint(
+        "FUNCTION Double(N : INTEGER) RETURNS INTEGER
+    OUTPUT N * 2
+ENDFUNCTION
+",
+        &[],
+        Dialect::Strict,
+    );
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].lint, Lint::MissingReturn);
+    assert_eq!(
+        report.warnings[0].msg,
+        "not every path through FUNCTION `Double` RETURNs a value."
+    );
+}
+
+#[test]
+fn infinite_loops() {
+    check_lints(
+        "DECLARE I : INTEGER
+I <- 0
+WHILE NOT FALSE
+    I <- I + 1
+ENDWHILE
+REPEAT
+    I <- I - 1
+UNTIL 1 = 2
+WHILE I < 10
+    I <- I + 1
+ENDWHILE
+OUTPUT I
+",
+        &[],
+        vec![("infinite-loop", 3), ("infinite-loop", 6)],
+    );
+}
+
+#[test]
+fn lowercase_keywords() {
+    let report = lint("output \"hi\"\n", &[], Dialect::Strict);
+    assert!(!report.errors.is_empty());
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].lint, Lint::LowercaseKeyword);
+    assert_eq!(
+        report.warnings[0].msg,
+        "`output` is not a keyword, keywords are written in upper case: `OUTPUT`."
+    );
+}
+
+#[test]
+fn suppression() {
+    let src = "DECLARE A : INTEGER // lint: allow(unused-variable)
+// lint: allow(unused-variable, never-assigned)
+DECLARE B : INTEGER
+DECLARE C : INTEGER
+DECLARE D : INTEGER
+OUTPUT D
+";
+    check_lints(
+        src,
+        &[],
+        vec![("unused-variable", 4), ("never-assigned", 5)],
+    );
+    check_lints(src, &[Lint::NeverAssigned], vec![("unused-variable", 4)]);
+}
@@ -0,0 +1,352 @@
+use crate::{
+    error::{ParseError, TypeError},
+    lexer::{
+        dialect::Dialect,
+        keyword::KEYWORDS,
+        token::{Token, TokenKind},
+    },
+    naive_tc::{self, Symbol},
+    syntax::{self, NodeKind, SyntaxNode, SyntaxToken},
+};
+use serde_json::{json, Value};
+use std::ops::Range;
+
+// Values of the LSP `SymbolKind` and `CompletionItemKind` enumerations.
+const SYMBOL_FUNCTION: u32 = 12;
+const SYMBOL_VARIABLE: u32 = 13;
+const COMPLETION_FUNCTION: u32 = 3;
+const COMPLETION_VARIABLE: u32 = 6;
+const COMPLETION_KEYWORD: u32 = 14;
+
+/// An open source file, analysed once per change.
+pub struct Document {
+    text: String,
+    /// Byte offset of the start of every line.
+    line_starts: Vec<usize>,
+    root: SyntaxNode,
+    parse_errors: Vec<ParseError>,
+    type_error: Option<TypeError>,
+    symbols: Vec<Symbol>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        let parse = syntax::parse(&text, Dialect::default());
+        let mut parse_errors = parse.errors;
+        let mut type_error = None;
+        let mut symbols = Vec::new();
+        // The type checker needs a complete AST, so it only runs on programs without syntax errors.
+        if parse_errors.is_empty() {
+            match syntax::lower(&parse.root) {
+                Ok(decls) => {
+                    let (result, checked_symbols) = naive_tc::check(decls);
+                    type_error = result.err();
+                    symbols = checked_symbols;
+                }
+                Err(errors) => parse_errors = errors,
+            }
+        }
+        Self {
+            text,
+            line_starts,
+            root: parse.root,
+            parse_errors,
+            type_error,
+            symbols,
+        }
+    }
+
+    /// LSP position of byte `offset`, with the character counted in UTF-16 code units.
+    fn position(&self, offset: usize) -> Value {
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let character: usize = self.text[self.line_starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+        json!({ "line": line, "character": character })
+    }
+
+    fn range(&self, range: Range<usize>) -> Value {
+        json!({ "start": self.position(range.start), "end": self.position(range.end) })
+    }
+
+    /// Byte offset of an LSP position, clamped to the end of its line.
+    pub fn offset(&self, position: &Value) -> Option<usize> {
+        let line = position["line"].as_u64()? as usize;
+        let character = position["character"].as_u64()? as usize;
+        let start = *self.line_starts.get(line)?;
+        let mut units = 0;
+        for (idx, char) in self.text[start..].char_indices() {
+            if units >= character || char == '\n' {
+                return Some(start + idx);
+            }
+            units += char.len_utf16();
+        }
+        Some(self.text.len())
+    }
+
+    /// Byte range of `line` without its indentation and line break.
+    fn line_range(&self, line: usize) -> Range<usize> {
+        let start = self.line_starts[line];
+        let end = self.line_starts.get(line + 1).map_or(self.text.len(), |next| next - 1);
+        let text = &self.text[start..end];
+        let indent = text.len() - text.trim_start().len();
+        start + indent..start + text.trim_end().len()
+    }
+
+    pub fn diagnostics(&self) -> Vec<Value> {
+        let mut diagnostics: Vec<Value> = self
+            .parse_errors
+            .iter()
+            .map(|error| {
+                let range = match &error.token {
+                    Some(token) => self.token_range(token),
+                    None => self.text.len()..self.text.len(),
+                };
+                diagnostic(self.range(range), error.msg)
+            })
+            .collect();
+        if let Some(error) = &self.type_error {
+            let range = match &error.token {
+                Some(token) => self.token_range(token),
+                None => self.line_range(error.line.min(self.line_starts.len() - 1)),
+            };
+            diagnostics.push(diagnostic(self.range(range), &error.msg));
+        }
+        diagnostics
+    }
+
+    /// Byte range of a token reported by the compiler, or of its line if it can't be found
+    /// in the syntax tree.
+    fn token_range(&self, error_token: &Token) -> Range<usize> {
+        self.root
+            .tokens()
+            .iter()
+            .find(|token| {
+                let token = token.token();
+                (token.line, token.col, &token.kind)
+                    == (error_token.line, error_token.col, &error_token.kind)
+            })
+            .map(|token| token.text_range())
+            .unwrap_or_else(|| self.line_range(error_token.line.min(self.line_starts.len() - 1)))
+    }
+
+    /// The identifier touching `offset`, either under or just before the cursor.
+    fn identifier_at(&self, offset: usize) -> Option<SyntaxToken> {
+        let is_identifier =
+            |token: &SyntaxToken| matches!(token.kind(), TokenKind::Identifier(_));
+        self.root
+            .token_at_offset(offset)
+            .filter(is_identifier)
+            .or_else(|| self.root.token_at_offset(offset.checked_sub(1)?).filter(is_identifier))
+    }
+
+    fn callables(&self) -> Vec<SyntaxNode> {
+        self.root
+            .children()
+            .into_iter()
+            .filter(|node| matches!(node.kind(), NodeKind::Procedure | NodeKind::Function))
+            .collect()
+    }
+
+    /// DECLAREs and DEFINEs outside of any PROCEDURE or FUNCTION.
+    fn globals(&self) -> Vec<SyntaxNode> {
+        self.root
+            .descendants()
+            .into_iter()
+            .filter(|node| is_variable(node) && enclosing_callable(node).is_none())
+            .collect()
+    }
+
+    /// Parameters, DECLAREs and DEFINEs of `callable` that come before `offset`.
+    fn locals(&self, callable: &SyntaxNode, offset: usize) -> Vec<SyntaxNode> {
+        callable
+            .descendants()
+            .into_iter()
+            .filter(|node| node.kind() == NodeKind::Param || is_variable(node))
+            .filter(|node| node.text_range().start < offset)
+            .collect()
+    }
+
+    pub fn hover(&self, offset: usize) -> Option<Value> {
+        let token = self.identifier_at(offset)?;
+        let name = token.text();
+        let contents = match self.callables().into_iter().find(|node| has_name(node, name)) {
+            Some(callable) => header(&callable),
+            None => {
+                let scope = enclosing_callable(&token.parent()).and_then(|node| node_name(&node));
+                let line = token.token().line;
+                let symbol = self
+                    .symbols
+                    .iter()
+                    .filter(|symbol| symbol.name == name && symbol.line <= line)
+                    .rfind(|symbol| scope.is_some() && symbol.scope == scope)
+                    .or_else(|| {
+                        self.symbols
+                            .iter()
+                            .find(|symbol| symbol.name == name && symbol.scope.is_none())
+                    })?;
+                format!("{} : {}", symbol.name, symbol.pseudo_type)
+            }
+        };
+        Some(json!({
+            "contents": { "kind": "markdown", "value": format!("```\n{}\n```", contents) },
+            "range": self.range(token.text_range()),
+        }))
+    }
+
+    /// The name token of the PROCEDURE, FUNCTION, parameter or DECLARE that the
+    /// identifier at `offset` refers to.
+    pub fn definition(&self, offset: usize) -> Option<Value> {
+        let token = self.identifier_at(offset)?;
+        let name = token.text();
+        let callable = self.callables().into_iter().find(|node| has_name(node, name));
+        let local = || {
+            let enclosing = enclosing_callable(&token.parent())?;
+            self.locals(&enclosing, token.text_range().start)
+                .into_iter()
+                .rfind(|node| has_name(node, name))
+        };
+        let global = || self.globals().into_iter().find(|node| has_name(node, name));
+        let declaration = callable.or_else(local).or_else(global)?;
+        Some(self.range(name_token(&declaration)?.text_range()))
+    }
+
+    /// Identifiers in scope at `offset`, then every keyword.
+    pub fn completion(&self, offset: usize) -> Vec<Value> {
+        let mut items: Vec<(String, u32)> = Vec::new();
+        let mut push = |name: String, kind: u32| {
+            if !items.iter().any(|(existing, _)| *existing == name) {
+                items.push((name, kind));
+            }
+        };
+        let enclosing = self
+            .root
+            .token_at_offset(offset.saturating_sub(1))
+            .and_then(|token| enclosing_callable(&token.parent()));
+        if let Some(callable) = enclosing {
+            self.locals(&callable, offset)
+                .iter()
+                .filter_map(node_name)
+                .for_each(|name| push(name, COMPLETION_VARIABLE));
+        }
+        self.globals()
+            .iter()
+            .filter_map(node_name)
+            .for_each(|name| push(name, COMPLETION_VARIABLE));
+        self.callables()
+            .iter()
+            .filter_map(node_name)
+            .for_each(|name| push(name, COMPLETION_FUNCTION));
+        KEYWORDS
+            .iter()
+            .for_each(|keyword| push(keyword.to_string(), COMPLETION_KEYWORD));
+        items
+            .into_iter()
+            .map(|(label, kind)| json!({ "label": label, "kind": kind }))
+            .collect()
+    }
+
+    /// PROCEDUREs and FUNCTIONs with their parameters and variables, and global variables.
+    pub fn document_symbols(&self) -> Vec<Value> {
+        let mut symbols = Vec::new();
+        for node in self.root.descendants() {
+            match node.kind() {
+                NodeKind::Procedure | NodeKind::Function => {
+                    let children: Vec<Value> = self
+                        .locals(&node, usize::MAX)
+                        .iter()
+                        .filter_map(|local| self.variable_symbol(local))
+                        .collect();
+                    if let Some(mut symbol) = self.symbol(&node, SYMBOL_FUNCTION, header(&node)) {
+                        symbol["children"] = json!(children);
+                        symbols.push(symbol);
+                    }
+                }
+                NodeKind::Declare | NodeKind::Define if enclosing_callable(&node).is_none() => {
+                    symbols.extend(self.variable_symbol(&node));
+                }
+                _ => (),
+            }
+        }
+        symbols
+    }
+
+    fn variable_symbol(&self, node: &SyntaxNode) -> Option<Value> {
+        let type_name = node
+            .children()
+            .into_iter()
+            .find(|child| child.kind() == NodeKind::TypeName)
+            .map(|type_name| type_name.text().trim().to_string())
+            .unwrap_or_default();
+        self.symbol(node, SYMBOL_VARIABLE, type_name)
+    }
+
+    fn symbol(&self, node: &SyntaxNode, kind: u32, detail: String) -> Option<Value> {
+        let name = name_token(node)?;
+        Some(json!({
+            "name": name.text(),
+            "detail": detail,
+            "kind": kind,
+            "range": self.range(significant_range(node)),
+            "selectionRange": self.range(name.text_range()),
+        }))
+    }
+}
+
+fn diagnostic(range: Value, msg: &str) -> Value {
+    json!({ "range": range, "severity": 1, "source": "pseudo_rs", "message": msg })
+}
+
+/// The identifier a PROCEDURE, FUNCTION, parameter or DECLARE introduces.
+fn name_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.child_tokens()
+        .into_iter()
+        .find(|token| matches!(token.kind(), TokenKind::Identifier(_)))
+}
+
+fn is_variable(node: &SyntaxNode) -> bool {
+    matches!(node.kind(), NodeKind::Declare | NodeKind::Define)
+}
+
+fn node_name(node: &SyntaxNode) -> Option<String> {
+    name_token(node).map(|token| token.text().to_string())
+}
+
+fn has_name(node: &SyntaxNode, name: &str) -> bool {
+    name_token(node).is_some_and(|token| token.text() == name)
+}
+
+fn enclosing_callable(node: &SyntaxNode) -> Option<SyntaxNode> {
+    std::iter::once(node.clone())
+        .chain(node.ancestors())
+        .find(|node| matches!(node.kind(), NodeKind::Procedure | NodeKind::Function))
+}
+
+/// The first line of a PROCEDURE or FUNCTION, e.g. `FUNCTION Add(A : INTEGER) RETURNS INTEGER`.
+fn header(node: &SyntaxNode) -> String {
+    node.tokens()
+        .iter()
+        .take_while(|token| token.kind() != &TokenKind::NewLine)
+        .map(|token| token.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Range of the node from its first to its last token, leaving out surrounding
+/// trivia and line breaks.
+fn significant_range(node: &SyntaxNode) -> Range<usize> {
+    let tokens: Vec<SyntaxToken> = node
+        .tokens()
+        .into_iter()
+        .filter(|token| !token.is_trivia() && token.kind() != &TokenKind::NewLine)
+        .collect();
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.text_range().start..last.text_range().end,
+        _ => node.text_range(),
+    }
+}
@@ -0,0 +1,175 @@
+//! A Language Server Protocol server, spoken over stdio by `pseudo_rs lsp`.
+//!
+//! Documents are fully re-analysed on every change: the lossless `syntax` tree backs
+//! navigation, and `naive_tc` supplies type errors and the types of variables.
+
+mod analysis;
+#[cfg(test)]
+mod tests;
+
+use self::analysis::Document;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Serves requests read from `reader` until the client sends `exit` or closes the stream.
+pub fn run(mut reader: impl BufRead, writer: impl Write) -> io::Result<()> {
+    let mut server = Server {
+        writer,
+        documents: HashMap::new(),
+    };
+    while let Some(message) = read_message(&mut reader)? {
+        match serde_json::from_slice::<Value>(&message) {
+            Ok(message) => {
+                if message["method"] == "exit" {
+                    break;
+                }
+                server.handle(message)?;
+            }
+            Err(error) => server.send(json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": PARSE_ERROR, "message": error.to_string() },
+            }))?,
+        }
+    }
+    Ok(())
+}
+
+/// Reads the body of the next message, framed by a `Content-Length` header.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "message without a Content-Length header")
+    })?;
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+struct Server<W> {
+    writer: W,
+    documents: HashMap<String, Document>,
+}
+
+impl<W: Write> Server<W> {
+    fn send(&mut self, message: Value) -> io::Result<()> {
+        let body = message.to_string();
+        write!(self.writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.writer.flush()
+    }
+
+    fn handle(&mut self, message: Value) -> io::Result<()> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    // Full document sync.
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {},
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "pseudo_rs" },
+            }),
+            "shutdown" => Value::Null,
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                return self.open(uri, text.to_string());
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                match changes.and_then(|changes| changes.last()?["text"].as_str()) {
+                    Some(text) => return self.open(uri, text.to_string()),
+                    None => return Ok(()),
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return self.publish_diagnostics(uri, Vec::new());
+            }
+            "textDocument/hover" => self
+                .at_position(&uri, params, Document::hover)
+                .unwrap_or(Value::Null),
+            "textDocument/definition" => self
+                .at_position(&uri, params, Document::definition)
+                .map(|range| json!({ "uri": uri, "range": range }))
+                .unwrap_or(Value::Null),
+            "textDocument/completion" => self
+                .at_position(&uri, params, |document, offset| Some(document.completion(offset)))
+                .map(|items| json!(items))
+                .unwrap_or(Value::Null),
+            "textDocument/documentSymbol" => match self.documents.get(&uri) {
+                Some(document) => json!(document.document_symbols()),
+                None => Value::Null,
+            },
+            _ => {
+                // Unknown notifications, such as `initialized`, are ignored.
+                if message.get("id").is_some() {
+                    self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": message["id"],
+                        "error": {
+                            "code": METHOD_NOT_FOUND,
+                            "message": format!("unsupported method `{}`", method),
+                        },
+                    }))?;
+                }
+                return Ok(());
+            }
+        };
+        self.send(json!({ "jsonrpc": "2.0", "id": message["id"], "result": result }))
+    }
+
+    fn open(&mut self, uri: String, text: String) -> io::Result<()> {
+        let document = Document::new(text);
+        let diagnostics = document.diagnostics();
+        self.documents.insert(uri.clone(), document);
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn publish_diagnostics(&mut self, uri: String, diagnostics: Vec<Value>) -> io::Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    /// Runs `query` at the position of a `TextDocumentPositionParams` request.
+    fn at_position<T>(
+        &self,
+        uri: &str,
+        params: &Value,
+        query: impl Fn(&Document, usize) -> Option<T>,
+    ) -> Option<T> {
+        let document = self.documents.get(uri)?;
+        let offset = document.offset(&params["position"])?;
+        query(document, offset)
+    }
+}
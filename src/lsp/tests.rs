@@ -0,0 +1,61 @@
+use super::{read_message, run};
+use serde_json::Value;
+
+/// Replays a recorded session: every `-->` line is sent to the server and the server must
+/// answer with exactly the `<--` lines, in order. Lines starting with `#` are comments.
+fn check_session(transcript: &str) {
+    let mut input = Vec::new();
+    let mut expected = Vec::new();
+    for line in transcript.lines() {
+        if let Some(message) = line.strip_prefix("--> ") {
+            input.extend(format!("Content-Length: {}\r\n\r\n{}", message.len(), message).bytes());
+        } else if let Some(message) = line.strip_prefix("<-- ") {
+            expected.push(serde_json::from_str::<Value>(message).unwrap());
+        }
+    }
+
+    let mut output = Vec::new();
+    run(input.as_slice(), &mut output).unwrap();
+
+    let mut output = output.as_slice();
+    let mut actual = Vec::new();
+    while let Some(message) = read_message(&mut output).unwrap() {
+        actual.push(serde_json::from_slice::<Value>(&message).unwrap());
+    }
+    assert_eq!(actual.len(), expected.len(), "got {:#?}", actual);
+    for (actual, expected) in actual.iter().zip(expected.iter()) {
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn lifecycle() {
+    check_session(include_str!("sessions/lifecycle.jsonrpc"));
+}
+
+#[test]
+fn diagnostics() {
+    check_session(include_str!("sessions/diagnostics.jsonrpc"));
+}
+
+#[test]
+fn navigation() {
+    check_session(include_str!("sessions/navigation.jsonrpc"));
+}
+
+#[test]
+fn completion() {
+    check_session(include_str!("sessions/completion.jsonrpc"));
+}
+
+#[test]
+fn unsupported() {
+    check_session(include_str!("sessions/unsupported.jsonrpc"));
+}
+
+#[test]
+fn utf16_positions() {
+    let document = super::analysis::Document::new(String::from("OUTPUT \"é😀\", X\n"));
+    let offset = document.offset(&serde_json::json!({ "line": 0, "character": 14 }));
+    assert_eq!(offset, Some("OUTPUT \"é😀\", ".len()));
+}
@@ -1,34 +1,192 @@
 mod args;
 mod debug;
 
-use args::Cli;
-use clap::Parser;
-
+use args::{Cli, Command, TraceArgs};
+use clap::{CommandFactory, Parser};
+use pseudo_rs::{vm::verifier::VerifyError, Options};
+use std::path::Path;
 
 fn main() {
     let cli = Cli::parse();
-    let src = match std::fs::read_to_string(cli.source_path.clone()) {
-        Ok(src) => src,
-        Err(_) =>  {
-            println!("Provided file path `{}` is not valid.", cli.source_path);
+
+    match cli.command {
+        Some(Command::Build {
+            source_path,
+            output,
+            dialect,
+        }) => {
+            let src = read_source(&source_path);
+            let output = match output {
+                Some(output) => output,
+                None => Path::new(&source_path)
+                    .with_extension("pbc")
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            let options = Options {
+                dialect: dialect.dialect,
+                ..Options::default()
+            };
+            let bytes = pseudo_rs::build(&src, &options);
+            if std::fs::write(&output, bytes).is_err() {
+                println!("Unable to write bytecode file `{}`.", output);
+            }
+            std::process::exit(0);
+        }
+        Some(Command::Fmt {
+            paths,
+            check,
+            dialect,
+        }) => {
+            let mut all_formatted = true;
+            for path in paths.iter() {
+                let src = read_source(path);
+                let formatted = match pseudo_rs::formatter::format(&src, dialect.dialect) {
+                    Ok(formatted) => formatted,
+                    Err(errors) => {
+                        println!("Unable to format `{}`, it has syntax errors:", path);
+                        pseudo_rs::error::print_parse_errors(&src, errors);
+                        all_formatted = false;
+                        continue;
+                    }
+                };
+                if formatted == src {
+                    continue;
+                }
+                if check {
+                    println!("`{}` is not formatted.", path);
+                    all_formatted = false;
+                } else if std::fs::write(path, formatted).is_err() {
+                    println!("Unable to write to `{}`.", path);
+                    all_formatted = false;
+                }
+            }
+            std::process::exit(if all_formatted { 0 } else { 1 });
+        }
+        Some(Command::Debug { source_path, run }) => {
+            let src = read_source(&source_path);
+            if let Err(error) = pseudo_rs::debug(&src, &run.options()) {
+                invalid_bytecode(error);
+            }
+            std::process::exit(0);
+        }
+        Some(Command::Lint {
+            paths,
+            allow,
+            dialect,
+        }) => {
+            let mut has_errors = false;
+            for path in paths.iter() {
+                let src = read_source(path);
+                let report = pseudo_rs::lint::lint(&src, &allow, dialect.dialect);
+                if !report.warnings.is_empty() || !report.errors.is_empty() {
+                    println!("{}:", path);
+                }
+                pseudo_rs::lint::print_warnings(&src, &report.warnings);
+                if !report.errors.is_empty() {
+                    pseudo_rs::error::print_parse_errors(&src, report.errors);
+                    has_errors = true;
+                }
+            }
+            std::process::exit(if has_errors { 1 } else { 0 });
+        }
+        Some(Command::Lsp) => {
+            let stdin = std::io::stdin();
+            let stdout = std::io::stdout();
+            if let Err(error) = pseudo_rs::lsp::run(stdin.lock(), stdout.lock()) {
+                eprintln!("error: {}", error);
+                std::process::exit(1);
+            }
+            std::process::exit(0);
+        }
+        Some(Command::Run { path, run, trace }) => {
+            let options = run.options();
+            let bytes = match std::fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(_) => {
+                    println!("Provided file path `{}` is not valid.", path);
+                    std::process::exit(0);
+                }
+            };
+            let is_bytecode = pseudo_rs::vm::serialize::is_bytecode(&bytes);
+            let result = if trace.trace_table {
+                run_traced(&bytes, is_bytecode, &options, trace)
+            } else if is_bytecode {
+                pseudo_rs::run_bytecode(&bytes, &options)
+            } else {
+                match String::from_utf8(bytes) {
+                    Ok(src) => pseudo_rs::interpret(&src, &options),
+                    Err(_) => {
+                        println!("Provided file `{}` is not valid UTF-8.", path);
+                        Ok(())
+                    }
+                }
+            };
+            if let Err(error) = result {
+                invalid_bytecode(error);
+            }
+            std::process::exit(0);
+        }
+        None => (),
+    }
+
+    let source_path = match cli.source_path {
+        Some(source_path) => source_path,
+        None => {
+            let _ = Cli::command().print_help();
             std::process::exit(0);
         }
     };
+    let src = read_source(&source_path);
+    let options = cli.run.options();
+    let dialect = options.dialect;
 
     if let Some(debug_mode) = cli.debug {
         use args::DebugMode::*;
         match debug_mode {
-            PrintAst => debug::print_ast(&src),
-            PrintTokens => debug::print_tokens(&src),
-            PrintBytecode => debug::print_bytecode(&src),
+            PrintAst => debug::print_ast(&src, dialect),
+            PrintTokens => debug::print_tokens(&src, dialect),
+            PrintBytecode => debug::print_bytecode(&src, dialect),
+            PrintCst => debug::print_cst(&src, dialect),
         };
         std::process::exit(0);
     };
 
     if cli.compile_to_c {
-        pseudo_rs::compile_to_c(&src);
+        pseudo_rs::compile_to_c(&src, &options);
         std::process::exit(0);
     }
 
-    pseudo_rs::interpret(&src);
+    let result = match cli.trace.trace_table {
+        true => run_traced(src.as_bytes(), false, &options, cli.trace),
+        false => pseudo_rs::interpret(&src, &options),
+    };
+    if let Err(error) = result {
+        invalid_bytecode(error);
+    }
+}
+
+fn run_traced(
+    file: &[u8],
+    is_bytecode: bool,
+    options: &Options,
+    trace: TraceArgs,
+) -> Result<(), VerifyError> {
+    pseudo_rs::trace(file, is_bytecode, options, trace.trace_vars, trace.trace_format)
+}
+
+/// Reports bytecode the verifier rejected and exits with a failure status.
+fn invalid_bytecode(error: VerifyError) -> ! {
+    println!("error: invalid bytecode: {}", error);
+    std::process::exit(1);
+}
+
+fn read_source(source_path: &str) -> String {
+    match std::fs::read_to_string(source_path) {
+        Ok(src) => src,
+        Err(_) => {
+            println!("Provided file path `{}` is not valid.", source_path);
+            std::process::exit(0);
+        }
+    }
 }
@@ -0,0 +1,632 @@
+use super::{
+    decl::{ir_params, CallableKind, IrParam},
+    types::pseudo_type,
+    TypeChecker,
+};
+use crate::{
+    error::{TypeError, TypeResult},
+    ir::{
+        ast::{self, Access, Member, MemberKind},
+        hlir::{self, MethodRef, Type, RECEIVER},
+    },
+    lexer::token::{Token, TokenKind},
+};
+use std::collections::HashMap;
+
+/// What the type checker knows about a CLASS.
+pub struct Class {
+    pub pseudo_type: Type,
+    /// Every attribute, inherited ones first, in the order they're stored in an object.
+    pub attributes: Vec<Attribute>,
+    /// Every method, inherited ones first, by slot. An overriding method takes the slot of
+    /// the one it overrides.
+    pub methods: Vec<Method>,
+}
+
+#[derive(Clone)]
+pub struct Attribute {
+    pub name: String,
+    pub pseudo_type: Type,
+    pub access: Access,
+    /// The CLASS declaring it.
+    pub owner: String,
+}
+
+#[derive(Clone)]
+pub struct Method {
+    pub name: String,
+    pub kind: CallableKind,
+    pub params: Vec<IrParam>,
+    pub return_type: Option<Type>,
+    pub access: Access,
+    /// The CLASS declaring this version of it.
+    pub owner: String,
+}
+
+impl Method {
+    /// The PROCEDURE/FUNCTION this version of the method is compiled to.
+    pub fn function_name(&self) -> String {
+        format!("{}.{}", self.owner, self.name)
+    }
+}
+
+/// A checked call of a method.
+pub struct MethodCall {
+    pub kind: CallableKind,
+    pub return_type: Option<Type>,
+    pub dispatch: Dispatch,
+}
+
+pub enum Dispatch {
+    /// A call through SUPER, of the parent's version whatever the object's class. The
+    /// object is the first of `args`.
+    Static { name: String, args: Vec<hlir::Expr> },
+    /// A call of the version in the object's class.
+    Virtual {
+        object: hlir::Expr,
+        method: MethodRef,
+        args: Vec<hlir::Expr>,
+    },
+}
+
+impl MethodCall {
+    pub fn into_expr(self) -> hlir::ExprKind {
+        match self.dispatch {
+            Dispatch::Static { name, args } => hlir::ExprKind::Call { callee: name, args },
+            Dispatch::Virtual {
+                object,
+                method,
+                args,
+            } => hlir::ExprKind::MethodCall {
+                object: Box::new(object),
+                method,
+                args,
+            },
+        }
+    }
+
+    pub fn into_stmt(self) -> hlir::StmtKind {
+        match self.dispatch {
+            Dispatch::Static { name, args } => hlir::StmtKind::Call { name, args },
+            Dispatch::Virtual {
+                object,
+                method,
+                args,
+            } => hlir::StmtKind::CallMethod {
+                object,
+                method,
+                args,
+            },
+        }
+    }
+}
+
+impl TypeChecker {
+    /// Makes every CLASS usable as a type, before anything else is declared. A CLASS may
+    /// inherit from one declared after it.
+    pub fn declare_classes(&mut self, decls: &[ast::Decl]) -> TypeResult<()> {
+        let mut headers = HashMap::new();
+        for decl in decls.iter() {
+            if let ast::Decl::Class {
+                name, parent, line, ..
+            } = decl
+            {
+                if headers.insert(name.as_str(), (parent.as_deref(), *line)).is_some() {
+                    return Err(TypeError::new(format!("CLASS `{}` declared twice.", name), *line));
+                }
+            }
+        }
+        for decl in decls.iter() {
+            if let ast::Decl::Class { name, .. } = decl {
+                self.class_type(name, &headers, &mut Vec::new())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Declares the type of the CLASS `name` after those of its ancestors. `inheriting`
+    /// holds the classes waiting on it, to catch a CLASS inheriting from itself.
+    fn class_type<'a>(
+        &mut self,
+        name: &'a str,
+        headers: &HashMap<&'a str, (Option<&'a str>, usize)>,
+        inheriting: &mut Vec<&'a str>,
+    ) -> TypeResult<Type> {
+        if let Some(pseudo_type) = self.types.get(name) {
+            return Ok(*pseudo_type);
+        }
+        let (parent, line) = headers[name];
+        let parent = match parent {
+            Some(parent) if parent == name || inheriting.contains(&parent) => {
+                return Err(TypeError::new(
+                    format!("CLASS `{}` inherits from itself.", name),
+                    line,
+                ))
+            }
+            Some(parent) if !headers.contains_key(parent) => {
+                return Err(TypeError::new(
+                    format!("CLASS `{}` inherits from `{}`, which isn't a CLASS.", name, parent),
+                    line,
+                ))
+            }
+            Some(parent) => {
+                inheriting.push(name);
+                let parent = self.class_type(parent, headers, inheriting)?;
+                inheriting.pop();
+                match parent {
+                    Type::Class(parent) => Some(parent),
+                    _ => unreachable!("only CLASSes are declared so far"),
+                }
+            }
+            None => None,
+        };
+        let pseudo_type = Type::class(name.to_string(), parent);
+        self.types.insert(name.to_string(), pseudo_type);
+        Ok(pseudo_type)
+    }
+
+    /// Collects the members of every CLASS, parents first so that their members can be
+    /// inherited.
+    pub fn define_classes(&mut self, decls: &[ast::Decl]) -> TypeResult<()> {
+        let mut classes: Vec<(&String, &Vec<Member>)> = decls
+            .iter()
+            .filter_map(|decl| match decl {
+                ast::Decl::Class { name, members, .. } => Some((name, members)),
+                _ => None,
+            })
+            .collect();
+        classes.sort_by_key(|(name, _)| match self.types[*name] {
+            Type::Class(mut class_type) => {
+                let mut depth = 0;
+                while let Some(parent) = class_type.parent {
+                    class_type = parent;
+                    depth += 1;
+                }
+                depth
+            }
+            _ => unreachable!(),
+        });
+        for (name, members) in classes {
+            self.define_class(name, members)?;
+        }
+        Ok(())
+    }
+
+    fn define_class(&mut self, name: &str, members: &[Member]) -> TypeResult<()> {
+        let class_type = self.types[name];
+        let (mut attributes, mut methods) = match class_type {
+            Type::Class(class_type) => match class_type.parent {
+                Some(parent) => {
+                    let parent = &self.classes[&parent.name];
+                    (parent.attributes.clone(), parent.methods.clone())
+                }
+                None => (Vec::new(), Vec::new()),
+            },
+            _ => unreachable!(),
+        };
+
+        let already_declared = |member: &str, line: usize| {
+            TypeError::new(
+                format!("`{}` is already a member of CLASS `{}`.", member, name),
+                line,
+            )
+        };
+        for member in members.iter() {
+            match &member.kind {
+                MemberKind::Attribute {
+                    name: attribute,
+                    type_name,
+                    line,
+                } => {
+                    if attributes.iter().any(|other| &other.name == attribute)
+                        || methods.iter().any(|other| &other.name == attribute)
+                    {
+                        return Err(already_declared(attribute, *line));
+                    }
+                    attributes.push(Attribute {
+                        name: attribute.clone(),
+                        pseudo_type: pseudo_type(type_name, &self.types, *line)?,
+                        access: member.access,
+                        owner: name.to_string(),
+                    });
+                }
+                MemberKind::Method(decl) => {
+                    let method = self.method_header(name, decl, member.access)?;
+                    let line = decl_line(decl);
+                    if attributes.iter().any(|other| other.name == method.name) {
+                        return Err(already_declared(&method.name, line));
+                    }
+                    match methods.iter().position(|other| other.name == method.name) {
+                        Some(slot) if methods[slot].owner == name => {
+                            return Err(already_declared(&method.name, line))
+                        }
+                        Some(slot) => {
+                            check_override(&methods[slot], &method, line)?;
+                            methods[slot] = method;
+                        }
+                        None => methods.push(method),
+                    }
+                }
+            }
+        }
+
+        self.classes.insert(
+            name.to_string(),
+            Class {
+                pseudo_type: class_type,
+                attributes,
+                methods,
+            },
+        );
+        Ok(())
+    }
+
+    fn method_header(&self, class: &str, decl: &ast::Decl, access: Access) -> TypeResult<Method> {
+        let (name, kind, params, return_type) = match decl {
+            ast::Decl::Procedure { name, params, body } => (
+                name,
+                CallableKind::Procedure,
+                ir_params(params, &self.types, body.line)?,
+                None,
+            ),
+            ast::Decl::Function {
+                name,
+                params,
+                body,
+                return_type_name,
+            } => {
+                if name == "NEW" {
+                    return Err(TypeError::new(
+                        "The constructor NEW must be a PROCEDURE.",
+                        body.line,
+                    ));
+                }
+                (
+                    name,
+                    CallableKind::Function,
+                    ir_params(params, &self.types, body.line)?,
+                    Some(pseudo_type(return_type_name, &self.types, body.line)?),
+                )
+            }
+            _ => unreachable!("the parser only accepts PROCEDUREs and FUNCTIONs as methods"),
+        };
+        Ok(Method {
+            name: name.clone(),
+            kind,
+            params,
+            return_type,
+            access,
+            owner: class.to_string(),
+        })
+    }
+
+    /// Checks the methods of the CLASS `name`, compiling each to a PROCEDURE/FUNCTION
+    /// taking the object as an extra first parameter.
+    pub fn class(&mut self, name: String, members: Vec<Member>) -> TypeResult<Vec<hlir::Decl>> {
+        let receiver = ast::Param {
+            name: RECEIVER.to_string(),
+            type_name: ast::TypeName::BaseTypeName(ast::BaseTypeName::Identifier(name.clone())),
+            passing_mode: None,
+        };
+        let previous_class = self.current_class.replace(name.clone());
+        let mut decls = Vec::new();
+        for member in members.into_iter() {
+            let method = match member.kind {
+                MemberKind::Method(method) => *method,
+                MemberKind::Attribute { .. } => continue,
+            };
+            let decl = match method {
+                ast::Decl::Procedure {
+                    name: method,
+                    mut params,
+                    body,
+                } => {
+                    params.insert(0, receiver.clone());
+                    ast::Decl::Procedure {
+                        name: format!("{}.{}", name, method),
+                        params,
+                        body,
+                    }
+                }
+                ast::Decl::Function {
+                    name: method,
+                    mut params,
+                    body,
+                    return_type_name,
+                } => {
+                    params.insert(0, receiver.clone());
+                    ast::Decl::Function {
+                        name: format!("{}.{}", name, method),
+                        params,
+                        body,
+                        return_type_name,
+                    }
+                }
+                _ => unreachable!("the parser only accepts PROCEDUREs and FUNCTIONs as methods"),
+            };
+            decls.push(self.decl(decl)?);
+        }
+        self.current_class = previous_class;
+
+        let class = &self.classes[&name];
+        let class_type = match class.pseudo_type {
+            Type::Class(class_type) => class_type,
+            _ => unreachable!("CLASSes have CLASS types"),
+        };
+        decls.push(hlir::Decl::Class {
+            class_type,
+            attributes: class
+                .attributes
+                .iter()
+                .map(|attribute| attribute.pseudo_type)
+                .collect(),
+            methods: class.methods.iter().map(Method::function_name).collect(),
+            name,
+        });
+        Ok(decls)
+    }
+
+    /// The object the method being checked was called on.
+    pub fn receiver(&self) -> Option<hlir::Expr> {
+        let class = self.current_class.as_ref()?;
+        Some(hlir::Expr {
+            pseudo_type: self.classes[class].pseudo_type,
+            expr_kind: hlir::ExprKind::Variable {
+                name: RECEIVER.to_string(),
+                token: Token::new(TokenKind::Identifier(RECEIVER.to_string()), self.line, 0, 0),
+            },
+        })
+    }
+
+    /// Whether `name` is an attribute or method of the object a method was called on,
+    /// used without naming the object. Parameters and local variables hide them.
+    pub fn is_own_member(&self, name: &str) -> bool {
+        let class = match &self.current_class {
+            Some(class) => &self.classes[class],
+            None => return false,
+        };
+        let is_local = self.symbol_table_stack[1..]
+            .iter()
+            .any(|symbol_table| symbol_table.contains_key(name));
+        !is_local
+            && (class.attributes.iter().any(|attribute| attribute.name == name)
+                || class.methods.iter().any(|method| method.name == name))
+    }
+
+    /// Finds the attribute `name` of an object of type `object_type`, by its index and type.
+    pub fn attribute(
+        &self,
+        object_type: Type,
+        name: &str,
+        token: &Token,
+    ) -> TypeResult<(usize, Type)> {
+        let class = self.class_of(object_type, token)?;
+        match class
+            .attributes
+            .iter()
+            .enumerate()
+            .find(|(_, attribute)| attribute.name == name)
+        {
+            Some((index, attribute)) => {
+                self.check_access(name, attribute.access, &attribute.owner, token)?;
+                Ok((index, attribute.pseudo_type))
+            }
+            None => Err(TypeError::at(
+                format!("`{}` has no attribute `{}`.", object_type, name),
+                token.clone(),
+            )),
+        }
+    }
+
+    /// The object a method is called on. SUPER is the object the method being checked was
+    /// called on, seen as one of the parent CLASS, and is `true` alongside it.
+    pub fn method_object(&mut self, object: ast::ExprKind) -> TypeResult<(hlir::Expr, bool)> {
+        let token = match object {
+            ast::ExprKind::Super { token } => token,
+            object => return Ok((self.expr(object)?, false)),
+        };
+        let parent = self
+            .current_class
+            .as_ref()
+            .and_then(|class| match self.classes[class].pseudo_type {
+                Type::Class(class_type) => class_type.parent,
+                _ => None,
+            });
+        match (parent, self.receiver()) {
+            (Some(parent), Some(receiver)) => Ok((
+                hlir::Expr {
+                    pseudo_type: Type::Class(parent),
+                    ..receiver
+                },
+                true,
+            )),
+            _ => Err(TypeError::at(
+                "SUPER can only be used in a method of a CLASS that INHERITS from another.",
+                token,
+            )),
+        }
+    }
+
+    /// Checks a call of the method `name` on `object`. Through SUPER, the call is of the
+    /// parent's version whatever the class of the object.
+    pub fn method_call(
+        &mut self,
+        (object, is_super): (hlir::Expr, bool),
+        name: &str,
+        token: &Token,
+        args: Vec<ast::ExprKind>,
+    ) -> TypeResult<MethodCall> {
+        let args = args
+            .into_iter()
+            .map(|arg| self.expr(arg))
+            .collect::<TypeResult<Vec<hlir::Expr>>>()?;
+        let class = self.class_of(object.pseudo_type, token)?;
+        let (slot, method) = match class
+            .methods
+            .iter()
+            .enumerate()
+            .find(|(_, method)| method.name == name)
+        {
+            Some(found) => found,
+            None => {
+                return Err(TypeError::at(
+                    format!("`{}` has no method `{}`.", object.pseudo_type, name),
+                    token.clone(),
+                ))
+            }
+        };
+        self.check_access(name, method.access, &method.owner, token)?;
+        if args.len() != method.params.len() {
+            return Err(self.error("Wrong number of arguments"));
+        }
+        for (param, arg) in method.params.iter().zip(args.iter()) {
+            if !param.pseudo_type.accepts(arg.pseudo_type) {
+                return Err(self.error("Wrong type of argument"));
+            }
+        }
+
+        let method_ref = MethodRef {
+            name: method.function_name(),
+            slot,
+        };
+        let kind = method.kind;
+        let return_type = method.return_type;
+        let dispatch = match is_super {
+            true => Dispatch::Static {
+                name: method_ref.name,
+                args: std::iter::once(object).chain(args).collect(),
+            },
+            false => Dispatch::Virtual {
+                object,
+                method: method_ref,
+                args,
+            },
+        };
+        Ok(MethodCall {
+            kind,
+            return_type,
+            dispatch,
+        })
+    }
+
+    /// Checks `NEW <class>(<arg>, ...)`, passing the arguments to the constructor.
+    pub fn construct(
+        &mut self,
+        class_type: Type,
+        args: Vec<ast::ExprKind>,
+    ) -> TypeResult<hlir::ExprKind> {
+        let name = match class_type {
+            Type::Class(class_type) => class_type.name.clone(),
+            _ => unreachable!(),
+        };
+        let args = args
+            .into_iter()
+            .map(|arg| self.expr(arg))
+            .collect::<TypeResult<Vec<hlir::Expr>>>()?;
+        let class = &self.classes[&name];
+        let constructor = class
+            .methods
+            .iter()
+            .enumerate()
+            .find(|(_, method)| method.name == "NEW");
+        let params: &[IrParam] = match constructor {
+            Some((_, method)) => {
+                if method.access == Access::Private
+                    && self.current_class.as_ref() != Some(&method.owner)
+                {
+                    return Err(self.error(format!("NEW is PRIVATE to `{}`.", method.owner)));
+                }
+                &method.params
+            }
+            None => &[],
+        };
+        if args.len() != params.len() {
+            return Err(self.error("Wrong number of arguments"));
+        }
+        for (param, arg) in params.iter().zip(args.iter()) {
+            if !param.pseudo_type.accepts(arg.pseudo_type) {
+                return Err(self.error("Wrong type of argument"));
+            }
+        }
+        let constructor = constructor.map(|(slot, method)| MethodRef {
+            name: method.function_name(),
+            slot,
+        });
+        Ok(hlir::ExprKind::Construct {
+            class: name,
+            constructor,
+            args,
+        })
+    }
+
+    fn class_of(&self, object_type: Type, token: &Token) -> TypeResult<&Class> {
+        match object_type {
+            Type::Class(class_type) => Ok(&self.classes[&class_type.name]),
+            _ => Err(TypeError::at(
+                format!("Only objects have attributes and methods, got {}.", object_type),
+                token.clone(),
+            )),
+        }
+    }
+
+    /// PRIVATE members can only be used in the methods of the CLASS declaring them.
+    fn check_access(&self, name: &str, access: Access, owner: &str, token: &Token) -> TypeResult<()> {
+        match access {
+            Access::Private if self.current_class.as_deref() != Some(owner) => Err(TypeError::at(
+                format!("`{}` is PRIVATE to `{}`.", name, owner),
+                token.clone(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A method may only override one it could have called, taking the same parameters and
+/// returning the same type. The constructor may take anything.
+fn check_override(overridden: &Method, method: &Method, line: usize) -> TypeResult<()> {
+    if method.name == "NEW" {
+        return Ok(());
+    }
+    if overridden.access == Access::Private {
+        return Err(TypeError::new(
+            format!(
+                "`{}` is PRIVATE to `{}`, so it can't be overridden.",
+                method.name, overridden.owner
+            ),
+            line,
+        ));
+    }
+    let same_params = overridden.params.len() == method.params.len()
+        && overridden
+            .params
+            .iter()
+            .zip(method.params.iter())
+            .all(|(a, b)| a.pseudo_type == b.pseudo_type);
+    if overridden.kind != method.kind || overridden.return_type != method.return_type || !same_params
+    {
+        return Err(TypeError::new(
+            format!(
+                "`{}` must take the same parameters and return the same type as the method it overrides in `{}`.",
+                method.name, overridden.owner
+            ),
+            line,
+        ));
+    }
+    if method.access == Access::Private {
+        return Err(TypeError::new(
+            format!(
+                "`{}` must be PUBLIC, like the method it overrides in `{}`.",
+                method.name, overridden.owner
+            ),
+            line,
+        ));
+    }
+    Ok(())
+}
+
+fn decl_line(decl: &ast::Decl) -> usize {
+    match decl {
+        ast::Decl::Procedure { body, .. } | ast::Decl::Function { body, .. } => body.line,
+        _ => unreachable!(),
+    }
+}
@@ -1,13 +1,14 @@
 use std::collections::HashMap;
 
 use crate::{
+    error::{TypeError, TypeResult},
     ir::{ast, hlir},
     naive_tc::TypeChecker,
 };
 
 use super::types::pseudo_type;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 pub enum CallableKind {
     Procedure,
     Function,
@@ -17,68 +18,93 @@ pub struct Callable {
     pub kind: CallableKind,
     pub params: Vec<IrParam>,
     pub return_type: Option<hlir::Type>,
+    /// Zero-based line of the header.
+    pub line: usize,
 }
 
+#[derive(Clone)]
 pub struct IrParam {
     pub pseudo_type: hlir::Type,
 }
 
-pub fn define_decl(decl: ast::Decl, map: &mut HashMap<String, Callable>) {
+pub fn define_decl(
+    decl: &ast::Decl,
+    types: &HashMap<String, hlir::Type>,
+    map: &mut HashMap<String, Callable>,
+) -> TypeResult<()> {
     match decl {
-        ast::Decl::Procedure { name, params, .. } => {
-            if map.contains_key(&name) {
-                unimplemented!("PROCEDURE defined twice.");
+        ast::Decl::Procedure { name, params, body } => {
+            if map.contains_key(name) {
+                return Err(TypeError::new("PROCEDURE defined twice.", body.line));
+            }
+            if name == "NEW" {
+                return Err(TypeError::new(
+                    "Only a CLASS can have a constructor NEW.",
+                    body.line,
+                ));
             }
             let callable = Callable {
                 kind: CallableKind::Procedure,
-                params: params
-                    .iter()
-                    .map(|param| IrParam {
-                        pseudo_type: pseudo_type(&param.type_name),
-                    })
-                    .collect(),
+                params: ir_params(params, types, body.line)?,
                 return_type: None,
+                line: body.line,
             };
-            map.insert(name, callable);
+            map.insert(name.clone(), callable);
         }
         ast::Decl::Function {
             name,
             params,
             return_type_name,
-            ..
+            body,
         } => {
-            if map.contains_key(&name) {
-                unimplemented!("FUNCTION defined twice.");
+            if map.contains_key(name) {
+                return Err(TypeError::new("FUNCTION defined twice.", body.line));
             }
             let callable = Callable {
                 kind: CallableKind::Function,
-                params: params
-                    .iter()
-                    .map(|param| IrParam {
-                        pseudo_type: pseudo_type(&param.type_name),
-                    })
-                    .collect(),
-                return_type: Some(pseudo_type(&return_type_name)),
+                params: ir_params(params, types, body.line)?,
+                return_type: Some(pseudo_type(return_type_name, types, body.line)?),
+                line: body.line,
             };
-            map.insert(name, callable);
+            map.insert(name.clone(), callable);
         }
+        ast::Decl::Type { .. } | ast::Decl::Class { .. } | ast::Decl::Stmt(_) => (),
     }
+    Ok(())
+}
+
+pub fn ir_params(
+    params: &[ast::Param],
+    types: &HashMap<String, hlir::Type>,
+    line: usize,
+) -> TypeResult<Vec<IrParam>> {
+    params
+        .iter()
+        .map(|param| {
+            Ok(IrParam {
+                pseudo_type: pseudo_type(&param.type_name, types, line)?,
+            })
+        })
+        .collect()
 }
 
 impl TypeChecker {
-    pub fn decl(&mut self, decl: ast::Decl) -> hlir::Decl {
+    pub fn decl(&mut self, decl: ast::Decl) -> TypeResult<hlir::Decl> {
         match decl {
             ast::Decl::Procedure { name, params, body } => {
+                self.line = body.line;
+                self.current_callable = Some(name.clone());
                 self.enter_scope();
-                let params = self.params(params);
-                self.declare_params(&params);
+                let params = self.params(params)?;
+                self.declare_params(&params)?;
                 let procedure = hlir::Decl::Procedure {
                     name,
                     params,
-                    body: self.stmt(body),
+                    body: self.stmt(body)?,
                 };
                 self.exit_scope();
-                procedure
+                self.current_callable = None;
+                Ok(procedure)
             }
             ast::Decl::Function {
                 name,
@@ -86,15 +112,17 @@ impl TypeChecker {
                 body,
                 return_type_name,
             } => {
+                self.line = body.line;
+                self.current_callable = Some(name.clone());
                 self.enter_scope();
-                let params = self.params(params);
-                self.declare_params(&params);
-                let return_type = pseudo_type(&return_type_name);
+                let params = self.params(params)?;
+                self.declare_params(&params)?;
+                let return_type = pseudo_type(&return_type_name, &self.types, self.line)?;
 
                 let previous_expected_return_type =
                     std::mem::replace(&mut self.current_expected_return_type, Some(return_type));
 
-                let body = self.stmt(body);
+                let body = self.stmt(body)?;
                 let function = hlir::Decl::Function {
                     name,
                     params,
@@ -105,8 +133,38 @@ impl TypeChecker {
                 self.current_expected_return_type = previous_expected_return_type;
 
                 self.exit_scope();
-                function
+                self.current_callable = None;
+                Ok(function)
             }
+            ast::Decl::Stmt(stmt) => match stmt.stmt_kind {
+                // Already declared before any PROCEDURE/FUNCTION was checked.
+                ast::StmtKind::VarDecl { name, type_name } => Ok(hlir::Decl::Stmt(hlir::Stmt {
+                    stmt_kind: hlir::StmtKind::VarDecl {
+                        name,
+                        pseudo_type: pseudo_type(&type_name, &self.types, stmt.line)?,
+                    },
+                    line: stmt.line,
+                })),
+                ast::StmtKind::Define {
+                    name,
+                    values,
+                    type_name,
+                } => {
+                    self.line = stmt.line;
+                    let (pseudo_type, values) = self.define(values, &type_name)?;
+                    Ok(hlir::Decl::Stmt(hlir::Stmt {
+                        stmt_kind: hlir::StmtKind::Define {
+                            name,
+                            pseudo_type,
+                            values,
+                        },
+                        line: stmt.line,
+                    }))
+                }
+                _ => Ok(hlir::Decl::Stmt(self.stmt(stmt)?)),
+            },
+            ast::Decl::Type { .. } => unreachable!("TYPEs are resolved before any declaration"),
+            ast::Decl::Class { .. } => unreachable!("CLASSes are checked by `class`"),
         }
     }
 
@@ -114,26 +172,23 @@ impl TypeChecker {
         self.callable_table.contains_key(name)
     }
 
-    pub fn params(&mut self, params: Vec<ast::Param>) -> Vec<hlir::Param> {
+    pub fn params(&mut self, params: Vec<ast::Param>) -> TypeResult<Vec<hlir::Param>> {
         params
             .into_iter()
-            .map(|param| hlir::Param {
-                name: param.name,
-                pseudo_type: pseudo_type(&param.type_name),
-                passing_mode: param.passing_mode,
+            .map(|param| {
+                Ok(hlir::Param {
+                    pseudo_type: pseudo_type(&param.type_name, &self.types, self.line)?,
+                    name: param.name,
+                    passing_mode: param.passing_mode,
+                })
             })
             .collect()
     }
 
-    fn declare_params(&mut self, params: &Vec<hlir::Param>) {
-        params.iter().for_each(|param| {
-            self.decl_var(param.name.clone(), param.pseudo_type);
-            self.symbol_table_stack
-                .last_mut()
-                .unwrap()
-                .get_mut(&param.name)
-                .unwrap()
-                .initialized = true;
-        });
+    fn declare_params(&mut self, params: &Vec<hlir::Param>) -> TypeResult<()> {
+        for param in params.iter() {
+            self.decl_var(param.name.clone(), param.pseudo_type)?;
+        }
+        Ok(())
     }
 }
@@ -1,48 +1,133 @@
 use super::decl::CallableKind;
+use super::types::pseudo_type;
 use super::{match_types, TypeChecker};
+use crate::error::{TypeError, TypeResult};
 use crate::ir::ast::{self, LiteralKind};
 use crate::ir::hlir::{self, Type};
-use crate::lexer::token::{KeywordKind, TokenKind};
+use crate::lexer::token::{KeywordKind, Token, TokenKind};
 
 impl TypeChecker {
-    pub fn expr(&mut self, expr: ast::ExprKind) -> hlir::Expr {
-        match expr {
+    pub fn expr(&mut self, expr: ast::ExprKind) -> TypeResult<hlir::Expr> {
+        let expr = match expr {
             ast::ExprKind::Binary { lhs, op, rhs } => {
                 use TokenKind::*;
                 // todo: cast INTEGER to REAL when necessary.
-                let lhs = self.expr(*lhs);
-                let rhs = self.expr(*rhs);
-                if lhs.pseudo_type != rhs.pseudo_type {
-                    unimplemented!("Mismatched types on binary expr");
+                let lhs = self.expr(*lhs)?;
+                let rhs = self.expr(*rhs)?;
+                if op.kind == Keyword(KeywordKind::In) {
+                    match rhs.pseudo_type {
+                        Type::Set(element) if *element == lhs.pseudo_type => (),
+                        Type::Set(_) => {
+                            return Err(self.error(format!(
+                                "Cannot look for {} in {}.",
+                                lhs.pseudo_type, rhs.pseudo_type
+                            )));
+                        }
+                        _ => {
+                            return Err(self.error(format!(
+                                "`IN` needs a set on its right, got {}.",
+                                rhs.pseudo_type
+                            )));
+                        }
+                    }
+                } else if lhs.pseudo_type.is_pointer() || rhs.pseudo_type.is_pointer() {
+                    if !matches!(op.kind, Equal | NotEqual) {
+                        return Err(self.error("Pointers can only be compared with `=` and `<>`."));
+                    }
+                    if !lhs.pseudo_type.accepts(rhs.pseudo_type)
+                        && !rhs.pseudo_type.accepts(lhs.pseudo_type)
+                    {
+                        return Err(self.error(format!(
+                            "Cannot compare {} with {}.",
+                            lhs.pseudo_type, rhs.pseudo_type
+                        )));
+                    }
+                } else if matches!(lhs.pseudo_type, Type::Class(_))
+                    || matches!(rhs.pseudo_type, Type::Class(_))
+                {
+                    if !matches!(op.kind, Equal | NotEqual) {
+                        return Err(self.error("Objects can only be compared with `=` and `<>`."));
+                    }
+                    if !lhs.pseudo_type.accepts(rhs.pseudo_type)
+                        && !rhs.pseudo_type.accepts(lhs.pseudo_type)
+                    {
+                        return Err(self.error(format!(
+                            "Cannot compare {} with {}.",
+                            lhs.pseudo_type, rhs.pseudo_type
+                        )));
+                    }
+                } else if let Type::Enum(_) = lhs.pseudo_type {
+                    match op.kind {
+                        // Stepping through the values, as in `Spring + 1`.
+                        Plus | Minus if rhs.pseudo_type != Type::Integer => {
+                            return Err(self.error(format!(
+                                "Only INTEGERs can be added to or subtracted from values of {}.",
+                                lhs.pseudo_type
+                            )));
+                        }
+                        Plus | Minus => (),
+                        Equal | NotEqual | Greater | GreaterEqual | Less | LessEqual => {
+                            if lhs.pseudo_type != rhs.pseudo_type {
+                                return Err(self.error(format!(
+                                    "Cannot compare {} with {}.",
+                                    lhs.pseudo_type, rhs.pseudo_type
+                                )));
+                            }
+                        }
+                        _ => {
+                            return Err(self.error(format!(
+                                "Values of {} can only be compared, or stepped through with `+` and `-`.",
+                                lhs.pseudo_type
+                            )));
+                        }
+                    }
+                } else if let Type::Set(_) = lhs.pseudo_type {
+                    if !matches!(op.kind, Plus | Minus | Star | Equal | NotEqual) {
+                        return Err(self.error(
+                            "Sets can only be combined with `+`, `-` and `*`, or compared with `=` and `<>`.",
+                        ));
+                    }
+                    if lhs.pseudo_type != rhs.pseudo_type {
+                        return Err(self.error(format!(
+                            "Cannot combine {} with {}.",
+                            lhs.pseudo_type, rhs.pseudo_type
+                        )));
+                    }
+                } else if op.kind == Ampersand {
+                    for operand in [&lhs, &rhs] {
+                        if !match_types(&operand.pseudo_type, &[Type::String, Type::Char]) {
+                            return Err(self.error(format!(
+                                "Can only concatenate STRINGs and CHARs, got {}.",
+                                operand.pseudo_type
+                            )));
+                        }
+                    }
+                } else if lhs.pseudo_type != rhs.pseudo_type {
+                    return Err(self.error("Mismatched types on binary expr"));
                 }
                 let pseudo_type = match op.kind {
                     Greater | GreaterEqual | Less | LessEqual => {
-                        if !match_types(&lhs.pseudo_type, &[Type::Real, Type::Integer]) {
-                            unimplemented!(
-                                "Cannot do comparison of anything other than INTEGER or REAL"
-                            );
+                        if !match_types(&lhs.pseudo_type, &[Type::Real, Type::Integer])
+                            && !matches!(lhs.pseudo_type, Type::Enum(_))
+                        {
+                            return Err(self.error("Cannot do comparison of anything other than INTEGER or REAL"));
                         }
                         Type::Boolean
                     },
                     Keyword(KeywordKind::Mod) => if !match_types(&lhs.pseudo_type, &[Type::Real, Type::Integer]) {
-                        unimplemented!("Can only perform `MOD` operation on INTEGERs or REALs.");
+                        return Err(self.error("Can only perform `MOD` operation on INTEGERs or REALs."));
                     } else { lhs.pseudo_type },
-                    Equal | NotEqual => Type::Boolean,
-                    Ampersand => {
-                        if lhs.pseudo_type != Type::String {
-                            unimplemented!("Can only concatenate two strings");
-                        }
-                        Type::String
-                    },
+                    Equal | NotEqual | Keyword(KeywordKind::In) => Type::Boolean,
+                    Ampersand => Type::String,
                     Slash => {
                         if lhs.pseudo_type != Type::Real {
-                            unimplemented!("`/` division operator can only be applied to REALs");
+                            return Err(self.error("`/` division operator can only be applied to REALs"));
                         }
                         Type::Real
                     },
                     Keyword(KeywordKind::Div) => {
                         if lhs.pseudo_type != Type::Integer {
-                            unimplemented!("`DIV` operator can only be applied to INTEGERs");
+                            return Err(self.error("`DIV` operator can only be applied to INTEGERs"));
                         }
                         Type::Integer
                     },
@@ -58,12 +143,10 @@ impl TypeChecker {
                 }
             }
             ast::ExprKind::Logical { lhs, op, rhs } => {
-                let lhs = self.expr(*lhs);
-                let rhs = self.expr(*rhs);
+                let lhs = self.expr(*lhs)?;
+                let rhs = self.expr(*rhs)?;
                 if lhs.pseudo_type != Type::Boolean || rhs.pseudo_type != Type::Boolean {
-                    unimplemented!(
-                        "Logical comparison must be done on expressions of BOOLEAN type."
-                    );
+                    return Err(self.error("Logical comparison must be done on expressions of BOOLEAN type."));
                 }
                 hlir::Expr {
                     pseudo_type: Type::Boolean,
@@ -75,16 +158,16 @@ impl TypeChecker {
                 }
             }
             ast::ExprKind::Unary { op, expr } => {
-                let expr = self.expr(*expr);
+                let expr = self.expr(*expr)?;
                 match op.kind {
                     TokenKind::Keyword(KeywordKind::Not) => {
                         if expr.pseudo_type != Type::Boolean {
-                            unimplemented!("Can only do NOT operation on booleans");
+                            return Err(self.error("Can only do NOT operation on booleans"));
                         }
                     }
                     TokenKind::Minus => {
                         if !match_types(&expr.pseudo_type, &[Type::Real, Type::Integer]) {
-                            unimplemented!("Can only negate REALs & INTEGERs");
+                            return Err(self.error("Can only negate REALs & INTEGERs"));
                         }
                     }
                     _ => unreachable!(),
@@ -97,16 +180,27 @@ impl TypeChecker {
                     },
                 }
             }
+            // An attribute of the object a method was called on.
+            ast::ExprKind::Assignment { target, value } if self.is_own_member(&target) => {
+                let token = Token::new(TokenKind::Identifier(target.clone()), self.line, 0, 0);
+                self.field_assignment(self.receiver().unwrap(), &target, &token, *value)?
+            }
             ast::ExprKind::Assignment { target, value } => {
-                let value = self.expr(*value);
+                let value = self.expr(*value)?;
+                let line = self.line;
                 let var_target = match self.get_var_mut(&target) {
                     Some(var) => var,
-                    None => unimplemented!("Attempting to assign to an undeclared variable"),
+                    None => return Err(self.error("Attempting to assign to an undeclared variable")),
                 };
-                if var_target.pseudo_type != value.pseudo_type {
-                    unimplemented!("Cannot assign type A to type B")
+                if !var_target.pseudo_type.accepts(value.pseudo_type) {
+                    return Err(TypeError::new(
+                        format!(
+                            "Cannot assign a value of type {} to `{}` of type {}.",
+                            value.pseudo_type, target, var_target.pseudo_type
+                        ),
+                        line,
+                    ));
                 }
-                var_target.initialized = true;
                 hlir::Expr {
                     pseudo_type: var_target.pseudo_type,
                     expr_kind: hlir::ExprKind::Assignment {
@@ -116,30 +210,27 @@ impl TypeChecker {
                 }
             }
             ast::ExprKind::Call { callee, args } => {
-                let callee = match *callee {
-                    ast::ExprKind::Variable(name) => name,
-                    _ => unimplemented!("Invalid FUCNTION callee expression"),
-                };
-                let args: Vec<hlir::Expr> = args.into_iter().map(|arg| self.expr(arg)).collect();
-
-                if let Some(function) = self.callable_table.get(&callee) {
-                    if function.kind != CallableKind::Function {
-                        unimplemented!("Use keyword `CALL` to invoke PROCEDUREs.");
+                let method_call = match *callee {
+                    ast::ExprKind::Field {
+                        object,
+                        name,
+                        token,
+                    } => {
+                        let object = self.method_object(*object)?;
+                        self.method_call(object, &name, &token, args)?
                     }
-                    if args.len() != function.params.len() {
-                        unimplemented!("Wrong number of arguments");
+                    ast::ExprKind::Variable { name, token } if self.is_own_member(&name) => {
+                        let object = (self.receiver().unwrap(), false);
+                        self.method_call(object, &name, &token, args)?
                     }
-                    for (param, arg) in function.params.iter().zip(args.iter()) {
-                        if param.pseudo_type != arg.pseudo_type {
-                            unimplemented!("Wrong type of argument");
-                        }
-                    }
-                    hlir::Expr {
-                        pseudo_type: function.return_type.unwrap(),
-                        expr_kind: hlir::ExprKind::Call { callee, args },
-                    }
-                } else {
-                    unimplemented!("Call to undefined FUNCTION `{}`", callee);
+                    callee => return self.function_call(callee, args),
+                };
+                if method_call.kind != CallableKind::Function {
+                    return Err(self.error("Use keyword `CALL` to invoke PROCEDUREs."));
+                }
+                hlir::Expr {
+                    pseudo_type: method_call.return_type.unwrap(),
+                    expr_kind: method_call.into_expr(),
                 }
             }
             ast::ExprKind::Literal(ref lit) => {
@@ -149,25 +240,224 @@ impl TypeChecker {
                     LiteralKind::Character(_) => hlir::Type::Char,
                     LiteralKind::String(_) => hlir::Type::String,
                     LiteralKind::Boolean(_) => hlir::Type::Boolean,
+                    LiteralKind::Null => hlir::Type::Null,
                 };
                 hlir::Expr {
                     pseudo_type,
                     expr_kind: hlir::ExprKind::Literal(lit.clone()),
                 }
             }
-            ast::ExprKind::Variable(name) => {
+            // An attribute of the object a method was called on.
+            ast::ExprKind::Variable { name, token } if self.is_own_member(&name) => {
+                let receiver = self.receiver().unwrap();
+                let (index, pseudo_type) = self.attribute(receiver.pseudo_type, &name, &token)?;
+                hlir::Expr {
+                    pseudo_type,
+                    expr_kind: hlir::ExprKind::Field {
+                        object: Box::new(receiver),
+                        index,
+                    },
+                }
+            }
+            ast::ExprKind::Variable { name, token } => {
                 let var = match self.get_var_mut(&name) {
                     Some(var) => var,
-                    None => unimplemented!("variable `{}` not declated", name),
+                    None => match self.enum_values.get(&name) {
+                        Some((pseudo_type, position)) => {
+                            return Ok(hlir::Expr {
+                                pseudo_type: *pseudo_type,
+                                expr_kind: hlir::ExprKind::Literal(LiteralKind::Integer(
+                                    *position as i64,
+                                )),
+                            })
+                        }
+                        None => {
+                            return Err(TypeError::at(format!("variable `{}` not declated", name), token))
+                        }
+                    },
                 };
-                if !var.initialized {
-                    unimplemented!("use of uninitialized variable `{}`.", name);
-                }
                 hlir::Expr {
                     pseudo_type: var.pseudo_type,
-                    expr_kind: hlir::ExprKind::Variable(name),
+                    expr_kind: hlir::ExprKind::Variable { name, token },
+                }
+            }
+            ast::ExprKind::Deref { pointer, op } => {
+                let pointer = self.expr(*pointer)?;
+                hlir::Expr {
+                    pseudo_type: self.pointee(pointer.pseudo_type, op)?,
+                    expr_kind: hlir::ExprKind::Deref {
+                        pointer: Box::new(pointer),
+                    },
+                }
+            }
+            ast::ExprKind::DerefAssignment { pointer, op, value } => {
+                let pointer = self.expr(*pointer)?;
+                let value = self.expr(*value)?;
+                let pointee = self.pointee(pointer.pseudo_type, op)?;
+                if !pointee.accepts(value.pseudo_type) {
+                    return Err(self.error(format!(
+                        "Cannot assign a value of type {} through a pointer of type {}.",
+                        value.pseudo_type, pointer.pseudo_type
+                    )));
+                }
+                hlir::Expr {
+                    pseudo_type: pointee,
+                    expr_kind: hlir::ExprKind::DerefAssignment {
+                        pointer: Box::new(pointer),
+                        value: Box::new(value),
+                    },
+                }
+            }
+            ast::ExprKind::AddressOf { name, token } => {
+                let var = match self.get_var_mut(&name) {
+                    Some(var) => var,
+                    None => {
+                        return Err(TypeError::at(format!("variable `{}` not declated", name), token))
+                    }
+                };
+                hlir::Expr {
+                    pseudo_type: Type::pointer(var.pseudo_type),
+                    expr_kind: hlir::ExprKind::AddressOf { name, token },
+                }
+            }
+            ast::ExprKind::New { type_name, args } => {
+                let pseudo_type = pseudo_type(&type_name, &self.types, self.line)?;
+                match pseudo_type {
+                    Type::Class(_) => hlir::Expr {
+                        pseudo_type,
+                        expr_kind: self.construct(pseudo_type, args)?,
+                    },
+                    _ if !args.is_empty() => {
+                        return Err(self.error(format!(
+                            "Only objects are made with arguments, got {}.",
+                            pseudo_type
+                        )))
+                    }
+                    pointee => hlir::Expr {
+                        pseudo_type: Type::pointer(pointee),
+                        expr_kind: hlir::ExprKind::New { pointee },
+                    },
+                }
+            }
+            ast::ExprKind::Field {
+                object,
+                name,
+                token,
+            } => {
+                let object = self.field_object(*object)?;
+                let (index, pseudo_type) = self.attribute(object.pseudo_type, &name, &token)?;
+                hlir::Expr {
+                    pseudo_type,
+                    expr_kind: hlir::ExprKind::Field {
+                        object: Box::new(object),
+                        index,
+                    },
+                }
+            }
+            ast::ExprKind::FieldAssignment {
+                object,
+                name,
+                token,
+                value,
+            } => {
+                let object = self.field_object(*object)?;
+                self.field_assignment(object, &name, &token, *value)?
+            }
+            ast::ExprKind::Super { token } => {
+                return Err(TypeError::at(
+                    "SUPER can only be used to call a method of the parent CLASS.",
+                    token,
+                ))
+            }
+        };
+        Ok(expr)
+    }
+
+    /// The object whose attribute is used, which can't be SUPER as attributes aren't
+    /// overridden.
+    fn field_object(&mut self, object: ast::ExprKind) -> TypeResult<hlir::Expr> {
+        match object {
+            ast::ExprKind::Super { token } => Err(TypeError::at(
+                "SUPER can only be used to call a method of the parent CLASS.",
+                token,
+            )),
+            object => self.expr(object),
+        }
+    }
+
+    fn field_assignment(
+        &mut self,
+        object: hlir::Expr,
+        name: &str,
+        token: &Token,
+        value: ast::ExprKind,
+    ) -> TypeResult<hlir::Expr> {
+        let (index, pseudo_type) = self.attribute(object.pseudo_type, name, token)?;
+        let value = self.expr(value)?;
+        if !pseudo_type.accepts(value.pseudo_type) {
+            return Err(self.error(format!(
+                "Cannot assign a value of type {} to `{}` of type {}.",
+                value.pseudo_type, name, pseudo_type
+            )));
+        }
+        Ok(hlir::Expr {
+            pseudo_type,
+            expr_kind: hlir::ExprKind::FieldAssignment {
+                object: Box::new(object),
+                index,
+                value: Box::new(value),
+            },
+        })
+    }
+
+    /// A call of the FUNCTION `callee`.
+    fn function_call(
+        &mut self,
+        callee: ast::ExprKind,
+        args: Vec<ast::ExprKind>,
+    ) -> TypeResult<hlir::Expr> {
+        let callee = match callee {
+            ast::ExprKind::Variable { name, .. } => name,
+            _ => return Err(self.error("Invalid FUCNTION callee expression")),
+        };
+        let args = args
+            .into_iter()
+            .map(|arg| self.expr(arg))
+            .collect::<TypeResult<Vec<hlir::Expr>>>()?;
+
+        if let Some(function) = self.callable_table.get(&callee) {
+            if function.kind != CallableKind::Function {
+                return Err(self.error("Use keyword `CALL` to invoke PROCEDUREs."));
+            }
+            if args.len() != function.params.len() {
+                return Err(self.error("Wrong number of arguments"));
+            }
+            for (param, arg) in function.params.iter().zip(args.iter()) {
+                if !param.pseudo_type.accepts(arg.pseudo_type) {
+                    return Err(self.error("Wrong type of argument"));
                 }
             }
+            Ok(hlir::Expr {
+                pseudo_type: function.return_type.unwrap(),
+                expr_kind: hlir::ExprKind::Call { callee, args },
+            })
+        } else {
+            Err(self.error(format!("Call to undefined FUNCTION `{}`", callee)))
+        }
+    }
+
+    /// The type `pointer_type` points to, or an error at the `^` if it isn't a pointer.
+    fn pointee(&self, pointer_type: Type, op: Token) -> TypeResult<Type> {
+        match pointer_type {
+            Type::Pointer(pointee) => Ok(*pointee),
+            Type::Null => Err(TypeError::at("Cannot dereference NULL.", op)),
+            _ => Err(TypeError::at(
+                format!(
+                    "Only pointers can be dereferenced with `^`, got {}.",
+                    pointer_type
+                ),
+                op,
+            )),
         }
     }
 }
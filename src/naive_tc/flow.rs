@@ -0,0 +1,206 @@
+use crate::{
+    cfg::{falls_through, unassigned_uses, Calls, Cfg},
+    error::{TypeError, TypeResult},
+    ir::{
+        ast::PassingMode,
+        hlir::{Decl, Expr, ExprKind, Param, Stmt, StmtKind},
+    },
+};
+use std::collections::HashSet;
+
+/// Checks that FUNCTIONs RETURN on every path and that variables are assigned before
+/// they're read, reporting the error found first in the source.
+pub fn check_flow(decls: &[Decl]) -> TypeResult<()> {
+    let calls = calls(decls);
+    let mut errors = Vec::new();
+    for decl in decls.iter() {
+        match decl {
+            Decl::Procedure { body, .. } => {
+                errors.extend(assignment_errors(&Cfg::build([body]), &calls));
+            }
+            Decl::Function { name, body, .. } => {
+                let cfg = Cfg::build([body]);
+                if falls_through(&cfg) {
+                    errors.push(TypeError::new(
+                        format!(
+                            "FUNCTION `{}` can reach ENDFUNCTION without a RETURN.",
+                            name
+                        ),
+                        body.line,
+                    ));
+                }
+                errors.extend(assignment_errors(&cfg, &calls));
+            }
+            Decl::Stmt(_) | Decl::Class { .. } => (),
+        }
+    }
+    let script = decls.iter().filter_map(|decl| match decl {
+        Decl::Stmt(stmt) => Some(stmt),
+        _ => None,
+    });
+    errors.extend(assignment_errors(&Cfg::build(script), &calls));
+
+    match errors
+        .into_iter()
+        .min_by_key(|error| (error.line, error.token.as_ref().map(|token| token.col)))
+    {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn assignment_errors(cfg: &Cfg, calls: &Calls) -> Vec<TypeError> {
+    unassigned_uses(cfg, calls)
+        .into_iter()
+        .map(|unassigned| {
+            let msg = format!(
+                "`{}` might be used before it is assigned a value.",
+                unassigned.name
+            );
+            TypeError::at(msg, unassigned.token)
+        })
+        .collect()
+}
+
+/// What each PROCEDURE/FUNCTION may assign when called.
+fn calls(decls: &[Decl]) -> Calls {
+    let mut calls = Calls::default();
+    for decl in decls.iter() {
+        let (name, params, body) = match decl {
+            Decl::Procedure { name, params, body } => (name, params, body),
+            Decl::Function {
+                name, params, body, ..
+            } => (name, params, body),
+            Decl::Stmt(_) | Decl::Class { .. } => continue,
+        };
+        let byref = params
+            .iter()
+            .map(|param| matches!(param.passing_mode, Some(PassingMode::ByRef)))
+            .collect();
+        calls.byref_params.insert(name.clone(), byref);
+
+        let mut assigned = HashSet::new();
+        let mut declared: HashSet<String> = params
+            .iter()
+            .map(|param: &Param| param.name.clone())
+            .collect();
+        names_in_stmt(body, &mut assigned, &mut declared);
+        calls
+            .assigned_globals
+            .extend(assigned.difference(&declared).cloned());
+    }
+    calls
+}
+
+/// Collects the variables `stmt` assigns and those it DECLAREs.
+fn names_in_stmt(stmt: &Stmt, assigned: &mut HashSet<String>, declared: &mut HashSet<String>) {
+    match &stmt.stmt_kind {
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            names_in_expr(condition, assigned);
+            names_in_stmt(then_branch, assigned, declared);
+            if let Some(else_branch) = else_branch {
+                names_in_stmt(else_branch, assigned, declared);
+            }
+        }
+        StmtKind::Repeat {
+            body,
+            until: condition,
+        }
+        | StmtKind::While { body, condition } => {
+            names_in_stmt(body, assigned, declared);
+            names_in_expr(condition, assigned);
+        }
+        StmtKind::Case {
+            selector,
+            arms,
+            otherwise,
+        } => {
+            names_in_expr(selector, assigned);
+            for arm in arms.iter() {
+                names_in_expr(&arm.value, assigned);
+                if let Some(to) = &arm.to {
+                    names_in_expr(to, assigned);
+                }
+                names_in_stmt(&arm.body, assigned, declared);
+            }
+            if let Some(otherwise) = otherwise {
+                names_in_stmt(otherwise, assigned, declared);
+            }
+        }
+        StmtKind::Try { body, handler } => {
+            names_in_stmt(body, assigned, declared);
+            names_in_stmt(handler, assigned, declared);
+        }
+        StmtKind::Call { args, .. } => names_in_args(args, assigned),
+        StmtKind::CallMethod { object, args, .. } => {
+            names_in_expr(object, assigned);
+            names_in_args(args, assigned);
+        }
+        StmtKind::VarDecl { name, .. } => {
+            declared.insert(name.clone());
+        }
+        StmtKind::Define { name, values, .. } => {
+            values.iter().for_each(|value| names_in_expr(value, assigned));
+            declared.insert(name.clone());
+        }
+        StmtKind::Return(expr) | StmtKind::Expr(expr) => names_in_expr(expr, assigned),
+        StmtKind::Output(exprs) => exprs.iter().for_each(|expr| names_in_expr(expr, assigned)),
+        StmtKind::Block(stmts) => stmts
+            .iter()
+            .for_each(|stmt| names_in_stmt(stmt, assigned, declared)),
+    }
+}
+
+fn names_in_expr(expr: &Expr, assigned: &mut HashSet<String>) {
+    match &expr.expr_kind {
+        ExprKind::Binary { lhs, rhs, .. } | ExprKind::Logical { lhs, rhs, .. } => {
+            names_in_expr(lhs, assigned);
+            names_in_expr(rhs, assigned);
+        }
+        ExprKind::Unary { expr, .. } => names_in_expr(expr, assigned),
+        ExprKind::Assignment { target, value } => {
+            names_in_expr(value, assigned);
+            assigned.insert(target.clone());
+        }
+        ExprKind::Call { args, .. } | ExprKind::Construct { args, .. } => {
+            names_in_args(args, assigned)
+        }
+        ExprKind::MethodCall { object, args, .. } => {
+            names_in_expr(object, assigned);
+            names_in_args(args, assigned);
+        }
+        ExprKind::Field { object, .. } => names_in_expr(object, assigned),
+        ExprKind::FieldAssignment { object, value, .. } => {
+            names_in_expr(object, assigned);
+            names_in_expr(value, assigned);
+        }
+        ExprKind::Deref { pointer } => names_in_expr(pointer, assigned),
+        ExprKind::DerefAssignment { pointer, value } => {
+            names_in_expr(pointer, assigned);
+            names_in_expr(value, assigned);
+        }
+        ExprKind::AddressOf { name, .. } => {
+            assigned.insert(name.clone());
+        }
+        ExprKind::Literal(_)
+        | ExprKind::Variable { .. }
+        | ExprKind::New { .. }
+        | ExprKind::Input => (),
+    }
+}
+
+/// Variables passed as arguments might be BYREF, and so assigned by the callee.
+fn names_in_args(args: &[Expr], assigned: &mut HashSet<String>) {
+    for arg in args.iter() {
+        match &arg.expr_kind {
+            ExprKind::Variable { name, .. } => {
+                assigned.insert(name.clone());
+            }
+            _ => names_in_expr(arg, assigned),
+        }
+    }
+}
@@ -1,45 +1,171 @@
+mod class;
 mod decl;
 mod expr;
+mod flow;
 mod stmt;
 mod types;
 mod var;
 
+#[cfg(test)]
+pub(crate) mod test_support;
+#[cfg(test)]
+mod tests;
+
 use self::{
-    decl::{define_decl, Callable},
+    class::Class,
+    decl::{define_decl, Callable, CallableKind},
+    types::pseudo_type,
     var::Variable,
 };
-use crate::ir::{ast, hlir::{self, Type}};
+use crate::{
+    error::{TypeError, TypeResult},
+    ir::{ast, hlir::{self, Type}},
+};
 use std::collections::HashMap;
 
 struct TypeChecker {
     symbol_table_stack: Vec<HashMap<String, Variable>>,
     callable_table: HashMap<String, Callable>,
+    /// Types declared with TYPE.
+    types: HashMap<String, Type>,
+    /// The values of enumerated types, with their type and position in it.
+    enum_values: HashMap<String, (Type, usize)>,
+    classes: HashMap<String, Class>,
     current_expected_return_type: Option<Type>,
+    /// Name of the PROCEDURE or FUNCTION whose body is being checked.
+    current_callable: Option<String>,
+    /// The CLASS whose methods are being checked.
+    current_class: Option<String>,
+    /// Line of the statement being checked, reported with errors.
+    line: usize,
+    /// Every variable and parameter declared so far, kept for tools after their scope ends.
+    symbols: Vec<Symbol>,
 }
 
-pub fn typecheck(decls: Vec<ast::Decl>) -> Vec<hlir::Decl> {
-    // First pass, declare all PROCEDUREs/FUNCTIONs
-    let mut callable_table = HashMap::new();
-    // todo: Don't clone the entire AST
-    for decl in decls.clone().into_iter() {
-        define_decl(decl, &mut callable_table);
-    }
+/// A variable or parameter, as recorded while type checking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub pseudo_type: Type,
+    /// The PROCEDURE or FUNCTION the variable is local to, `None` for globals.
+    pub scope: Option<String>,
+    /// Zero-based line of the declaration.
+    pub line: usize,
+}
 
-    if !callable_table.contains_key("Main") {
-        unimplemented!("`PROCEDURE Main` wasn't defined");
-    }
+pub fn typecheck(decls: Vec<ast::Decl>) -> TypeResult<Vec<hlir::Decl>> {
+    check(decls).0
+}
 
+/// Type checks `decls`, also returning the symbols declared up to the first error.
+pub fn check(decls: Vec<ast::Decl>) -> (TypeResult<Vec<hlir::Decl>>, Vec<Symbol>) {
     let mut tc = TypeChecker {
         symbol_table_stack: vec![HashMap::new()],
-        callable_table,
+        callable_table: HashMap::new(),
+        types: HashMap::new(),
+        enum_values: HashMap::new(),
+        classes: HashMap::new(),
         current_expected_return_type: None,
+        current_callable: None,
+        current_class: None,
+        line: 0,
+        symbols: Vec::new(),
     };
+    let hlir = tc.program(decls);
+    (hlir, tc.symbols)
+}
+
+impl TypeChecker {
+    fn program(&mut self, decls: Vec<ast::Decl>) -> TypeResult<Vec<hlir::Decl>> {
+        // CLASSes and TYPEs come first, so that anything may use them. A TYPE may only use
+        // those declared before it.
+        self.declare_classes(&decls)?;
+        for decl in decls.iter() {
+            if let ast::Decl::Type {
+                name,
+                type_name,
+                line,
+            } = decl
+            {
+                if self.types.contains_key(name) {
+                    return Err(TypeError::new(format!("TYPE `{}` declared twice.", name), *line));
+                }
+                let pseudo_type = match type_name {
+                    ast::TypeName::Enum(values) => self.enumeration(name, values, *line)?,
+                    _ => pseudo_type(type_name, &self.types, *line)?,
+                };
+                self.types.insert(name.clone(), pseudo_type);
+            }
+        }
+        self.define_classes(&decls)?;
+
+        // declare all PROCEDUREs/FUNCTIONs
+        for decl in decls.iter() {
+            define_decl(decl, &self.types, &mut self.callable_table)?;
+        }
+
+        if let Some(main) = self.callable_table.get("Main") {
+            if main.kind != CallableKind::Procedure || !main.params.is_empty() {
+                return Err(TypeError::new(
+                    "`Main` must be a PROCEDURE without parameters.",
+                    main.line,
+                ));
+            }
+        }
+
+        // Global variables are visible to every PROCEDURE/FUNCTION, wherever they are declared.
+        for decl in decls.iter() {
+            if let ast::Decl::Stmt(ast::Stmt {
+                stmt_kind: ast::StmtKind::VarDecl { name, type_name },
+                line,
+            }) = decl
+            {
+                self.line = *line;
+                let pseudo_type = pseudo_type(type_name, &self.types, *line)?;
+                self.decl_var(name.clone(), pseudo_type)?;
+            }
+            if let ast::Decl::Stmt(ast::Stmt {
+                stmt_kind: ast::StmtKind::Define { name, type_name, .. },
+                line,
+            }) = decl
+            {
+                self.line = *line;
+                let pseudo_type = pseudo_type(type_name, &self.types, *line)?;
+                self.decl_var(name.clone(), pseudo_type)?;
+            }
+        }
+
+        let mut hlir = Vec::new();
+        for decl in decls.into_iter() {
+            match decl {
+                ast::Decl::Type { .. } => (),
+                ast::Decl::Class { name, members, .. } => hlir.extend(self.class(name, members)?),
+                decl => hlir.push(self.decl(decl)?),
+            }
+        }
+        flow::check_flow(&hlir)?;
+        Ok(hlir)
+    }
+
+    /// Declares the values of the enumerated type `name`.
+    fn enumeration(&mut self, name: &str, values: &[String], line: usize) -> TypeResult<Type> {
+        let pseudo_type = Type::enumeration(name.to_string(), values.to_vec());
+        for (position, value) in values.iter().enumerate() {
+            if let Some((other, _)) = self.enum_values.get(value) {
+                return Err(TypeError::new(
+                    format!("`{}` is already a value of `{}`.", value, other),
+                    line,
+                ));
+            }
+            self.enum_values
+                .insert(value.clone(), (pseudo_type, position));
+        }
+        Ok(pseudo_type)
+    }
 
-    let mut hlir_decls = Vec::new();
-    for decl in decls.into_iter() {
-        hlir_decls.push(tc.decl(decl));
+    fn error(&self, msg: impl Into<String>) -> TypeError {
+        TypeError::new(msg, self.line)
     }
-    hlir_decls
 }
 
 pub fn match_types(pseudo_type: &hlir::Type, types: &[hlir::Type]) -> bool {
@@ -1,99 +1,309 @@
 use super::{decl::CallableKind, types::pseudo_type};
 use crate::{
-    ir::{ast, hlir},
+    error::TypeResult,
+    ir::{ast, hlir::{self, Type}},
+    lexer::token::{Token, TokenKind},
     naive_tc::TypeChecker,
 };
 
 impl TypeChecker {
-    pub fn stmt(&mut self, stmt: ast::Stmt) -> hlir::Stmt {
-        match stmt {
-            ast::Stmt::If {
+    pub fn stmt(&mut self, stmt: ast::Stmt) -> TypeResult<hlir::Stmt> {
+        let line = stmt.line;
+        let previous_line = std::mem::replace(&mut self.line, line);
+        let stmt_kind = self.stmt_kind(stmt.stmt_kind)?;
+        self.line = previous_line;
+        Ok(hlir::Stmt { stmt_kind, line })
+    }
+
+    fn stmt_kind(&mut self, stmt_kind: ast::StmtKind) -> TypeResult<hlir::StmtKind> {
+        let stmt_kind = match stmt_kind {
+            ast::StmtKind::If {
                 condition,
                 then_branch,
                 else_branch,
             } => {
-                let condition = self.expr(condition);
+                let condition = self.expr(condition)?;
                 if condition.pseudo_type != hlir::Type::Boolean {
-                    unimplemented!("IF condition must be of BOOLEAN type.");
+                    return Err(self.error("IF condition must be of BOOLEAN type."));
                 }
-                hlir::Stmt::If {
+                hlir::StmtKind::If {
                     condition,
-                    then_branch: Box::new(self.stmt(*then_branch)),
+                    then_branch: Box::new(self.stmt(*then_branch)?),
                     else_branch: match else_branch {
-                        Some(else_branch) => Some(Box::new(self.stmt(*else_branch))),
+                        Some(else_branch) => Some(Box::new(self.stmt(*else_branch)?)),
                         None => None,
                     },
                 }
             }
-            ast::Stmt::Repeat { body, until } => {
-                let body: Box<hlir::Stmt> = Box::new(self.stmt(*body));
-                let until = self.expr(until);
-                hlir::Stmt::Repeat { body, until }
+            ast::StmtKind::Repeat { body, until } => {
+                let body: Box<hlir::Stmt> = Box::new(self.stmt(*body)?);
+                let until = self.expr(until)?;
+                hlir::StmtKind::Repeat { body, until }
+            }
+            ast::StmtKind::While { body, condition } => {
+                let body = Box::new(self.stmt(*body)?);
+                let condition = self.expr(condition)?;
+                hlir::StmtKind::While { body, condition }
+            }
+            // A method of the object a method was called on.
+            ast::StmtKind::Call { name, args } if self.is_own_member(&name) => {
+                let token = Token::new(TokenKind::Identifier(name.clone()), self.line, 0, 0);
+                let object = (self.receiver().unwrap(), false);
+                let method_call = self.method_call(object, &name, &token, args)?;
+                if method_call.kind != CallableKind::Procedure {
+                    return Err(self.error("Call functions without keyword `CALL`."));
+                }
+                method_call.into_stmt()
             }
-            ast::Stmt::While { body, condition } => {
-                let body = Box::new(self.stmt(*body));
-                let condition = self.expr(condition);
-                hlir::Stmt::While { body, condition }
+            ast::StmtKind::CallMethod {
+                object,
+                method,
+                token,
+                args,
+            } => {
+                let object = self.method_object(object)?;
+                let method_call = self.method_call(object, &method, &token, args)?;
+                if method_call.kind != CallableKind::Procedure {
+                    return Err(self.error("Call functions without keyword `CALL`."));
+                }
+                method_call.into_stmt()
             }
-            ast::Stmt::Call { name, args } => {
-                let args: Vec<hlir::Expr> = args.into_iter().map(|arg| self.expr(arg)).collect();
+            ast::StmtKind::Call { name, args } => {
+                let args = args
+                    .into_iter()
+                    .map(|arg| self.expr(arg))
+                    .collect::<TypeResult<Vec<hlir::Expr>>>()?;
                 if let Some(procedure) = self.callable_table.get(&name) {
                     if procedure.kind != CallableKind::Procedure {
-                        unimplemented!("Call functions without keyword `CALL`.");
+                        return Err(self.error("Call functions without keyword `CALL`."));
                     }
                     if args.len() != procedure.params.len() {
-                        unimplemented!("wrong number of arguments");
+                        return Err(self.error("wrong number of arguments"));
                     }
                     for (param, arg) in procedure.params.iter().zip(args.iter()) {
-                        if param.pseudo_type != arg.pseudo_type {
-                            unimplemented!("wrong Type of argument");
+                        if !param.pseudo_type.accepts(arg.pseudo_type) {
+                            return Err(self.error("wrong Type of argument"));
                         }
                     }
                 } else {
-                    unimplemented!("CALL to undefined PROCEDURE `{}`.", name);
+                    return Err(self.error(format!("CALL to undefined PROCEDURE `{}`.", name)));
                 }
-                hlir::Stmt::Call { name, args }
-            }
-            ast::Stmt::Return(expr_kind) => {
-                // todo: check all branches to see that a value is always being returned.
-                // hint: control flow graph
-                let expr = self.expr(expr_kind);
-                if self.current_expected_return_type.unwrap() != expr.pseudo_type {
-                    unimplemented!(
+                hlir::StmtKind::Call { name, args }
+            }
+            ast::StmtKind::Return(expr_kind) => {
+                let expr = self.expr(expr_kind)?;
+                let expected_return_type = match self.current_expected_return_type {
+                    Some(expected_return_type) => expected_return_type,
+                    None => return Err(self.error("RETURN outside of a FUNCTION.")),
+                };
+                if !expected_return_type.accepts(expr.pseudo_type) {
+                    return Err(self.error(format!(
                         "Attempting to return type {:?} when should be returning {:?}",
-                        expr.pseudo_type,
-                        self.current_expected_return_type
-                    );
+                        expr.pseudo_type, expected_return_type
+                    )));
                 }
-                hlir::Stmt::Return(expr)
+                hlir::StmtKind::Return(expr)
             }
-            ast::Stmt::VarDecl { name, type_name } => {
-                let pseudo_type = pseudo_type(&type_name);
-                self.decl_var(name.clone(), pseudo_type);
-                hlir::Stmt::VarDecl { name }
+            ast::StmtKind::VarDecl { name, type_name } => {
+                let pseudo_type = pseudo_type(&type_name, &self.types, self.line)?;
+                self.decl_var(name.clone(), pseudo_type)?;
+                hlir::StmtKind::VarDecl { name, pseudo_type }
             }
-            ast::Stmt::Expr(expr_kind) => hlir::Stmt::Expr(self.expr(expr_kind)),
-            ast::Stmt::Output(expr_kinds) => hlir::Stmt::Output(
-                expr_kinds
+            ast::StmtKind::Define {
+                name,
+                values,
+                type_name,
+            } => {
+                let (pseudo_type, values) = self.define(values, &type_name)?;
+                self.decl_var(name.clone(), pseudo_type)?;
+                hlir::StmtKind::Define {
+                    name,
+                    pseudo_type,
+                    values,
+                }
+            }
+            // `SUPER.NEW(...)` and other calls of PROCEDUREs written without CALL, as
+            // constructors usually are.
+            ast::StmtKind::Expr(ast::ExprKind::Call { callee, args })
+                if matches!(*callee, ast::ExprKind::Field { .. }) =>
+            {
+                let (object, method, token) = match *callee {
+                    ast::ExprKind::Field {
+                        object,
+                        name,
+                        token,
+                    } => (*object, name, token),
+                    _ => unreachable!(),
+                };
+                let object = self.method_object(object)?;
+                let method_call = self.method_call(object, &method, &token, args)?;
+                match method_call.return_type {
+                    Some(pseudo_type) => hlir::StmtKind::Expr(hlir::Expr {
+                        pseudo_type,
+                        expr_kind: method_call.into_expr(),
+                    }),
+                    None => method_call.into_stmt(),
+                }
+            }
+            ast::StmtKind::Expr(expr_kind) => hlir::StmtKind::Expr(self.expr(expr_kind)?),
+            ast::StmtKind::Output(expr_kinds) => {
+                let exprs: Vec<hlir::Expr> = expr_kinds
                     .into_iter()
                     .map(|expr_kind| self.expr(expr_kind))
-                    .collect(),
-            ),
-            ast::Stmt::Input(holder) => {
-                let var = match self.get_var_mut(&holder) {
-                    Some(var) => var,
-                    None => unimplemented!("invalid assignment target for input buf"),
-                };
-                var.initialized = true;
-                hlir::Stmt::Input(holder)
+                    .collect::<TypeResult<_>>()?;
+                if let Some(pointer) = exprs.iter().find(|expr| expr.pseudo_type.is_pointer()) {
+                    return Err(self.error(format!(
+                        "Cannot OUTPUT a pointer of type {}, dereference it with `^`.",
+                        pointer.pseudo_type
+                    )));
+                }
+                if let Some(object) = exprs
+                    .iter()
+                    .find(|expr| matches!(expr.pseudo_type, Type::Class(_)))
+                {
+                    return Err(self.error(format!(
+                        "Cannot OUTPUT an object of CLASS {}, OUTPUT its attributes instead.",
+                        object.pseudo_type
+                    )));
+                }
+                hlir::StmtKind::Output(exprs)
             }
-            ast::Stmt::Block(stmts) => {
+            ast::StmtKind::Input(target) => hlir::StmtKind::Expr(self.input(target)?),
+            ast::StmtKind::Case {
+                selector,
+                arms,
+                otherwise,
+            } => {
+                let selector = self.expr(selector)?;
+                let arms = arms
+                    .into_iter()
+                    .map(|arm| self.case_arm(arm, selector.pseudo_type))
+                    .collect::<TypeResult<_>>()?;
+                hlir::StmtKind::Case {
+                    selector,
+                    arms,
+                    otherwise: match otherwise {
+                        Some(otherwise) => Some(Box::new(self.stmt(*otherwise)?)),
+                        None => None,
+                    },
+                }
+            }
+            ast::StmtKind::Try { body, handler } => hlir::StmtKind::Try {
+                body: Box::new(self.stmt(*body)?),
+                handler: Box::new(self.stmt(*handler)?),
+            },
+            ast::StmtKind::Block(stmts) => {
                 self.enter_scope();
-                let block =
-                    hlir::Stmt::Block(stmts.into_iter().map(|stmt| self.stmt(stmt)).collect());
+                let stmts = stmts
+                    .into_iter()
+                    .map(|stmt| self.stmt(stmt))
+                    .collect::<TypeResult<_>>()?;
                 self.exit_scope();
-                block
+                hlir::StmtKind::Block(stmts)
             }
+        };
+        Ok(stmt_kind)
+    }
+
+    /// `INPUT <target>` as an assignment of the line typed in, read as the target's type.
+    fn input(&mut self, target: ast::ExprKind) -> TypeResult<hlir::Expr> {
+        let target = self.expr(target)?;
+        let pseudo_type = target.pseudo_type;
+        if !matches!(
+            pseudo_type,
+            Type::Integer | Type::Real | Type::Char | Type::Boolean | Type::String | Type::Enum(_)
+        ) {
+            return Err(self.error(format!(
+                "Cannot INPUT {}, only INTEGERs, REALs, CHARs, STRINGs, BOOLEANs or enumerated values.",
+                pseudo_type
+            )));
+        }
+        let value = Box::new(hlir::Expr {
+            pseudo_type,
+            expr_kind: hlir::ExprKind::Input,
+        });
+        let expr_kind = match target.expr_kind {
+            hlir::ExprKind::Variable { name, .. } => hlir::ExprKind::Assignment {
+                target: name,
+                value,
+            },
+            hlir::ExprKind::Field { object, index } => hlir::ExprKind::FieldAssignment {
+                object,
+                index,
+                value,
+            },
+            hlir::ExprKind::Deref { pointer } => hlir::ExprKind::DerefAssignment { pointer, value },
+            // An enumerated value, which is a constant.
+            _ => {
+                return Err(self.error(
+                    "INPUT can only store in a variable, an attribute or through a pointer.",
+                ))
+            }
+        };
+        Ok(hlir::Expr {
+            pseudo_type,
+            expr_kind,
+        })
+    }
+
+    /// Checks that `type_name` is a set type holding every one of `values`.
+    pub fn define(
+        &mut self,
+        values: Vec<ast::ExprKind>,
+        type_name: &ast::TypeName,
+    ) -> TypeResult<(Type, Vec<hlir::Expr>)> {
+        let pseudo_type = pseudo_type(type_name, &self.types, self.line)?;
+        let element = match pseudo_type {
+            Type::Set(element) => *element,
+            _ => {
+                return Err(self.error(format!(
+                    "DEFINE needs a set type, not {}.",
+                    pseudo_type
+                )))
+            }
+        };
+        let values = values
+            .into_iter()
+            .map(|value| {
+                let value = self.expr(value)?;
+                match value.pseudo_type == element {
+                    true => Ok(value),
+                    false => Err(self.error(format!(
+                        "Cannot put {} in {}.",
+                        value.pseudo_type, pseudo_type
+                    ))),
+                }
+            })
+            .collect::<TypeResult<_>>()?;
+        Ok((pseudo_type, values))
+    }
+
+    fn case_arm(&mut self, arm: ast::CaseArm, selector_type: Type) -> TypeResult<hlir::CaseArm> {
+        let previous_line = std::mem::replace(&mut self.line, arm.body.line);
+        let value = self.expr(arm.value)?;
+        let to = match arm.to {
+            Some(to) => Some(self.expr(to)?),
+            None => None,
+        };
+        for value in std::iter::once(&value).chain(to.iter()) {
+            if value.pseudo_type != selector_type {
+                return Err(self.error(format!(
+                    "CASE value of type {} doesn't match the selector of type {}.",
+                    value.pseudo_type, selector_type
+                )));
+            }
+        }
+        if to.is_some() && !matches!(selector_type, Type::Integer | Type::Real | Type::Enum(_)) {
+            return Err(self.error(format!(
+                "Only INTEGER, REAL and enumerated CASE values can be ranges, not {}.",
+                selector_type
+            )));
         }
+        self.line = previous_line;
+        Ok(hlir::CaseArm {
+            value,
+            to,
+            body: self.stmt(arm.body)?,
+        })
     }
 }
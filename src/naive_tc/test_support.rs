@@ -0,0 +1,16 @@
+//! Helpers for tests that type check pseudocode, shared by the type checker's and the
+//! control flow graph's tests.
+
+use crate::{error::TypeError, ir::hlir::Decl, lexer::Lexer, naive_tc, parser::program};
+
+pub fn typecheck(src: &str) -> Result<Vec<Decl>, TypeError> {
+    naive_tc::typecheck(program(Lexer::new(src).peekable()).unwrap())
+}
+
+/// Checks the message and the one-based line and column of the error reported for `src`.
+pub fn check_error(src: &str, msg: &str, line: usize, col: Option<usize>) {
+    let error = typecheck(src).unwrap_err();
+    assert_eq!(error.msg, msg);
+    assert_eq!(error.line + 1, line);
+    assert_eq!(error.token.map(|token| token.col), col);
+}
@@ -0,0 +1,210 @@
+use super::test_support::{check_error, typecheck};
+
+#[test]
+fn input_targets() {
+    assert!(typecheck(
+        "TYPE PInt = ^INTEGER
+CLASS Person
+    PUBLIC Age : INTEGER
+ENDCLASS
+DECLARE P : PInt
+DECLARE Bob : Person
+P <- NEW INTEGER
+Bob <- NEW Person
+INPUT P^
+INPUT Bob.Age
+OUTPUT P^ + Bob.Age
+",
+    )
+    .is_ok());
+    check_error(
+        "TYPE PInt = ^INTEGER\nDECLARE P : PInt\nINPUT P\n",
+        "Cannot INPUT ^INTEGER, only INTEGERs, REALs, CHARs, STRINGs, BOOLEANs or enumerated values.",
+        3,
+        None,
+    );
+}
+
+#[test]
+fn concatenation() {
+    assert!(typecheck("DECLARE S : STRING\nS <- 'a' & 'b' & \"c\"\n").is_ok());
+    check_error(
+        "DECLARE S : STRING\nS <- \"a\" & 1\n",
+        "Can only concatenate STRINGs and CHARs, got INTEGER.",
+        2,
+        None,
+    );
+}
+
+#[test]
+fn pointers() {
+    // Taking the address of a variable counts as assigning it, like passing it BYREF.
+    assert!(typecheck(
+        "DECLARE X : INTEGER
+DECLARE P : ^INTEGER
+P <- @X
+P^ <- 1
+OUTPUT X
+",
+    )
+    .is_ok());
+    check_error(
+        "DECLARE X : INTEGER
+X <- 1
+OUTPUT X^
+",
+        "Only pointers can be dereferenced with `^`, got INTEGER.",
+        3,
+        Some(9),
+    );
+}
+
+#[test]
+fn case_arms() {
+    // Without OTHERWISE, the selector might match none of the arms.
+    check_error(
+        "TYPE Season = (Spring, Summer)
+FUNCTION Warmth(Now : Season) RETURNS INTEGER
+    CASE OF Now
+        Spring : RETURN 1
+        Summer : RETURN 2
+    ENDCASE
+ENDFUNCTION
+OUTPUT Warmth(Spring)
+",
+        "FUNCTION `Warmth` can reach ENDFUNCTION without a RETURN.",
+        2,
+        None,
+    );
+    assert!(typecheck(
+        "DECLARE X : INTEGER
+DECLARE Y : INTEGER
+INPUT Y
+CASE OF Y
+    1 : X <- 1
+    2 TO 4 : X <- 2
+    OTHERWISE
+        X <- 3
+ENDCASE
+OUTPUT X
+",
+    )
+    .is_ok());
+    check_error(
+        "TYPE Season = (Spring, Summer)
+DECLARE X : INTEGER
+X <- 1
+CASE OF X
+    Spring : OUTPUT X
+ENDCASE
+",
+        "CASE value of type Season doesn't match the selector of type INTEGER.",
+        5,
+        None,
+    );
+}
+
+#[test]
+fn sets() {
+    // DEFINE assigns the set its values.
+    assert!(typecheck(
+        "PROCEDURE Show
+    DEFINE Vowels ('A', 'E') : SET OF CHAR
+    OUTPUT 'A' IN Vowels
+ENDPROCEDURE
+CALL Show
+",
+    )
+    .is_ok());
+    check_error(
+        "TYPE Letters = SET OF CHAR
+DEFINE Vowels ('A', 'E') : Letters
+OUTPUT 1 IN Vowels
+",
+        "Cannot look for INTEGER in SET OF CHAR.",
+        3,
+        None,
+    );
+    check_error(
+        "DEFINE Digits ('1', 2) : SET OF CHAR
+",
+        "Cannot put INTEGER in SET OF CHAR.",
+        1,
+        None,
+    );
+}
+
+#[test]
+fn classes() {
+    let pet = "CLASS Pet
+    PRIVATE Name : STRING
+    PUBLIC FUNCTION Sound RETURNS STRING
+        RETURN Name
+    ENDFUNCTION
+ENDCLASS
+";
+    check_error(
+        &format!("{}DECLARE Rex : Pet\nRex <- NEW Pet\nOUTPUT Rex.Name\n", pet),
+        "`Name` is PRIVATE to `Pet`.",
+        9,
+        Some(12),
+    );
+    check_error(
+        &format!(
+            "{}CLASS Cat INHERITS Pet
+    PUBLIC FUNCTION Sound RETURNS INTEGER
+        RETURN 1
+    ENDFUNCTION
+ENDCLASS
+",
+            pet
+        ),
+        "`Sound` must take the same parameters and return the same type as the method it overrides in `Pet`.",
+        8,
+        None,
+    );
+    // Attributes are reached through the object, so the object must be made first.
+    check_error(
+        &format!("{}DECLARE Rex : Pet\nOUTPUT Rex.Sound()\n", pet),
+        "`Rex` might be used before it is assigned a value.",
+        8,
+        Some(8),
+    );
+}
+
+#[test]
+fn try_except() {
+    // The TRY block might stop before assigning `X`, so the EXCEPT block can't count on it.
+    check_error(
+        "DECLARE X : INTEGER
+DECLARE Y : INTEGER
+TRY
+    X <- 1
+    Y <- X DIV 0
+EXCEPT
+    OUTPUT X
+ENDTRY
+",
+        "`X` might be used before it is assigned a value.",
+        7,
+        Some(12),
+    );
+    assert!(typecheck(
+        "DECLARE X : INTEGER
+TRY
+    X <- 10 DIV 0
+EXCEPT
+    X <- 0
+ENDTRY
+OUTPUT X
+FUNCTION Halve(N : INTEGER) RETURNS INTEGER
+    TRY
+        RETURN N DIV 2
+    EXCEPT
+        RETURN 0
+    ENDTRY
+ENDFUNCTION
+",
+    )
+    .is_ok());
+}
@@ -1,15 +1,44 @@
-use crate::ir::{ast, ast::TypeName, hlir::Type};
+use crate::{
+    error::{TypeError, TypeResult},
+    ir::{ast, ast::TypeName, hlir::Type},
+};
+use std::collections::HashMap;
 
-pub fn pseudo_type(type_name: &TypeName) -> Type {
+/// Resolves `type_name`, looking up the names declared with TYPE in `types`.
+pub fn pseudo_type(
+    type_name: &TypeName,
+    types: &HashMap<String, Type>,
+    line: usize,
+) -> TypeResult<Type> {
     match type_name {
         TypeName::BaseTypeName(base_type_name) => match base_type_name {
-            ast::BaseTypeName::Integer => Type::Integer,
-            ast::BaseTypeName::Real => Type::Real,
-            ast::BaseTypeName::String => Type::String,
-            ast::BaseTypeName::Char => Type::Char,
-            ast::BaseTypeName::Boolean => Type::Boolean,
-            ast::BaseTypeName::Date => unimplemented!(),
-            ast::BaseTypeName::Identifier(_) => unimplemented!(),
+            ast::BaseTypeName::Integer => Ok(Type::Integer),
+            ast::BaseTypeName::Real => Ok(Type::Real),
+            ast::BaseTypeName::String => Ok(Type::String),
+            ast::BaseTypeName::Char => Ok(Type::Char),
+            ast::BaseTypeName::Boolean => Ok(Type::Boolean),
+            ast::BaseTypeName::Date => Err(TypeError::new("DATE is not supported yet.", line)),
+            ast::BaseTypeName::Identifier(name) => match types.get(name) {
+                Some(pseudo_type) => Ok(*pseudo_type),
+                None => Err(TypeError::new(format!("unknown type `{}`.", name), line)),
+            },
         },
+        TypeName::Pointer(pointee) => Ok(Type::pointer(pseudo_type(pointee, types, line)?)),
+        TypeName::Set(element) => match pseudo_type(element, types, line)? {
+            element @ (Type::Integer | Type::Char | Type::String | Type::Boolean | Type::Enum(_)) => {
+                Ok(Type::set(element))
+            }
+            element => Err(TypeError::new(
+                format!(
+                    "Sets can only hold INTEGERs, CHARs, STRINGs, BOOLEANs or enumerated values, not {}.",
+                    element
+                ),
+                line,
+            )),
+        },
+        TypeName::Enum(_) => Err(TypeError::new(
+            "Enumerated types can only be declared with TYPE.",
+            line,
+        )),
     }
 }
@@ -1,10 +1,9 @@
-use super::TypeChecker;
-use crate::ir::hlir::Type;
+use super::{Symbol, TypeChecker};
+use crate::{error::TypeResult, ir::hlir::Type};
 use std::collections::HashMap;
 
 pub struct Variable {
     pub pseudo_type: Type,
-    pub initialized: bool,
 }
 
 impl TypeChecker {
@@ -16,17 +15,23 @@ impl TypeChecker {
         self.symbol_table_stack.pop();
     }
 
-    pub fn decl_var(&mut self, name: String, pseudo_type: Type) {
+    pub fn decl_var(&mut self, name: String, pseudo_type: Type) -> TypeResult<()> {
         if self.check_var_exists(&name) {
-            panic!("var `{}` already declared", name);
+            return Err(self.error(format!("`{}` is already declared.", name)));
         }
+        self.symbols.push(Symbol {
+            name: name.clone(),
+            pseudo_type,
+            scope: self.current_callable.clone(),
+            line: self.line,
+        });
         self.symbol_table_stack.last_mut().unwrap().insert(
             name,
             Variable {
                 pseudo_type,
-                initialized: false,
             },
         );
+        Ok(())
     }
 
     pub fn get_var_mut(&mut self, name: &str) -> Option<&mut Variable> {
@@ -43,6 +48,7 @@ impl TypeChecker {
 
     fn check_var_exists(&self, name: &str) -> bool {
         self.check_decl_exists(name)
+            || self.enum_values.contains_key(name)
             || self
                 .symbol_table_stack
                 .iter()
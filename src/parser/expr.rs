@@ -18,12 +18,27 @@ where
             self.tokens.next();
             // Note: This allows chained assignment syntax `a <- b <- c`.
             // Might need to change this...
-            return Ok(ExprKind::Assignment {
-                target: match expr {
-                    ExprKind::Variable(name) => name,
-                    _ => self.error("invalid assignment target", None)?,
+            return Ok(match expr {
+                ExprKind::Variable { name, .. } => ExprKind::Assignment {
+                    target: name,
+                    value: Box::new(self.expr()?),
                 },
-                value: Box::new(self.expr()?),
+                ExprKind::Deref { pointer, op } => ExprKind::DerefAssignment {
+                    pointer,
+                    op,
+                    value: Box::new(self.expr()?),
+                },
+                ExprKind::Field {
+                    object,
+                    name,
+                    token,
+                } => ExprKind::FieldAssignment {
+                    object,
+                    name,
+                    token,
+                    value: Box::new(self.expr()?),
+                },
+                _ => self.error("invalid assignment target", None)?,
             });
         }
 
@@ -88,6 +103,7 @@ where
             GreaterEqual,
             Less,
             LessEqual,
+            Keyword(In),
         ]) {
             let op = self.tokens.next().unwrap();
             let rhs = self.term()?;
@@ -147,35 +163,92 @@ where
             });
         }
 
-        self.call_expr()
+        if self.match_tokens(&[At]) {
+            self.tokens.next();
+            return match self.tokens.next() {
+                Some(token) => match &token.kind {
+                    Identifier(name) => Ok(ExprKind::AddressOf {
+                        name: name.to_owned(),
+                        token: token.clone(),
+                    }),
+                    _ => self.error("expected variable after `@`.", Some(token)),
+                },
+                None => self.error("expected variable after `@`.", None),
+            };
+        }
+
+        self.deref()
+    }
+
+    /// Postfix `^`, binding tighter than any other operator.
+    fn deref(&mut self) -> ParseResult<ExprKind> {
+        let mut expr = self.call_expr()?;
+
+        while self.match_tokens(&[Caret]) {
+            expr = ExprKind::Deref {
+                pointer: Box::new(expr),
+                op: self.tokens.next().unwrap(),
+            };
+        }
+
+        Ok(expr)
     }
 
+    /// Calls and `.` member access, as in `Pets.First().Name`.
     fn call_expr(&mut self) -> ParseResult<ExprKind> {
         let mut expr = self.primary()?;
 
-        if self.match_tokens(&[OpenParen]) {
-            self.tokens.next();
-            let mut args = Vec::new();
-            loop {
-                if self.match_tokens(&[CloseParen]) {
-                    break;
-                }
-                args.push(self.expr()?);
-                if !self.match_tokens(&[Comma]) {
-                    break;
-                }
+        loop {
+            if self.match_tokens(&[OpenParen]) {
+                expr = ExprKind::Call {
+                    callee: Box::new(expr),
+                    args: self.args()?,
+                };
+            } else if self.match_tokens(&[Dot]) {
                 self.tokens.next();
+                let (name, token) = self.member_name()?;
+                expr = ExprKind::Field {
+                    object: Box::new(expr),
+                    name,
+                    token,
+                };
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// `(<arg>, ...)`
+    pub fn args(&mut self) -> ParseResult<Vec<ExprKind>> {
+        self.tokens.next();
+        let mut args = Vec::new();
+        loop {
+            if self.match_tokens(&[CloseParen]) {
+                break;
             }
-            self.consume(
-                CloseParen,
-                "expected `)` after arguments",
-            )?;
-            expr = ExprKind::Call {
-                callee: Box::new(expr),
-                args,
+            args.push(self.expr()?);
+            if !self.match_tokens(&[Comma]) {
+                break;
             }
+            self.tokens.next();
+        }
+        self.consume(
+            CloseParen,
+            "expected `)` after arguments",
+        )?;
+        Ok(args)
+    }
+
+    /// The name of an attribute or method after `.`, which may be the constructor `NEW`.
+    pub fn member_name(&mut self) -> ParseResult<(String, Token)> {
+        match self.tokens.next() {
+            Some(token) => match token.kind.clone() {
+                Identifier(name) => Ok((name, token)),
+                Keyword(New) => Ok((String::from("NEW"), token)),
+                _ => self.error("expected attribute or method name after `.`.", Some(token)),
+            },
+            None => self.error("expected attribute or method name after `.`.", None),
         }
-        Ok(expr)
     }
 
     fn primary(&mut self) -> ParseResult<ExprKind> {
@@ -194,6 +267,16 @@ where
                 Keyword(keyword) => match keyword {
                     True => ExprKind::Literal(LiteralKind::Boolean(true)),
                     False => ExprKind::Literal(LiteralKind::Boolean(false)),
+                    Null => ExprKind::Literal(LiteralKind::Null),
+                    New => {
+                        let type_name = self.type_name()?;
+                        let args = match self.match_tokens(&[OpenParen]) {
+                            true => self.args()?,
+                            false => Vec::new(),
+                        };
+                        ExprKind::New { type_name, args }
+                    }
+                    Super => ExprKind::Super { token: t.clone() },
                     _ => {
                         return self.error(
                             "expected literal, identifier or grouping (not keyword)",
@@ -201,7 +284,10 @@ where
                         )
                     }
                 },
-                Identifier(name) => ExprKind::Variable(name.to_owned()),
+                Identifier(name) => ExprKind::Variable {
+                    name: name.to_owned(),
+                    token: t.clone(),
+                },
                 OpenParen => {
                     let expr = self.expr()?;
                     self.consume(
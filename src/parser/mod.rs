@@ -24,12 +24,19 @@ where
     let mut declarations = Vec::new();
 
     while parser.tokens.peek().is_some() {
+        let is_callable = parser.match_tokens(&[
+            TokenKind::Keyword(KeywordKind::Function),
+            TokenKind::Keyword(KeywordKind::Procedure),
+        ]);
         match parser.decl() {
             Ok(decl) => declarations.push(decl),
             Err(error) => {
                 parser.had_error = true;
                 parser.errors.push(error);
-                parser.synchronize_decl();
+                match is_callable {
+                    true => parser.synchronize_decl(),
+                    false => parser.synchronize_stmt(),
+                }
             }
         }
     }
@@ -58,17 +65,37 @@ where
             TokenKind::Keyword(KeywordKind::Procedure),
         ]) && self.tokens.peek().is_some()
         {
-            self.tokens.next();
+            self.skip_token();
         }
     }
 
     fn synchronize_stmt(&mut self) {
         while !self.match_tokens(&[TokenKind::NewLine]) && self.tokens.peek().is_some() {
-            self.tokens.next();
+            self.skip_token();
         }
         self.tokens.next();
     }
 
+    /// Skips a token while recovering from an error, still reporting it if the lexer
+    /// couldn't make sense of it.
+    fn skip_token(&mut self) {
+        let token = match self.tokens.next() {
+            Some(token) => token,
+            None => return,
+        };
+        if let TokenKind::Error(msg) = token.kind {
+            let reported = self.errors.iter().any(|error| {
+                error.token.as_ref().is_some_and(|reported| {
+                    reported.line == token.line && reported.col == token.col
+                })
+            });
+            if !reported {
+                self.had_error = true;
+                self.errors.push(ParseError::new(msg, Some(token)));
+            }
+        }
+    }
+
     fn consume(&mut self, kind: TokenKind, msg: &'static str) -> ParseResult<Token> {
         match self.tokens.next() {
             Some(token) => {
@@ -97,8 +124,16 @@ where
         }
     }
 
+    /// Errors at a token the lexer couldn't make sense of are reported with the lexer's
+    /// message, as that is the actual problem.
     #[inline]
     pub fn error<T>(&mut self, msg: &'static str, token: Option<Token>) -> ParseResult<T> {
-        Err(ParseError::new(msg, token))
+        match &token {
+            Some(Token {
+                kind: TokenKind::Error(lexer_msg),
+                ..
+            }) => Err(ParseError::new(lexer_msg, token)),
+            _ => Err(ParseError::new(msg, token)),
+        }
     }
 }
\ No newline at end of file
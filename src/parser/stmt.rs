@@ -1,4 +1,7 @@
-use crate::ir::ast::{Decl, ExprKind, LiteralKind, Param, PassingMode, Stmt};
+use crate::ir::ast::{
+    Access, CaseArm, Decl, ExprKind, LiteralKind, Member, MemberKind, Param, PassingMode, Stmt,
+    StmtKind, TypeName,
+};
 use crate::lexer::token::{KeywordKind, Token, TokenKind};
 use crate::error::ParseResult;
 use crate::parser::Parser;
@@ -8,12 +11,23 @@ where
     I: Iterator<Item = Token>,
 {
     pub fn decl(&mut self) -> ParseResult<Decl> {
+        if !self.match_tokens(&[
+            TokenKind::Keyword(KeywordKind::Procedure),
+            TokenKind::Keyword(KeywordKind::Function),
+            TokenKind::Keyword(KeywordKind::Type),
+            TokenKind::Keyword(KeywordKind::Class),
+        ]) {
+            return Ok(Decl::Stmt(self.stmt()?));
+        }
+
         let token = self.tokens.next();
         let decl = match token {
             Some(tok) => match tok.kind {
                 TokenKind::Keyword(ref keyword) => match keyword {
                     KeywordKind::Procedure => self.procedure(tok),
                     KeywordKind::Function => self.function(tok),
+                    KeywordKind::Type => self.type_decl(tok),
+                    KeywordKind::Class => self.class(tok),
                     _ => self.error("expected declaration.", Some(tok)),
                 },
                 _ => self.error("expected declaration.", Some(tok)),
@@ -55,13 +69,20 @@ where
             stmts.push(stmt);
         }
         match block_terminated {
-            true => Ok(Stmt::Block(stmts)),
+            true => Ok(Stmt {
+                stmt_kind: StmtKind::Block(stmts),
+                line: block_decl.line,
+            }),
             false => self.error("Block unterminated", Some(block_decl))?,
         }
     }
 
     fn stmt(&mut self) -> ParseResult<Stmt> {
-        match self.tokens.peek() {
+        let line = match self.tokens.peek() {
+            Some(t) => t.line,
+            None => return self.error("expected statement", None),
+        };
+        let stmt_kind = match self.tokens.peek() {
             Some(t) => match &t.kind {
                 TokenKind::Keyword(keyword) => match keyword {
                     KeywordKind::Output => self.output(),
@@ -70,15 +91,27 @@ where
                     KeywordKind::Repeat => self.repeat(),
                     KeywordKind::While => self.while_stmt(),
                     KeywordKind::For => self.for_stmt(),
+                    KeywordKind::Case => self.case_stmt(),
+                    KeywordKind::Try => self.try_stmt(),
                     KeywordKind::Declare => self.var_decl(),
+                    KeywordKind::Define => self.define(),
                     KeywordKind::Call => self.call(),
                     KeywordKind::Return => self.return_stmt(),
+                    KeywordKind::Type => {
+                        let token = self.tokens.next();
+                        self.error("TYPEs can only be declared at the top level.", token)
+                    }
+                    KeywordKind::Class => {
+                        let token = self.tokens.next();
+                        self.error("CLASSes can only be declared at the top level.", token)
+                    }
                     _ => self.expr_stmt(),
                 },
                 _ => self.expr_stmt(),
             },
             None => return self.error("expected statement", None),
-        }
+        }?;
+        Ok(Stmt { stmt_kind, line })
     }
 
     fn param(&mut self) -> ParseResult<Param> {
@@ -158,6 +191,8 @@ where
         let name = match self.tokens.next() {
             Some(token) => match token.kind {
                 TokenKind::Identifier(name) => name,
+                // The constructor of a CLASS.
+                TokenKind::Keyword(KeywordKind::New) => String::from("NEW"),
                 _ => {
                     return self.error(
                         "expected identifier for PROCEDURE name.",
@@ -229,12 +264,175 @@ where
         })
     }
 
-    fn call(&mut self) -> ParseResult<Stmt> {
+    fn type_decl(&mut self, type_keyword: Token) -> ParseResult<Decl> {
+        let name = match self.tokens.next() {
+            Some(token) => match token.kind {
+                TokenKind::Identifier(name) => name,
+                _ => return self.error("expected identifier for TYPE name.", Some(token)),
+            },
+            None => return self.error("expected identifier for TYPE name.", None),
+        };
+
+        self.consume(TokenKind::Equal, "expected `=` after TYPE name.")?;
+
+        let type_name = match self.match_tokens(&[TokenKind::OpenParen]) {
+            true => self.enum_values()?,
+            false => self.type_name()?,
+        };
+
+        Ok(Decl::Type {
+            name,
+            type_name,
+            line: type_keyword.line,
+        })
+    }
+
+    /// `(<value>, ...)` after `TYPE <name> =`.
+    fn enum_values(&mut self) -> ParseResult<TypeName> {
         self.tokens.next();
+        let mut values = Vec::new();
+        loop {
+            match self.tokens.next() {
+                Some(Token {
+                    kind: TokenKind::Identifier(value),
+                    ..
+                }) => values.push(value),
+                token => return self.error("expected identifier for enumerated value.", token),
+            }
+
+            if !self.match_tokens(&[TokenKind::Comma]) {
+                break;
+            }
+            self.tokens.next();
+        }
+
+        self.consume(
+            TokenKind::CloseParen,
+            "expected `)` after enumerated values.",
+        )?;
+
+        Ok(TypeName::Enum(values))
+    }
 
+    fn class(&mut self, class_keyword: Token) -> ParseResult<Decl> {
         let name = match self.tokens.next() {
             Some(token) => match token.kind {
                 TokenKind::Identifier(name) => name,
+                _ => return self.error("expected identifier for CLASS name.", Some(token)),
+            },
+            None => return self.error("expected identifier for CLASS name.", None),
+        };
+
+        let parent = match self.match_tokens(&[TokenKind::Keyword(KeywordKind::Inherits)]) {
+            true => {
+                self.tokens.next();
+                match self.tokens.next() {
+                    Some(Token {
+                        kind: TokenKind::Identifier(parent),
+                        ..
+                    }) => Some(parent),
+                    token => {
+                        return self.error(
+                            "expected identifier for parent CLASS after keyword `INHERITS`.",
+                            token,
+                        )
+                    }
+                }
+            }
+            false => None,
+        };
+
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after CLASS header.",
+        )?;
+
+        let mut members = Vec::new();
+        loop {
+            if self.match_tokens(&[TokenKind::Keyword(KeywordKind::EndClass)]) {
+                self.tokens.next();
+                break;
+            } else if self.tokens.peek().is_none() {
+                return self.error("Block unterminated", Some(class_keyword));
+            }
+            match self.member() {
+                Ok(member) => members.push(member),
+                Err(err) => {
+                    self.had_error = true;
+                    self.errors.push(err);
+                    self.synchronize_stmt();
+                }
+            }
+        }
+
+        Ok(Decl::Class {
+            name,
+            parent,
+            members,
+            line: class_keyword.line,
+        })
+    }
+
+    /// An attribute or method of a CLASS, with the new line after it.
+    fn member(&mut self) -> ParseResult<Member> {
+        let access = match self.tokens.peek().map(|token| &token.kind) {
+            Some(TokenKind::Keyword(KeywordKind::Public)) => Some(Access::Public),
+            Some(TokenKind::Keyword(KeywordKind::Private)) => Some(Access::Private),
+            _ => None,
+        };
+        if access.is_some() {
+            self.tokens.next();
+        }
+
+        let token = self.tokens.next();
+        let kind = match token {
+            Some(token) => match token.kind {
+                TokenKind::Keyword(KeywordKind::Procedure) => {
+                    MemberKind::Method(Box::new(self.procedure(token)?))
+                }
+                TokenKind::Keyword(KeywordKind::Function) => {
+                    MemberKind::Method(Box::new(self.function(token)?))
+                }
+                TokenKind::Identifier(name) if access.is_some() => {
+                    self.consume(
+                        TokenKind::Colon,
+                        "expected `:` after attribute name.",
+                    )?;
+                    MemberKind::Attribute {
+                        name,
+                        type_name: self.type_name()?,
+                        line: token.line,
+                    }
+                }
+                TokenKind::Identifier(_) => {
+                    return self.error(
+                        "expected keyword `PUBLIC` or `PRIVATE` before attribute.",
+                        Some(token),
+                    )
+                }
+                _ => return self.error("expected attribute or method in CLASS.", Some(token)),
+            },
+            None => return self.error("expected attribute or method in CLASS.", None),
+        };
+
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after CLASS member.",
+        )?;
+
+        Ok(Member {
+            access: access.unwrap_or(Access::Public),
+            kind,
+        })
+    }
+
+    fn call(&mut self) -> ParseResult<StmtKind> {
+        self.tokens.next();
+
+        let mut object = match self.tokens.next() {
+            Some(token) => match token.kind.clone() {
+                TokenKind::Identifier(name) => ExprKind::Variable { name, token },
+                TokenKind::Keyword(KeywordKind::Super) => ExprKind::Super { token },
                 _ => {
                     return self.error(
                             "expected identifier for PROCEDURE name after keyword, `CALL`.",
@@ -250,11 +448,26 @@ where
             }
         };
 
+        // `CALL <object>.<method>`, where the object may itself be an attribute.
+        let mut method = None;
+        while self.match_tokens(&[TokenKind::Dot]) {
+            self.tokens.next();
+            let member = self.member_name()?;
+            if let Some((name, token)) = method.replace(member) {
+                object = ExprKind::Field {
+                    object: Box::new(object),
+                    name,
+                    token,
+                };
+            }
+        }
+
         let mut args = Vec::new();
         if self.match_tokens(&[TokenKind::OpenParen]) {
             self.tokens.next();
 
-            loop {
+            // `CALL Object.Method()` has no arguments.
+            while !self.match_tokens(&[TokenKind::CloseParen]) {
                 args.push(self.expr()?);
 
                 if !self.match_tokens(&[TokenKind::Comma]) {
@@ -274,20 +487,32 @@ where
             "expected new line after procedure call.",
         )?;
 
-        Ok(Stmt::Call { name, args })
+        match (object, method) {
+            (ExprKind::Variable { name, .. }, None) => Ok(StmtKind::Call { name, args }),
+            (ExprKind::Super { token }, None) => {
+                self.error("expected `.` and a method after keyword `SUPER`.", Some(token))
+            }
+            (object, Some((method, token))) => Ok(StmtKind::CallMethod {
+                object,
+                method,
+                token,
+                args,
+            }),
+            (_, None) => unreachable!("the object is a variable or SUPER until a `.`"),
+        }
     }
 
-    fn return_stmt(&mut self) -> ParseResult<Stmt> {
+    fn return_stmt(&mut self) -> ParseResult<StmtKind> {
         self.tokens.next();
         let expr = self.expr()?;
         self.consume(
             TokenKind::NewLine,
             "expected new line after expression.",
         )?;
-        Ok(Stmt::Return(expr))
+        Ok(StmtKind::Return(expr))
     }
 
-    fn var_decl(&mut self) -> ParseResult<Stmt> {
+    fn var_decl(&mut self) -> ParseResult<StmtKind> {
         self.tokens.next();
 
         let name = match self.tokens.next() {
@@ -317,11 +542,59 @@ where
             "expected new line after variable declaration.",
         )?;
 
-        Ok(Stmt::VarDecl { name, type_name })
+        Ok(StmtKind::VarDecl { name, type_name })
+    }
+
+    /// `DEFINE <name> (<value>, ...) : <set type>`
+    fn define(&mut self) -> ParseResult<StmtKind> {
+        self.tokens.next();
+
+        let name = match self.tokens.next() {
+            Some(token) => match token.kind {
+                TokenKind::Identifier(name) => name,
+                _ => return self.error("expected identifier for set name.", Some(token)),
+            },
+            None => return self.error("expected identifier for set name.", None),
+        };
+
+        self.consume(
+            TokenKind::OpenParen,
+            "expected `(` before the values of a set.",
+        )?;
+        let mut values = Vec::new();
+        if !self.match_tokens(&[TokenKind::CloseParen]) {
+            loop {
+                values.push(self.expr()?);
+
+                if !self.match_tokens(&[TokenKind::Comma]) {
+                    break;
+                }
+                self.tokens.next();
+            }
+        }
+        self.consume(
+            TokenKind::CloseParen,
+            "expected `)` after the values of a set.",
+        )?;
+
+        self.consume(TokenKind::Colon, "expected `:` after the values of a set.")?;
+
+        let type_name = self.type_name()?;
+
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after DEFINE.",
+        )?;
+
+        Ok(StmtKind::Define {
+            name,
+            values,
+            type_name,
+        })
     }
 
-    fn expr_stmt(&mut self) -> ParseResult<Stmt> {
-        let expr_stmt = Stmt::Expr(self.expr()?);
+    fn expr_stmt(&mut self) -> ParseResult<StmtKind> {
+        let expr_stmt = StmtKind::Expr(self.expr()?);
         self.consume(
             TokenKind::NewLine,
             "expected new line after expression.",
@@ -329,7 +602,7 @@ where
         Ok(expr_stmt)
     }
 
-    fn output(&mut self) -> ParseResult<Stmt> {
+    fn output(&mut self) -> ParseResult<StmtKind> {
         self.tokens.next();
 
         let mut exprs = Vec::new();
@@ -344,25 +617,28 @@ where
             TokenKind::NewLine,
             "expected new line after expression.",
         )?;
-        Ok(Stmt::Output(exprs))
+        Ok(StmtKind::Output(exprs))
     }
 
-    fn input(&mut self) -> ParseResult<Stmt> {
-        self.tokens.next();
-        let holder = match self.expr()? {
-            ExprKind::Variable(name) => name,
-
-            // todo: Add token previous
-            _ => self.error("Cannot store input in that", None)?,
+    fn input(&mut self) -> ParseResult<StmtKind> {
+        let input_keyword = self.tokens.next();
+        let target = match self.expr()? {
+            target @ (ExprKind::Variable { .. } | ExprKind::Field { .. } | ExprKind::Deref { .. }) => {
+                target
+            }
+            _ => self.error(
+                "INPUT can only store in a variable, an attribute or through a pointer.",
+                input_keyword,
+            )?,
         };
         self.consume(
             TokenKind::NewLine,
             "expected new line after expression.",
         )?;
-        Ok(Stmt::Input(holder))
+        Ok(StmtKind::Input(target))
     }
 
-    fn if_stmt(&mut self) -> ParseResult<Stmt> {
+    fn if_stmt(&mut self) -> ParseResult<StmtKind> {
         let if_keyword = self.tokens.next().unwrap();
         let condition = self.expr()?;
 
@@ -411,14 +687,14 @@ where
             "expected new line after keyword, `ENDIF`.",
         )?;
 
-        Ok(Stmt::If {
+        Ok(StmtKind::If {
             condition,
             then_branch,
             else_branch,
         })
     }
 
-    fn repeat(&mut self) -> ParseResult<Stmt> {
+    fn repeat(&mut self) -> ParseResult<StmtKind> {
         let repeat_keyword = self.tokens.next().unwrap();
 
         self.consume(
@@ -440,15 +716,18 @@ where
             "expected new line after REPEAT loop condition.",
         )?;
 
-        Ok(Stmt::Repeat {
+        Ok(StmtKind::Repeat {
             body,
             until: condition,
         })
     }
 
-    fn while_stmt(&mut self) -> ParseResult<Stmt> {
+    fn while_stmt(&mut self) -> ParseResult<StmtKind> {
         let while_keyword = self.tokens.next().unwrap();
         let condition = self.expr()?;
+        if self.match_tokens(&[TokenKind::Keyword(KeywordKind::Do)]) {
+            self.tokens.next();
+        }
 
         self.consume(
             TokenKind::NewLine,
@@ -467,11 +746,50 @@ where
             "expected new line after keyword, `ENDWHILE`.",
         )?;
 
-        Ok(Stmt::While { body, condition })
+        Ok(StmtKind::While { body, condition })
     }
 
-    fn for_stmt(&mut self) -> ParseResult<Stmt> {
+    fn try_stmt(&mut self) -> ParseResult<StmtKind> {
+        let try_keyword = self.tokens.next().unwrap();
+
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after keyword, `TRY`.",
+        )?;
+
+        let body = Box::new(self.block(
+            &[TokenKind::Keyword(KeywordKind::Except)],
+            try_keyword.clone(),
+        )?);
+
+        self.consume(
+            TokenKind::Keyword(KeywordKind::Except),
+            "expected keyword, `EXCEPT`, after TRY block.",
+        )?;
+
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after keyword, `EXCEPT`.",
+        )?;
+
+        let handler = Box::new(self.block(&[TokenKind::Keyword(KeywordKind::EndTry)], try_keyword)?);
+
+        self.consume(
+            TokenKind::Keyword(KeywordKind::EndTry),
+            "expected keyword, `ENDTRY`, after EXCEPT block.",
+        )?;
+
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after keyword, `ENDTRY`.",
+        )?;
+
+        Ok(StmtKind::Try { body, handler })
+    }
+
+    fn for_stmt(&mut self) -> ParseResult<StmtKind> {
         let for_keyword = self.tokens.next().unwrap();
+        let counter_token = self.tokens.peek().cloned();
         let initializer = self.expr()?;
 
         self.consume(
@@ -495,17 +813,35 @@ where
             "expected new line after `FOR` loop header.",
         )?;
 
-        let body = self.block(&[TokenKind::Keyword(KeywordKind::Next)], for_keyword)?;
-
-        self.consume(
-            TokenKind::Keyword(KeywordKind::Next),
-            "expected keyword, `NEXT`, after count-controlled loop body.",
+        let line = for_keyword.line;
+        let body = self.block(
+            &[
+                TokenKind::Keyword(KeywordKind::Next),
+                TokenKind::Keyword(KeywordKind::EndFor),
+            ],
+            for_keyword,
         )?;
 
-        let counter = self.expr()?;
+        let counter = match self.tokens.next() {
+            Some(token) if token.kind == TokenKind::Keyword(KeywordKind::Next) => self.expr()?,
+            // `ENDFOR` doesn't repeat the counter, it's the variable the initializer assigns.
+            Some(token) if token.kind == TokenKind::Keyword(KeywordKind::EndFor) => {
+                match (&initializer, counter_token) {
+                    (ExprKind::Assignment { target, .. }, Some(token)) => ExprKind::Variable {
+                        name: target.clone(),
+                        token,
+                    },
+                    _ => self.error("FOR loop must initialize a counter variable.", Some(token))?,
+                }
+            }
+            token => self.error(
+                "expected keyword, `NEXT`, after count-controlled loop body.",
+                token,
+            )?,
+        };
 
         match counter {
-            ExprKind::Variable(_) => (),
+            ExprKind::Variable { .. } => (),
             _ => self.error(
                 "FOR loop must specify variable to increment.",
                 None, // todo: figure out how to insert token here.
@@ -517,39 +853,189 @@ where
             "expected new line after identifier.",
         )?;
 
-        // todo: account for imperfect steps
-        // FOR i <- 0 TO 10 STEP 3      results in infinite loop.
+        // The loop runs while the counter hasn't passed the end value. Counting down needs
+        // a negative STEP, which can only be told apart here when it is written out.
+        let counting_down = matches!(
+            &step,
+            Some(ExprKind::Unary { op, .. }) if op.kind == TokenKind::Minus
+        );
 
         // de-sugaring FOR loops into WHILE loops
-        Ok(Stmt::Block(vec![
-            Stmt::Expr(initializer),
-            Stmt::While {
-                body: Box::new(Stmt::Block(vec![
-                    body,
-                    Stmt::Expr(ExprKind::Assignment {
-                        target: match counter.clone() {
-                            ExprKind::Variable(name) => name,
-                            _ => self.error(
-                                "invalid FOR loop increment variable.",
-                                None,
-                            )?,
-                        },
-                        value: Box::new(ExprKind::Binary {
-                            lhs: Box::new(counter.clone()),
-                            op: Token::from(TokenKind::Plus),
-                            rhs: Box::new(match step {
-                                Some(s) => s,
-                                None => ExprKind::Literal(LiteralKind::Integer(1)),
-                            }),
-                        }),
+        Ok(StmtKind::Block(vec![
+            Stmt {
+                stmt_kind: StmtKind::Expr(initializer),
+                line,
+            },
+            Stmt {
+                stmt_kind: StmtKind::While {
+                    body: Box::new(Stmt {
+                        stmt_kind: StmtKind::Block(vec![
+                            body,
+                            Stmt {
+                                stmt_kind: StmtKind::Expr(ExprKind::Assignment {
+                                    target: match counter.clone() {
+                                        ExprKind::Variable { name, .. } => name,
+                                        _ => self.error(
+                                            "invalid FOR loop increment variable.",
+                                            None,
+                                        )?,
+                                    },
+                                    value: Box::new(ExprKind::Binary {
+                                        lhs: Box::new(counter.clone()),
+                                        op: Token::from(TokenKind::Plus),
+                                        rhs: Box::new(match step {
+                                            Some(s) => s,
+                                            None => ExprKind::Literal(LiteralKind::Integer(1)),
+                                        }),
+                                    }),
+                                }),
+                                line,
+                            },
+                        ]),
+                        line,
                     }),
-                ])),
-                condition: ExprKind::Binary {
-                    lhs: Box::new(counter),
-                    op: Token::from(TokenKind::NotEqual),
-                    rhs: Box::new(to),
+                    condition: ExprKind::Binary {
+                        lhs: Box::new(counter),
+                        op: Token::from(match counting_down {
+                            true => TokenKind::GreaterEqual,
+                            false => TokenKind::LessEqual,
+                        }),
+                        rhs: Box::new(to),
+                    },
                 },
+                line,
             },
         ]))
     }
+
+    fn case_stmt(&mut self) -> ParseResult<StmtKind> {
+        let case_keyword = self.tokens.next().unwrap();
+
+        self.consume(
+            TokenKind::Keyword(KeywordKind::Of),
+            "expected keyword `OF` after keyword `CASE`.",
+        )?;
+
+        let selector = self.expr()?;
+
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after CASE selector.",
+        )?;
+
+        // Every line either starts an arm with `<value> :` or continues the arm before it.
+        let mut arms: Vec<CaseArm> = Vec::new();
+        let mut otherwise: Option<Box<Stmt>> = None;
+        loop {
+            let first_token = match self.tokens.peek() {
+                Some(token) => match token.kind {
+                    TokenKind::NewLine => {
+                        self.tokens.next();
+                        continue;
+                    }
+                    TokenKind::Keyword(KeywordKind::EndCase) => break,
+                    _ => token.clone(),
+                },
+                None => return self.error("Block unterminated", Some(case_keyword)),
+            };
+            let line = first_token.line;
+            let empty_body = Stmt {
+                stmt_kind: StmtKind::Block(Vec::new()),
+                line,
+            };
+
+            let stmt = if self.match_tokens(&[TokenKind::Keyword(KeywordKind::Otherwise)]) {
+                if otherwise.is_some() {
+                    return self.error("OTHERWISE can only be used once in a CASE statement.", Some(first_token));
+                }
+                self.tokens.next();
+                if self.match_tokens(&[TokenKind::Colon]) {
+                    self.tokens.next();
+                }
+                otherwise = Some(Box::new(empty_body));
+                if self.match_tokens(&[TokenKind::NewLine]) {
+                    continue;
+                }
+                self.stmt()?
+            } else if self.at_stmt_keyword() {
+                self.stmt()?
+            } else {
+                let expr = self.expr()?;
+                if self.match_tokens(&[TokenKind::Keyword(KeywordKind::To), TokenKind::Colon]) {
+                    if otherwise.is_some() {
+                        return self.error("OTHERWISE must be the last arm of a CASE statement.", Some(first_token));
+                    }
+                    let to = match self.match_tokens(&[TokenKind::Keyword(KeywordKind::To)]) {
+                        true => {
+                            self.tokens.next();
+                            Some(self.expr()?)
+                        }
+                        false => None,
+                    };
+                    self.consume(TokenKind::Colon, "expected `:` after CASE value.")?;
+                    arms.push(CaseArm {
+                        value: expr,
+                        to,
+                        body: empty_body,
+                    });
+                    if self.match_tokens(&[TokenKind::NewLine]) {
+                        continue;
+                    }
+                    self.stmt()?
+                } else {
+                    self.consume(
+                        TokenKind::NewLine,
+                        "expected new line after expression.",
+                    )?;
+                    Stmt {
+                        stmt_kind: StmtKind::Expr(expr),
+                        line,
+                    }
+                }
+            };
+
+            let body = match (&mut otherwise, arms.last_mut()) {
+                (Some(otherwise), _) => otherwise.as_mut(),
+                (None, Some(arm)) => &mut arm.body,
+                (None, None) => {
+                    return self.error("expected a CASE value before the statement.", Some(first_token))
+                }
+            };
+            if let StmtKind::Block(stmts) = &mut body.stmt_kind {
+                stmts.push(stmt);
+            }
+        }
+
+        self.tokens.next();
+        self.consume(
+            TokenKind::NewLine,
+            "expected new line after keyword, `ENDCASE`.",
+        )?;
+
+        Ok(StmtKind::Case {
+            selector,
+            arms,
+            otherwise,
+        })
+    }
+
+    /// Whether the next statement starts with a keyword, rather than being an expression.
+    fn at_stmt_keyword(&mut self) -> bool {
+        self.match_tokens(&[
+            TokenKind::Keyword(KeywordKind::Output),
+            TokenKind::Keyword(KeywordKind::Input),
+            TokenKind::Keyword(KeywordKind::If),
+            TokenKind::Keyword(KeywordKind::Repeat),
+            TokenKind::Keyword(KeywordKind::While),
+            TokenKind::Keyword(KeywordKind::For),
+            TokenKind::Keyword(KeywordKind::Case),
+            TokenKind::Keyword(KeywordKind::Try),
+            TokenKind::Keyword(KeywordKind::Declare),
+            TokenKind::Keyword(KeywordKind::Define),
+            TokenKind::Keyword(KeywordKind::Call),
+            TokenKind::Keyword(KeywordKind::Return),
+            TokenKind::Keyword(KeywordKind::Type),
+            TokenKind::Keyword(KeywordKind::Class),
+        ])
+    }
 }
@@ -8,7 +8,18 @@ impl<I> Parser<I>
 {
     pub fn type_name(&mut self) -> ParseResult<TypeName> {
         if self.match_tokens(&[TokenKind::Keyword(KeywordKind::Array)]) {
-            unimplemented!("parsing of ARRAY types.")
+            let array = self.tokens.next();
+            self.error("ARRAY types are not supported yet.", array)
+        } else if self.match_tokens(&[TokenKind::Caret]) {
+            self.tokens.next();
+            Ok(TypeName::Pointer(Box::new(self.type_name()?)))
+        } else if self.match_tokens(&[TokenKind::Keyword(KeywordKind::Set)]) {
+            self.tokens.next();
+            self.consume(
+                TokenKind::Keyword(KeywordKind::Of),
+                "expected keyword `OF` after keyword `SET`.",
+            )?;
+            Ok(TypeName::Set(Box::new(self.type_name()?)))
         } else {
             self.base_type_name()
         }
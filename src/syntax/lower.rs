@@ -0,0 +1,24 @@
+use super::SyntaxNode;
+use crate::{
+    error::ParseError,
+    ir::ast::Decl,
+    lexer::token::{Token, TokenKind},
+    parser::program,
+};
+
+/// Converts a syntax tree down to `ir::ast`.
+///
+/// The significant tokens of the tree are fed to `parser::program` exactly as `Lexer`
+/// would have produced them, so both routes always agree on the AST and its errors.
+pub fn lower(root: &SyntaxNode) -> Result<Vec<Decl>, Vec<ParseError>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    for token in root.tokens() {
+        let collapsed_new_line = token.kind() == &TokenKind::NewLine
+            && tokens.last().is_none_or(|last| last.kind == TokenKind::NewLine);
+        if token.is_trivia() || collapsed_new_line {
+            continue;
+        }
+        tokens.push(token.token().clone());
+    }
+    program(tokens.into_iter().peekable())
+}
@@ -0,0 +1,303 @@
+//! Lossless concrete syntax tree.
+//!
+//! `parser` drops everything the compiler doesn't need, while the tree built here keeps
+//! every byte of the source, whitespace and comments included, so tools such as the
+//! formatter can rewrite a program without losing anything. It follows the green/red
+//! tree design: immutable green nodes only know their kind, their children and the
+//! length of their text, and are wrapped on demand into red `SyntaxNode`s which also know
+//! their parent and their offset in the source.
+
+mod lower;
+mod parser;
+#[cfg(test)]
+mod tests;
+
+pub use self::{
+    lower::lower,
+    parser::{parse, Parse},
+};
+
+use crate::lexer::token::{Token, TokenKind};
+use std::{fmt::Write, ops::Range, rc::Rc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Root,
+
+    Procedure,
+    Function,
+    TypeDecl,
+    Class,
+    /// An attribute of a CLASS, with its PUBLIC or PRIVATE.
+    Attribute,
+    ParamList,
+    Param,
+    TypeName,
+    /// Statements between the header of a compound statement and its closing keyword.
+    Block,
+
+    If,
+    Else,
+    While,
+    Repeat,
+    For,
+    Case,
+    CaseArm,
+    Try,
+    /// The EXCEPT block of a TRY statement.
+    Except,
+    Call,
+    Return,
+    Declare,
+    Define,
+    Output,
+    Input,
+    ExprStmt,
+
+    Assignment,
+    Binary,
+    Unary,
+    CallExpr,
+    /// An attribute or method of an object, `Object.Member`.
+    Field,
+    ArgList,
+    Paren,
+    Literal,
+    Name,
+    Deref,
+    AddressOf,
+    New,
+
+    /// Tokens skipped while recovering from a syntax error.
+    Error,
+}
+
+#[derive(Debug)]
+pub struct GreenNode {
+    kind: NodeKind,
+    children: Vec<GreenElement>,
+    len: usize,
+}
+
+impl GreenNode {
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+        Self {
+            kind,
+            children,
+            len,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GreenToken {
+    /// The token as lexed, with the line and column it was found at.
+    token: Token,
+    text: String,
+}
+
+impl GreenToken {
+    pub fn new(token: Token, text: String) -> Self {
+        Self { token, text }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+}
+
+/// Whitespace and comments, which carry no meaning for the grammar.
+pub fn is_trivia(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::Whitespace | TokenKind::Comment)
+}
+
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    offset: usize,
+}
+
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    offset: usize,
+}
+
+#[derive(Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    pub fn ancestors(&self) -> Vec<SyntaxNode> {
+        let mut ancestors = Vec::new();
+        let mut node = self.parent();
+        while let Some(parent) = node {
+            node = parent.parent();
+            ancestors.push(parent);
+        }
+        ancestors
+    }
+
+    /// Byte range of the node in the source, trivia included.
+    pub fn text_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.len
+    }
+
+    pub fn text(&self) -> String {
+        self.tokens().iter().map(|token| token.text()).collect()
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        self.0
+            .green
+            .children
+            .iter()
+            .map(|child| {
+                let element = match child {
+                    GreenElement::Node(node) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                        green: node.clone(),
+                        parent: Some(self.clone()),
+                        offset,
+                    }))),
+                    GreenElement::Token(token) => SyntaxElement::Token(SyntaxToken {
+                        green: token.clone(),
+                        parent: self.clone(),
+                        offset,
+                    }),
+                };
+                offset += child.len();
+                element
+            })
+            .collect()
+    }
+
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|element| match element {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    /// Tokens directly under this node, trivia excluded.
+    pub fn child_tokens(&self) -> Vec<SyntaxToken> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|element| match element {
+                SyntaxElement::Token(token) if !token.is_trivia() => Some(token),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The node and every node below it, in source order.
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut descendants = vec![self.clone()];
+        self.children()
+            .iter()
+            .for_each(|child| descendants.extend(child.descendants()));
+        descendants
+    }
+
+    /// Every token below the node, trivia included, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// The token covering byte `offset`.
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+        self.tokens()
+            .into_iter()
+            .find(|token| token.text_range().contains(&offset))
+    }
+
+    /// An indented outline of the tree, for debugging and tests.
+    pub fn debug_dump(&self) -> String {
+        let mut out = String::new();
+        self.dump(&mut out, 0);
+        out
+    }
+
+    fn dump(&self, out: &mut String, depth: usize) {
+        let range = self.text_range();
+        let _ = writeln!(out, "{}{:?}@{}..{}", "  ".repeat(depth), self.kind(), range.start, range.end);
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => node.dump(out, depth + 1),
+                SyntaxElement::Token(token) => {
+                    let _ = writeln!(out, "{}{:?} {:?}", "  ".repeat(depth + 1), token.kind(), token.text());
+                }
+            }
+        }
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> &TokenKind {
+        &self.green.token.kind
+    }
+
+    pub fn token(&self) -> &Token {
+        &self.green.token
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    pub fn text_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.text.len()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        is_trivia(self.kind())
+    }
+}
@@ -0,0 +1,884 @@
+use super::{is_trivia, GreenElement, GreenNode, GreenToken, NodeKind, SyntaxNode};
+use crate::{
+    error::ParseError,
+    lexer::{
+        dialect::Dialect,
+        lex_with_trivia,
+        token::{KeywordKind::*, Token, TokenKind::{self, *}},
+    },
+};
+use std::rc::Rc;
+
+pub struct Parse {
+    pub root: SyntaxNode,
+    pub errors: Vec<ParseError>,
+}
+
+/// Builds the lossless syntax tree of `src`. Parsing never fails: tokens that don't fit
+/// the grammar end up in `NodeKind::Error` nodes and are reported in `Parse::errors`.
+pub fn parse(src: &str, dialect: Dialect) -> Parse {
+    let mut parser = CstParser {
+        tokens: lex_with_trivia(src, dialect),
+        pos: 0,
+        stack: vec![(NodeKind::Root, Vec::new())],
+        errors: Vec::new(),
+    };
+    parser.root();
+
+    let (kind, children) = parser.stack.pop().unwrap();
+    Parse {
+        root: SyntaxNode::new_root(Rc::new(GreenNode::new(kind, children))),
+        errors: parser.errors,
+    }
+}
+
+/// Binary operators from the loosest to the tightest binding, as in `parser::expr`.
+const BINARY_OPERATORS: [&[TokenKind]; 6] = [
+    &[Keyword(Or)],
+    &[Keyword(And)],
+    &[Equal, NotEqual],
+    &[Greater, GreaterEqual, Less, LessEqual, Keyword(In)],
+    &[Plus, Minus, Ampersand],
+    &[Star, Slash, Keyword(Div), Keyword(Mod)],
+];
+
+/// Keywords that close a block. A block stops at any of them, so a missing closing
+/// keyword is reported once by the construct that's missing it.
+const BLOCK_CLOSERS: [TokenKind; 13] = [
+    Keyword(EndProcedure),
+    Keyword(EndFunction),
+    Keyword(EndIf),
+    Keyword(Else),
+    Keyword(EndWhile),
+    Keyword(Until),
+    Keyword(Next),
+    Keyword(EndFor),
+    Keyword(EndCase),
+    Keyword(Otherwise),
+    Keyword(EndClass),
+    Keyword(Except),
+    Keyword(EndTry),
+];
+
+struct CstParser<'a> {
+    tokens: Vec<(Token, &'a str)>,
+    pos: usize,
+    /// Nodes being built, innermost last, with the children collected so far.
+    stack: Vec<(NodeKind, Vec<GreenElement>)>,
+    errors: Vec<ParseError>,
+}
+
+impl CstParser<'_> {
+    /// Index of the first significant token at or after `idx`.
+    fn significant(&self, mut idx: usize) -> usize {
+        while self
+            .tokens
+            .get(idx)
+            .is_some_and(|(token, _)| is_trivia(&token.kind))
+        {
+            idx += 1;
+        }
+        idx
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens
+            .get(self.significant(self.pos))
+            .map(|(token, _)| &token.kind)
+    }
+
+    /// The significant token after the next one.
+    fn peek_second(&self) -> Option<&TokenKind> {
+        self.tokens
+            .get(self.significant(self.significant(self.pos) + 1))
+            .map(|(token, _)| &token.kind)
+    }
+
+    fn at(&self, kind: &TokenKind) -> bool {
+        self.peek() == Some(kind)
+    }
+
+    fn at_any(&self, kinds: &[TokenKind]) -> bool {
+        self.peek().is_some_and(|kind| kinds.contains(kind))
+    }
+
+    fn at_end_of_line(&self) -> bool {
+        matches!(self.peek(), None | Some(NewLine))
+    }
+
+    fn push(&mut self, element: GreenElement) {
+        self.stack.last_mut().unwrap().1.push(element);
+    }
+
+    /// Moves any trivia before the next significant token into the current node.
+    fn eat_trivia(&mut self) {
+        while self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|(token, _)| is_trivia(&token.kind))
+        {
+            self.bump_raw();
+        }
+    }
+
+    fn bump_raw(&mut self) {
+        let (token, text) = self.tokens[self.pos].clone();
+        self.pos += 1;
+        self.push(GreenElement::Token(Rc::new(GreenToken::new(token, text.to_string()))));
+    }
+
+    /// Adds the next significant token, and the trivia before it, to the current node.
+    fn bump(&mut self) {
+        self.eat_trivia();
+        if self.pos < self.tokens.len() {
+            self.bump_raw();
+        }
+    }
+
+    /// Starts a node at the next significant token. Blocks also take the trivia before
+    /// their first statement, so comments stay inside the block they're written in.
+    fn start_node(&mut self, kind: NodeKind) {
+        if kind != NodeKind::Block {
+            self.eat_trivia();
+        }
+        self.stack.push((kind, Vec::new()));
+    }
+
+    fn finish_node(&mut self) {
+        let (kind, children) = self.stack.pop().unwrap();
+        self.push(GreenElement::Node(Rc::new(GreenNode::new(kind, children))));
+    }
+
+    /// Marks the position of a node that may later be wrapped by `start_node_at`.
+    fn checkpoint(&mut self) -> usize {
+        self.eat_trivia();
+        self.stack.last().unwrap().1.len()
+    }
+
+    /// Starts a node holding everything added to the current node since `checkpoint`.
+    fn start_node_at(&mut self, checkpoint: usize, kind: NodeKind) {
+        let children = self.stack.last_mut().unwrap().1.split_off(checkpoint);
+        self.stack.push((kind, children));
+    }
+
+    fn error(&mut self, msg: &'static str) {
+        let token = self
+            .tokens
+            .get(self.significant(self.pos))
+            .map(|(token, _)| token.clone());
+        let msg = match &token {
+            Some(Token {
+                kind: TokenKind::Error(lexer_msg),
+                ..
+            }) => lexer_msg,
+            _ => msg,
+        };
+        self.errors.push(ParseError::new(msg, token));
+    }
+
+    fn expect(&mut self, kind: TokenKind, msg: &'static str) -> bool {
+        if self.at(&kind) {
+            self.bump();
+            true
+        } else {
+            self.error(msg);
+            false
+        }
+    }
+
+    fn expect_identifier(&mut self, msg: &'static str) {
+        match self.peek() {
+            Some(Identifier(_)) => self.bump(),
+            _ => self.error(msg),
+        }
+    }
+
+    /// Ends a line, skipping whatever is left on it after an error.
+    fn expect_new_line(&mut self, msg: &'static str) {
+        if !self.at_end_of_line() {
+            self.error(msg);
+            self.start_node(NodeKind::Error);
+            while !self.at_end_of_line() {
+                self.bump();
+            }
+            self.finish_node();
+        }
+        if self.at(&NewLine) {
+            self.bump();
+        }
+    }
+
+    fn root(&mut self) {
+        while let Some(kind) = self.peek() {
+            match kind {
+                NewLine => self.bump(),
+                Keyword(Procedure) => self.procedure(),
+                Keyword(Function) => self.function(),
+                Keyword(Type) => self.type_decl(),
+                Keyword(Class) => self.class(),
+                _ => self.stmt_or_skip(),
+            }
+        }
+        // Trailing trivia at the end of the file.
+        self.eat_trivia();
+    }
+
+    /// Also a method of a CLASS, after its PUBLIC or PRIVATE.
+    fn procedure(&mut self) {
+        self.start_node(NodeKind::Procedure);
+        self.access_modifier();
+        self.bump();
+        // The constructor of a CLASS.
+        match self.at(&Keyword(New)) {
+            true => self.bump(),
+            false => self.expect_identifier("expected identifier for PROCEDURE name."),
+        }
+        if self.at(&OpenParen) {
+            self.param_list();
+        }
+        self.expect_new_line("expected new line after PROCEDURE header.");
+        self.block(&[Keyword(EndProcedure)]);
+        if self.expect(Keyword(EndProcedure), "expected `ENDPROCEDURE` after PROCEDURE body.") {
+            self.expect_new_line("expected new line after declaration.");
+        }
+        self.finish_node();
+    }
+
+    fn function(&mut self) {
+        self.start_node(NodeKind::Function);
+        self.access_modifier();
+        self.bump();
+        self.expect_identifier("expected identifier for FUNCTION name.");
+        if self.at(&OpenParen) {
+            self.param_list();
+        }
+        if self.expect(
+            Keyword(Returns),
+            "expected keyword `RETURNS` after FUNCTION declaration",
+        ) {
+            self.type_name();
+        }
+        self.expect_new_line("expected new line after FUNCTION return type.");
+        self.block(&[Keyword(EndFunction)]);
+        if self.expect(Keyword(EndFunction), "expected `ENDFUNCTION` after FUNCTION body.") {
+            self.expect_new_line("expected new line after declaration.");
+        }
+        self.finish_node();
+    }
+
+    fn access_modifier(&mut self) {
+        if self.at_any(&[Keyword(Public), Keyword(Private)]) {
+            self.bump();
+        }
+    }
+
+    fn class(&mut self) {
+        self.start_node(NodeKind::Class);
+        self.bump();
+        self.expect_identifier("expected identifier for CLASS name.");
+        if self.at(&Keyword(Inherits)) {
+            self.bump();
+            self.expect_identifier(
+                "expected identifier for parent CLASS after keyword `INHERITS`.",
+            );
+        }
+        self.expect_new_line("expected new line after CLASS header.");
+        self.start_node(NodeKind::Block);
+        while let Some(kind) = self.peek() {
+            if kind == &NewLine {
+                self.bump();
+            } else if BLOCK_CLOSERS.contains(kind) {
+                break;
+            } else {
+                self.member();
+            }
+        }
+        self.finish_node();
+        if self.expect(Keyword(EndClass), "expected `ENDCLASS` after CLASS members.") {
+            self.expect_new_line("expected new line after declaration.");
+        }
+        self.finish_node();
+    }
+
+    /// An attribute or method of a CLASS. Attributes need their PUBLIC or PRIVATE, to tell
+    /// them apart from statements.
+    fn member(&mut self) {
+        let has_modifier = self.at_any(&[Keyword(Public), Keyword(Private)]);
+        let kind = match has_modifier {
+            true => self.peek_second(),
+            false => self.peek(),
+        };
+        match kind {
+            Some(Keyword(Procedure)) => self.procedure(),
+            Some(Keyword(Function)) => self.function(),
+            Some(Identifier(_)) if has_modifier => {
+                self.start_node(NodeKind::Attribute);
+                self.bump();
+                self.bump();
+                if self.expect(Colon, "expected `:` after attribute name.") {
+                    self.type_name();
+                }
+                self.expect_new_line("expected new line after CLASS member.");
+                self.finish_node();
+            }
+            Some(Identifier(_)) => {
+                self.error("expected keyword `PUBLIC` or `PRIVATE` before attribute.");
+                self.skip_line();
+            }
+            _ => {
+                self.error("expected attribute or method in CLASS.");
+                self.skip_line();
+            }
+        }
+    }
+
+    /// Puts the rest of the line in an error node.
+    fn skip_line(&mut self) {
+        self.start_node(NodeKind::Error);
+        while !self.at_end_of_line() {
+            self.bump();
+        }
+        self.finish_node();
+    }
+
+    fn type_decl(&mut self) {
+        self.start_node(NodeKind::TypeDecl);
+        self.bump();
+        self.expect_identifier("expected identifier for TYPE name.");
+        if self.expect(Equal, "expected `=` after TYPE name.") {
+            match self.at(&OpenParen) {
+                true => self.enum_values(),
+                false => self.type_name(),
+            }
+        }
+        self.expect_new_line("expected new line after declaration.");
+        self.finish_node();
+    }
+
+    /// `(<value>, ...)`, the values of an enumerated type.
+    fn enum_values(&mut self) {
+        self.start_node(NodeKind::TypeName);
+        self.bump();
+        loop {
+            self.expect_identifier("expected identifier for enumerated value.");
+            if !self.at(&Comma) {
+                break;
+            }
+            self.bump();
+        }
+        self.expect(CloseParen, "expected `)` after enumerated values.");
+        self.finish_node();
+    }
+
+    fn param_list(&mut self) {
+        self.start_node(NodeKind::ParamList);
+        self.bump();
+        if !self.at(&CloseParen) {
+            loop {
+                self.param();
+                if !self.at(&Comma) {
+                    break;
+                }
+                self.bump();
+            }
+        }
+        self.expect(CloseParen, "expected `)` after parameters.");
+        self.finish_node();
+    }
+
+    fn param(&mut self) {
+        self.start_node(NodeKind::Param);
+        if self.at_any(&[Keyword(ByRef), Keyword(ByVal)]) {
+            self.bump();
+        }
+        self.expect_identifier("expected identifier for parameter name.");
+        if self.expect(Colon, "expected `:` after parameter name.") {
+            self.type_name();
+        }
+        self.finish_node();
+    }
+
+    fn type_name(&mut self) {
+        self.start_node(NodeKind::TypeName);
+        match self.peek() {
+            Some(Keyword(Array)) => {
+                self.bump();
+                if self.at(&OpenSqrBracket) {
+                    while !self.at_end_of_line() && !self.at(&CloseSqrBracket) {
+                        self.bump();
+                    }
+                    self.expect(CloseSqrBracket, "expected `]` after ARRAY bounds.");
+                }
+                if self.expect(Keyword(Of), "expected keyword `OF` after ARRAY bounds.") {
+                    self.type_name();
+                }
+            }
+            Some(Caret) => {
+                self.bump();
+                self.type_name();
+            }
+            Some(Keyword(Set)) => {
+                self.bump();
+                if self.expect(Keyword(Of), "expected keyword `OF` after keyword `SET`.") {
+                    self.type_name();
+                }
+            }
+            Some(Keyword(Integer | Real | Str | Char | Boolean | Date) | Identifier(_)) => {
+                self.bump()
+            }
+            _ => self.error("expected a base type like INTEGER or something."),
+        }
+        self.finish_node();
+    }
+
+    /// Statements up to the next closing keyword.
+    fn block(&mut self, terminators: &[TokenKind]) {
+        self.start_node(NodeKind::Block);
+        while let Some(kind) = self.peek() {
+            if kind == &NewLine {
+                self.bump();
+            } else if terminators.contains(kind) || BLOCK_CLOSERS.contains(kind) {
+                break;
+            } else {
+                self.stmt_or_skip();
+            }
+        }
+        self.finish_node();
+    }
+
+    /// Parses a statement, making sure at least one token is consumed.
+    fn stmt_or_skip(&mut self) {
+        let pos = self.pos;
+        self.stmt();
+        if self.pos == pos {
+            self.start_node(NodeKind::Error);
+            self.bump();
+            self.finish_node();
+        }
+    }
+
+    fn stmt(&mut self) {
+        match self.peek() {
+            Some(Keyword(If)) => self.if_stmt(),
+            Some(Keyword(While)) => self.while_stmt(),
+            Some(Keyword(Repeat)) => self.repeat(),
+            Some(Keyword(For)) => self.for_stmt(),
+            Some(Keyword(Case)) => self.case(),
+            Some(Keyword(Try)) => self.try_stmt(),
+            Some(Keyword(Declare)) => {
+                self.start_node(NodeKind::Declare);
+                self.bump();
+                self.expect_identifier("expected identifier for variable name.");
+                if self.expect(Colon, "expected `:` after variable name.") {
+                    self.type_name();
+                }
+                self.expect_new_line("expected new line after variable declaration.");
+                self.finish_node();
+            }
+            Some(Keyword(Define)) => {
+                self.start_node(NodeKind::Define);
+                self.bump();
+                self.expect_identifier("expected identifier for set name.");
+                if self.expect(OpenParen, "expected `(` before the values of a set.") {
+                    if !self.at(&CloseParen) {
+                        loop {
+                            self.expr();
+                            if !self.at(&Comma) {
+                                break;
+                            }
+                            self.bump();
+                        }
+                    }
+                    self.expect(CloseParen, "expected `)` after the values of a set.");
+                }
+                if self.expect(Colon, "expected `:` after the values of a set.") {
+                    self.type_name();
+                }
+                self.expect_new_line("expected new line after DEFINE.");
+                self.finish_node();
+            }
+            Some(Keyword(Call)) => {
+                self.start_node(NodeKind::Call);
+                self.bump();
+                match self.at(&Keyword(Super)) {
+                    true => self.bump(),
+                    false => self.expect_identifier(
+                        "expected identifier for PROCEDURE name after keyword, `CALL`.",
+                    ),
+                }
+                // A method, `CALL Object.Method(...)`.
+                while self.at(&Dot) {
+                    self.bump();
+                    self.member_name();
+                }
+                if self.at(&OpenParen) {
+                    self.arg_list();
+                }
+                self.expect_new_line("expected new line after procedure call.");
+                self.finish_node();
+            }
+            Some(Keyword(Return)) => self.keyword_expr_stmt(NodeKind::Return),
+            Some(Keyword(Input)) => self.keyword_expr_stmt(NodeKind::Input),
+            Some(Keyword(Output)) => {
+                self.start_node(NodeKind::Output);
+                self.bump();
+                loop {
+                    self.expr();
+                    if !self.at(&Comma) {
+                        break;
+                    }
+                    self.bump();
+                }
+                self.expect_new_line("expected new line after expression.");
+                self.finish_node();
+            }
+            Some(Keyword(Procedure | Function)) => {
+                self.error("PROCEDUREs and FUNCTIONs can only be declared at the top level.");
+                self.start_node(NodeKind::Error);
+                self.bump();
+                self.finish_node();
+            }
+            Some(Keyword(Type)) => {
+                self.error("TYPEs can only be declared at the top level.");
+                self.start_node(NodeKind::Error);
+                self.bump();
+                self.finish_node();
+            }
+            Some(Keyword(Class)) => {
+                self.error("CLASSes can only be declared at the top level.");
+                self.start_node(NodeKind::Error);
+                self.bump();
+                self.finish_node();
+            }
+            _ => {
+                self.start_node(NodeKind::ExprStmt);
+                self.expr();
+                self.expect_new_line("expected new line after expression.");
+                self.finish_node();
+            }
+        }
+    }
+
+    fn keyword_expr_stmt(&mut self, kind: NodeKind) {
+        self.start_node(kind);
+        self.bump();
+        self.expr();
+        self.expect_new_line("expected new line after expression.");
+        self.finish_node();
+    }
+
+    fn if_stmt(&mut self) {
+        self.start_node(NodeKind::If);
+        self.bump();
+        self.expr();
+        self.expect(Keyword(Then), "expected keyword `THEN` after condition.");
+        self.expect_new_line("expected new line after keyword, `THEN`.");
+        self.block(&[Keyword(Else), Keyword(EndIf)]);
+        if self.at(&Keyword(Else)) {
+            self.start_node(NodeKind::Else);
+            self.bump();
+            self.expect_new_line("expected new line after keyword, `ELSE`.");
+            self.block(&[Keyword(EndIf)]);
+            self.finish_node();
+        }
+        if self.expect(Keyword(EndIf), "expected `ENDIF` after `IF` statement.") {
+            self.expect_new_line("expected new line after keyword, `ENDIF`.");
+        }
+        self.finish_node();
+    }
+
+    fn try_stmt(&mut self) {
+        self.start_node(NodeKind::Try);
+        self.bump();
+        self.expect_new_line("expected new line after keyword, `TRY`.");
+        self.block(&[Keyword(Except)]);
+        if self.at(&Keyword(Except)) {
+            self.start_node(NodeKind::Except);
+            self.bump();
+            self.expect_new_line("expected new line after keyword, `EXCEPT`.");
+            self.block(&[Keyword(EndTry)]);
+            self.finish_node();
+            if self.expect(Keyword(EndTry), "expected keyword, `ENDTRY`, after EXCEPT block.") {
+                self.expect_new_line("expected new line after keyword, `ENDTRY`.");
+            }
+        } else {
+            self.error("expected keyword, `EXCEPT`, after TRY block.");
+            if self.at(&Keyword(EndTry)) {
+                self.bump();
+                self.expect_new_line("expected new line after keyword, `ENDTRY`.");
+            }
+        }
+        self.finish_node();
+    }
+
+    fn while_stmt(&mut self) {
+        self.start_node(NodeKind::While);
+        self.bump();
+        self.expr();
+        if self.at(&Keyword(Do)) {
+            self.bump();
+        }
+        self.expect_new_line("expected new line after WHILE loop condition.");
+        self.block(&[Keyword(EndWhile)]);
+        if self.expect(
+            Keyword(EndWhile),
+            "expected keyword, `ENDWHILE`, after pre-condition loop body.",
+        ) {
+            self.expect_new_line("expected new line after keyword, `ENDWHILE`.");
+        }
+        self.finish_node();
+    }
+
+    fn repeat(&mut self) {
+        self.start_node(NodeKind::Repeat);
+        self.bump();
+        self.expect_new_line("expected new line after keyword, `REPEAT`.");
+        self.block(&[Keyword(Until)]);
+        if self.expect(
+            Keyword(Until),
+            "expected keyword, `UNTIL`, after post-condition loop body.",
+        ) {
+            self.expr();
+            self.expect_new_line("expected new line after REPEAT loop condition.");
+        }
+        self.finish_node();
+    }
+
+    fn for_stmt(&mut self) {
+        self.start_node(NodeKind::For);
+        self.bump();
+        self.expr();
+        if self.expect(
+            Keyword(To),
+            "expected keyword, `TO`, after initializer expression.",
+        ) {
+            self.expr();
+        }
+        if self.at(&Keyword(Step)) {
+            self.bump();
+            self.expr();
+        }
+        self.expect_new_line("expected new line after `FOR` loop header.");
+        self.block(&[Keyword(Next), Keyword(EndFor)]);
+        if self.at(&Keyword(EndFor)) {
+            self.bump();
+            self.expect_new_line("expected new line after keyword, `ENDFOR`.");
+        } else if self.expect(
+            Keyword(Next),
+            "expected keyword, `NEXT`, after count-controlled loop body.",
+        ) {
+            if !self.at_end_of_line() {
+                self.expr();
+            }
+            self.expect_new_line("expected new line after identifier.");
+        }
+        self.finish_node();
+    }
+
+    fn case(&mut self) {
+        self.start_node(NodeKind::Case);
+        self.bump();
+        self.expect(Keyword(Of), "expected keyword `OF` after keyword `CASE`.");
+        self.expr();
+        self.expect_new_line("expected new line after CASE selector.");
+        while let Some(kind) = self.peek() {
+            match kind {
+                NewLine => self.bump(),
+                Keyword(EndCase) => break,
+                Keyword(Otherwise) => {
+                    self.start_node(NodeKind::CaseArm);
+                    self.bump();
+                    if self.at(&Colon) {
+                        self.bump();
+                    }
+                    self.case_arm_body();
+                    self.finish_node();
+                }
+                _ if self.at_case_label() => {
+                    self.start_node(NodeKind::CaseArm);
+                    self.expr();
+                    if self.at(&Keyword(To)) {
+                        self.bump();
+                        self.expr();
+                    }
+                    self.expect(Colon, "expected `:` after CASE value.");
+                    self.case_arm_body();
+                    self.finish_node();
+                }
+                kind if BLOCK_CLOSERS.contains(kind) => break,
+                _ => self.stmt_or_skip(),
+            }
+        }
+        if self.expect(Keyword(EndCase), "expected `ENDCASE` after CASE statement.") {
+            self.expect_new_line("expected new line after keyword, `ENDCASE`.");
+        }
+        self.finish_node();
+    }
+
+    /// The statement after an arm's `:` and any further lines up to the next arm.
+    fn case_arm_body(&mut self) {
+        self.start_node(NodeKind::Block);
+        if !self.at_end_of_line() {
+            self.stmt_or_skip();
+        }
+        while let Some(kind) = self.peek() {
+            if kind == &NewLine {
+                self.bump();
+            } else if BLOCK_CLOSERS.contains(kind) || self.at_case_label() {
+                break;
+            } else {
+                self.stmt_or_skip();
+            }
+        }
+        self.finish_node();
+    }
+
+    /// Whether the current line starts a CASE arm, i.e. has a `:` outside of brackets.
+    fn at_case_label(&self) -> bool {
+        if self.at_any(&[Keyword(Declare), Keyword(Define)]) {
+            return false;
+        }
+        let mut depth = 0;
+        for (token, _) in self.tokens[self.pos..].iter() {
+            match token.kind {
+                NewLine => return false,
+                OpenParen | OpenSqrBracket => depth += 1,
+                CloseParen | CloseSqrBracket => depth -= 1,
+                Colon if depth == 0 => return true,
+                _ => (),
+            }
+        }
+        false
+    }
+
+    fn arg_list(&mut self) {
+        self.start_node(NodeKind::ArgList);
+        self.bump();
+        if !self.at(&CloseParen) {
+            loop {
+                self.expr();
+                if !self.at(&Comma) {
+                    break;
+                }
+                self.bump();
+            }
+        }
+        self.expect(CloseParen, "expected `)` after arguments.");
+        self.finish_node();
+    }
+
+    fn expr(&mut self) {
+        let checkpoint = self.checkpoint();
+        self.binary(0);
+        if self.at(&LeftArrow) {
+            self.start_node_at(checkpoint, NodeKind::Assignment);
+            self.bump();
+            self.expr();
+            self.finish_node();
+        }
+    }
+
+    fn binary(&mut self, level: usize) {
+        if level == BINARY_OPERATORS.len() {
+            return self.unary();
+        }
+        let checkpoint = self.checkpoint();
+        self.binary(level + 1);
+        while self.at_any(BINARY_OPERATORS[level]) {
+            self.start_node_at(checkpoint, NodeKind::Binary);
+            self.bump();
+            self.binary(level + 1);
+            self.finish_node();
+        }
+    }
+
+    fn unary(&mut self) {
+        if self.at_any(&[Minus, Keyword(Not)]) {
+            self.start_node(NodeKind::Unary);
+            self.bump();
+            self.unary();
+            self.finish_node();
+        } else if self.at(&At) {
+            self.start_node(NodeKind::AddressOf);
+            self.bump();
+            match self.peek() {
+                Some(Identifier(_)) => self.primary(),
+                _ => self.error("expected variable after `@`."),
+            }
+            self.finish_node();
+        } else {
+            self.call_expr();
+        }
+    }
+
+    fn call_expr(&mut self) {
+        let checkpoint = self.checkpoint();
+        self.primary();
+        loop {
+            if self.at(&OpenParen) {
+                self.start_node_at(checkpoint, NodeKind::CallExpr);
+                self.arg_list();
+                self.finish_node();
+            } else if self.at(&Dot) {
+                self.start_node_at(checkpoint, NodeKind::Field);
+                self.bump();
+                self.member_name();
+                self.finish_node();
+            } else {
+                break;
+            }
+        }
+        while self.at(&Caret) {
+            self.start_node_at(checkpoint, NodeKind::Deref);
+            self.bump();
+            self.finish_node();
+        }
+    }
+
+    /// An attribute or method after `.`, which may be the constructor NEW.
+    fn member_name(&mut self) {
+        match self.peek() {
+            Some(Identifier(_) | Keyword(New)) => self.bump(),
+            _ => self.error("expected attribute or method name after `.`."),
+        }
+    }
+
+    fn primary(&mut self) {
+        match self.peek() {
+            Some(TokenKind::Literal(_) | Keyword(True | False | Null)) => {
+                self.start_node(NodeKind::Literal);
+                self.bump();
+                self.finish_node();
+            }
+            Some(Keyword(New)) => {
+                self.start_node(NodeKind::New);
+                self.bump();
+                self.type_name();
+                // The arguments of a CLASS's constructor.
+                if self.at(&OpenParen) {
+                    self.arg_list();
+                }
+                self.finish_node();
+            }
+            Some(Identifier(_) | Keyword(Super)) => {
+                self.start_node(NodeKind::Name);
+                self.bump();
+                self.finish_node();
+            }
+            Some(OpenParen) => {
+                self.start_node(NodeKind::Paren);
+                self.bump();
+                self.expr();
+                self.expect(CloseParen, "expected closing `)` after grouping expression");
+                self.finish_node();
+            }
+            Some(kind) if kind != &NewLine && !BLOCK_CLOSERS.contains(kind) => {
+                self.error("expected literal, identifier or grouping");
+                self.start_node(NodeKind::Error);
+                self.bump();
+                self.finish_node();
+            }
+            _ => self.error("expected literal, identifier or grouping"),
+        }
+    }
+}
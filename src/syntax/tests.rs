@@ -0,0 +1,205 @@
+use super::{lower, parse, NodeKind};
+use crate::{
+    lexer::{dialect::Dialect, Lexer},
+    parser::program,
+};
+
+const SRC: &str = "// Totals the numbers from 1 to 3.
+DECLARE Total : INTEGER
+
+FUNCTION Double(N : INTEGER) RETURNS INTEGER
+    RETURN N * 2   // trailing comment
+ENDFUNCTION
+
+Total <- 0
+FOR I <- 1 TO 3
+    // inside the loop
+    Total  <-  Total + Double(I)
+NEXT I
+IF Total > 5 THEN
+    OUTPUT \"big\", Total
+ELSE
+    OUTPUT \"small\"
+ENDIF
+";
+
+#[test]
+fn round_trips_source() {
+    let parse = parse(SRC, Dialect::Strict);
+    assert!(parse.errors.is_empty());
+    assert_eq!(parse.root.text(), SRC);
+    assert_eq!(parse.root.text_range(), 0..SRC.len());
+}
+
+#[test]
+fn round_trips_invalid_source() {
+    let src = "IF X > THEN\n  Y <- \"oops\nENDWHILE\n\t'ab' )";
+    let parse = parse(src, Dialect::Strict);
+    assert!(!parse.errors.is_empty());
+    assert_eq!(parse.root.text(), src);
+}
+
+#[test]
+fn comments_stay_in_their_block() {
+    let root = parse(SRC, Dialect::Strict).root;
+    let for_stmt = root
+        .descendants()
+        .into_iter()
+        .find(|node| node.kind() == NodeKind::For)
+        .unwrap();
+    let block = for_stmt
+        .children()
+        .into_iter()
+        .find(|node| node.kind() == NodeKind::Block)
+        .unwrap();
+    assert!(block.text().contains("// inside the loop"));
+
+    let function = root
+        .children()
+        .into_iter()
+        .find(|node| node.kind() == NodeKind::Function)
+        .unwrap();
+    let return_stmt = function
+        .descendants()
+        .into_iter()
+        .find(|node| node.kind() == NodeKind::Return)
+        .unwrap();
+    assert_eq!(return_stmt.text(), "RETURN N * 2   // trailing comment\n");
+}
+
+#[test]
+fn expression_structure() {
+    let root = parse("X <- 1 + 2 * 3\n", Dialect::Strict).root;
+    let kinds: Vec<NodeKind> = root.descendants().iter().map(|node| node.kind()).collect();
+    use NodeKind::*;
+    assert_eq!(
+        kinds,
+        vec![Root, ExprStmt, Assignment, Name, Binary, Literal, Binary, Literal, Literal]
+    );
+}
+
+#[test]
+fn lowers_to_the_same_ast_as_the_parser() {
+    let lowered = lower(&parse(SRC, Dialect::Strict).root).unwrap();
+    let parsed = program(Lexer::new(SRC).peekable()).unwrap();
+    assert_eq!(format!("{:?}", lowered), format!("{:?}", parsed));
+}
+
+#[test]
+fn token_at_offset() {
+    let root = parse(SRC, Dialect::Strict).root;
+    let offset = SRC.find("Double(I)").unwrap() + 2;
+    let token = root.token_at_offset(offset).unwrap();
+    assert_eq!(token.text(), "Double");
+    assert_eq!(token.token().line, 10);
+    assert_eq!(token.parent().kind(), NodeKind::Name);
+}
+
+#[test]
+fn igcse_loops() {
+    let src = "// dialect: igcse
+DECLARE Total : INTEGER
+DECLARE Count : INTEGER
+Total ← 0
+FOR Count ← 1 TO 4
+    Total ← Total + Count
+ENDFOR
+WHILE Total > 6 DO
+    Total ← Total - 3
+ENDWHILE
+";
+    let parse = parse(src, Dialect::Strict);
+    assert!(parse.errors.is_empty());
+    assert_eq!(parse.root.text(), src);
+    let lowered = lower(&parse.root).unwrap();
+    let parsed = program(Lexer::new(src).peekable()).unwrap();
+    assert_eq!(format!("{:?}", lowered), format!("{:?}", parsed));
+}
+
+#[test]
+fn enumerated_types_and_case() {
+    let src = "TYPE Season = (Spring, Summer, Autumn)
+DECLARE Now : Season
+Now <- Spring
+CASE OF Now
+    Spring : OUTPUT \"spring\"
+        Now <- Now + 1
+    Summer TO Autumn : OUTPUT \"warm\"
+    OTHERWISE : OUTPUT \"cold\"
+ENDCASE
+";
+    let parse = parse(src, Dialect::Strict);
+    assert!(parse.errors.is_empty());
+    assert_eq!(parse.root.text(), src);
+    let lowered = lower(&parse.root).unwrap();
+    let parsed = program(Lexer::new(src).peekable()).unwrap();
+    assert_eq!(format!("{:?}", lowered), format!("{:?}", parsed));
+}
+
+#[test]
+fn sets() {
+    let src = "TYPE Letters = SET OF CHAR
+DEFINE Vowels ('A', 'E', 'I') : Letters
+DEFINE Empty () : SET OF INTEGER
+OUTPUT 'E' IN Vowels + Vowels
+";
+    let parse = parse(src, Dialect::Strict);
+    assert!(parse.errors.is_empty());
+    assert_eq!(parse.root.text(), src);
+    let lowered = lower(&parse.root).unwrap();
+    let parsed = program(Lexer::new(src).peekable()).unwrap();
+    assert_eq!(format!("{:?}", lowered), format!("{:?}", parsed));
+}
+
+#[test]
+fn classes() {
+    let src = "CLASS Pet
+    PRIVATE Name : STRING
+    PUBLIC PROCEDURE NEW(GivenName : STRING)
+        Name <- GivenName
+    ENDPROCEDURE
+    FUNCTION GetName RETURNS STRING
+        RETURN Name
+    ENDFUNCTION
+ENDCLASS
+CLASS Cat INHERITS Pet
+    PUBLIC PROCEDURE NEW(GivenName : STRING)
+        SUPER.NEW(GivenName)
+    ENDPROCEDURE
+ENDCLASS
+DECLARE Tom : Cat
+Tom <- NEW Cat(\"Tom\")
+CALL Tom.Owner.Greet()
+OUTPUT Tom.GetName(), Tom.Owner.Name
+";
+    let parse = parse(src, Dialect::Strict);
+    assert!(parse.errors.is_empty());
+    assert_eq!(parse.root.text(), src);
+    let lowered = lower(&parse.root).unwrap();
+    let parsed = program(Lexer::new(src).peekable()).unwrap();
+    assert_eq!(format!("{:?}", lowered), format!("{:?}", parsed));
+}
+
+#[test]
+fn try_except() {
+    let src = "DECLARE X : INTEGER
+TRY
+    X <- 10 DIV 0
+EXCEPT
+    OUTPUT \"caught\"
+ENDTRY
+";
+    let result = parse(src, Dialect::Strict);
+    assert!(result.errors.is_empty());
+    assert_eq!(result.root.text(), src);
+    let lowered = lower(&result.root).unwrap();
+    let parsed = program(Lexer::new(src).peekable()).unwrap();
+    assert_eq!(format!("{:?}", lowered), format!("{:?}", parsed));
+
+    let missing_except = parse("TRY\n    OUTPUT 1\nENDTRY\n", Dialect::Strict);
+    assert_eq!(missing_except.errors.len(), 1);
+    assert_eq!(
+        missing_except.errors[0].msg,
+        "expected keyword, `EXCEPT`, after TRY block."
+    );
+}
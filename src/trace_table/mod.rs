@@ -0,0 +1,240 @@
+//! Trace tables, as used in exam questions: a row is filled in for every assignment and
+//! every OUTPUT as the program runs, recorded by a `vm::Hook` using the debug info that
+//! `codegen_bytecode` emits.
+
+use crate::{
+    as_rs_string,
+    ir::hlir::Type,
+    vm::{
+        disassembler::{enum_value, format_value},
+        instr::Instr,
+        obj::{as_set, ObjKind},
+        pointer::format_pointer,
+        value::{real_to_str, Value, ValueKind},
+        Hook, Vm,
+    },
+};
+
+const OUTPUT_COLUMN: &str = "OUTPUT";
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum TraceFormat {
+    Table,
+    Csv,
+    Markdown,
+}
+
+pub struct TraceTable {
+    /// Variables to record, or every variable when empty.
+    chosen: Vec<String>,
+    columns: Vec<String>,
+    rows: Vec<Vec<Option<String>>>,
+    /// Text of the OUTPUT statement currently being executed.
+    output_line: String,
+}
+
+impl TraceTable {
+    pub fn new(chosen: Vec<String>) -> Self {
+        Self {
+            columns: chosen.clone(),
+            chosen,
+            rows: Vec::new(),
+            output_line: String::new(),
+        }
+    }
+
+    /// Records `value` under `column`. Like a trace table on paper, rows are filled in left
+    /// to right, so a new row is started whenever the cell in the current row, or one to
+    /// its right, is already filled.
+    fn record(&mut self, column: &str, value: String) {
+        let column_idx = match self.columns.iter().position(|name| name == column) {
+            Some(idx) => idx,
+            None => {
+                self.columns.push(column.to_string());
+                self.rows.iter_mut().for_each(|row| row.push(None));
+                self.columns.len() - 1
+            }
+        };
+        let header = self.header();
+        let position = header.iter().position(|name| *name == column).unwrap();
+        let from_column: Vec<usize> = header[position..]
+            .iter()
+            .map(|column| self.columns.iter().position(|name| name == column).unwrap())
+            .collect();
+        let needs_row = match self.rows.last() {
+            Some(row) => from_column.iter().any(|idx| row[*idx].is_some()),
+            None => true,
+        };
+        if needs_row {
+            self.rows.push(vec![None; self.columns.len()]);
+        }
+        self.rows.last_mut().unwrap()[column_idx] = Some(value);
+    }
+
+    fn is_traced(&self, name: &str) -> bool {
+        self.chosen.is_empty() || self.chosen.iter().any(|chosen| chosen == name)
+    }
+
+    /// Column names, with OUTPUT always last.
+    fn header(&self) -> Vec<&str> {
+        let mut header: Vec<&str> = self
+            .columns
+            .iter()
+            .filter(|column| *column != OUTPUT_COLUMN)
+            .map(|column| column.as_str())
+            .collect();
+        if self.columns.iter().any(|column| column == OUTPUT_COLUMN) {
+            header.push(OUTPUT_COLUMN);
+        }
+        header
+    }
+
+    fn cells(&self) -> Vec<Vec<&str>> {
+        let header = self.header();
+        self.rows
+            .iter()
+            .map(|row| {
+                header
+                    .iter()
+                    .map(|column| {
+                        let idx = self.columns.iter().position(|name| name == column).unwrap();
+                        row[idx].as_deref().unwrap_or("")
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn render(&self, format: TraceFormat) -> String {
+        let header = self.header();
+        let rows = self.cells();
+        let mut out = String::new();
+        match format {
+            TraceFormat::Table => {
+                let widths: Vec<usize> = header
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, column)| {
+                        rows.iter()
+                            .map(|row| row[idx].chars().count())
+                            .chain(std::iter::once(column.chars().count()))
+                            .max()
+                            .unwrap()
+                    })
+                    .collect();
+                let line = |cells: &[&str]| {
+                    let cells: Vec<String> = cells
+                        .iter()
+                        .zip(widths.iter())
+                        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                        .collect();
+                    format!("| {} |\n", cells.join(" | "))
+                };
+                let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+                let separator = format!("+-{}-+\n", separator.join("-+-"));
+                out.push_str(&separator);
+                out.push_str(&line(&header));
+                out.push_str(&separator);
+                rows.iter().for_each(|row| out.push_str(&line(row)));
+                out.push_str(&separator);
+            }
+            TraceFormat::Csv => {
+                let line = |cells: &[&str]| {
+                    let cells: Vec<String> = cells.iter().map(|cell| csv_field(cell)).collect();
+                    format!("{}\n", cells.join(","))
+                };
+                out.push_str(&line(&header));
+                rows.iter().for_each(|row| out.push_str(&line(row)));
+            }
+            TraceFormat::Markdown => {
+                let line = |cells: &[&str]| {
+                    let cells: Vec<String> =
+                        cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                    format!("| {} |\n", cells.join(" | "))
+                };
+                out.push_str(&line(&header));
+                let separator: Vec<&str> = header.iter().map(|_| "---").collect();
+                out.push_str(&line(&separator));
+                rows.iter().for_each(|row| out.push_str(&line(row)));
+            }
+        }
+        out
+    }
+}
+
+impl Hook for TraceTable {
+    fn before_instr(&mut self, vm: &Vm, instr_idx: usize) {
+        let frames = vm.frames(instr_idx);
+        let frame = frames.last().unwrap();
+        let chunk = &frame.function.chunk;
+
+        // OUTPUT's spaces and newlines don't look at the stack, which may be empty then.
+        match (&chunk.instructions[instr_idx], frame.slots.last()) {
+            (Instr::StoreLocal(slot), Some(top)) => {
+                if let Some(local) = chunk.local_at(*slot, instr_idx) {
+                    if self.is_traced(&local.name) {
+                        let value = format_cell(top, local.pseudo_type);
+                        self.record(&local.name, value);
+                    }
+                }
+            }
+            (Instr::StoreGlobal(idx), Some(top)) => {
+                let script = &frames.first().unwrap().function.chunk;
+                // PROCEDUREs and FUNCTIONs are stored in globals too, without a type.
+                let pseudo_type = match script.global_types[*idx] {
+                    Some(pseudo_type) => pseudo_type,
+                    None => return,
+                };
+                if let Some(name) = script.global_names.get(*idx) {
+                    if self.is_traced(name) {
+                        self.record(name, format_cell(top, pseudo_type));
+                    }
+                }
+            }
+            (Instr::Output(pseudo_type), Some(top)) => {
+                self.output_line.push_str(&format_cell(top, *pseudo_type))
+            }
+            (Instr::OutputSpace, _) => self.output_line.push(' '),
+            (Instr::OutputLn, _) => {
+                let line = std::mem::take(&mut self.output_line);
+                self.record(OUTPUT_COLUMN, line.trim_end().to_string());
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Formats a value of type `pseudo_type` the way it would be written into a trace table:
+/// as OUTPUT would print it, without quotes.
+pub fn format_cell(value: &Value, pseudo_type: Type) -> String {
+    let kind = match pseudo_type {
+        Type::Enum(enum_type) => return enum_value(value, enum_type),
+        _ => ValueKind::from(pseudo_type),
+    };
+    unsafe {
+        match kind {
+            ValueKind::Integer => value.integer.to_string(),
+            ValueKind::Real => real_to_str(value.real),
+            ValueKind::Char => value.char.to_string(),
+            ValueKind::Boolean => String::from(if value.boolean { "TRUE" } else { "FALSE" }),
+            ValueKind::Obj if value.obj.is_null() => String::new(),
+            ValueKind::Obj => match (*value.obj).kind {
+                ObjKind::Set => as_set(value.obj).to_string(),
+                ObjKind::Instance => format_value(value, kind),
+                _ => as_rs_string!(value.obj).clone(),
+            },
+            ValueKind::Pointer => format_pointer(value),
+        }
+    }
+}
+
+fn csv_field(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests;
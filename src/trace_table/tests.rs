@@ -0,0 +1,105 @@
+use super::{TraceFormat, TraceTable};
+use crate::{codegen_bytecode::emit, lexer::Lexer, naive_tc, parser::program, vm::Vm};
+
+const SRC: &str = "PROCEDURE Main
+    DECLARE Count : INTEGER
+    DECLARE Total : INTEGER
+    Total <- 0
+    Count <- 1
+    WHILE Count < 3
+        Total <- Total + Count
+        OUTPUT \"total,\", Total
+        Count <- Count + 1
+    ENDWHILE
+ENDPROCEDURE
+";
+
+fn trace(src: &str, chosen: &[&str]) -> TraceTable {
+    let mut vm = Vm::new();
+    let decls = program(Lexer::new(src).peekable()).unwrap();
+    let script = emit(naive_tc::typecheck(decls).unwrap(), &mut vm).unwrap();
+    let mut trace_table = TraceTable::new(chosen.iter().map(|name| name.to_string()).collect());
+    vm.execute_with_hook(script, &mut trace_table).unwrap();
+    trace_table
+}
+
+#[test]
+fn rows_start_when_a_cell_is_reused() {
+    let table = trace(SRC, &[]).render(TraceFormat::Table);
+    assert_eq!(
+        table,
+        "\
++-------+-------+----------+
+| Total | Count | OUTPUT   |
++-------+-------+----------+
+| 0     | 1     |          |
+| 1     |       | total, 1 |
+|       | 2     |          |
+| 3     |       | total, 3 |
+|       | 3     |          |
++-------+-------+----------+
+"
+    );
+}
+
+#[test]
+fn chosen_variables_keep_their_order() {
+    let table = trace(SRC, &["Count", "Total"]).render(TraceFormat::Markdown);
+    assert_eq!(
+        table,
+        "\
+| Count | Total | OUTPUT |
+| --- | --- | --- |
+|  | 0 |  |
+| 1 | 1 | total, 1 |
+| 2 | 3 | total, 3 |
+| 3 |  |  |
+"
+    );
+}
+
+#[test]
+fn csv_quotes_fields() {
+    let table = trace(SRC, &["Count"]).render(TraceFormat::Csv);
+    assert_eq!(table, "Count,OUTPUT\n1,\"total, 1\"\n2,\"total, 3\"\n3,\n");
+}
+
+#[test]
+fn output_with_an_empty_stack() {
+    let top_level = "DECLARE X : INTEGER\nX <- 2\nOUTPUT X\nOUTPUT \"done\"\n";
+    assert_eq!(
+        trace(top_level, &[]).render(TraceFormat::Csv),
+        "X,OUTPUT\n2,2\n,done\n"
+    );
+
+    // A value assigned after an OUTPUT goes on the next row, not left of it.
+    let reassigned = "DECLARE I : INTEGER\nOUTPUT \"start\"\nI <- 1\nOUTPUT I\nI <- 2\n";
+    assert_eq!(
+        trace(reassigned, &[]).render(TraceFormat::Csv),
+        "I,OUTPUT\n,start\n1,1\n2,\n"
+    );
+
+    let no_locals = "PROCEDURE Main\n    OUTPUT \"a\", 'b'\n    OUTPUT 1\nENDPROCEDURE\n";
+    assert_eq!(
+        trace(no_locals, &[]).render(TraceFormat::Csv),
+        "OUTPUT\na b\n1\n"
+    );
+}
+
+#[test]
+fn enumerated_values_by_name() {
+    let src = "TYPE Season = (Spring, Summer, Autumn)
+DECLARE Now : Season
+Now <- Summer
+PROCEDURE Next
+    DECLARE Later : Season
+    Later <- Now + 1
+    OUTPUT Later
+ENDPROCEDURE
+CALL Next
+";
+    assert_eq!(
+        trace(src, &[]).render(TraceFormat::Csv),
+        "Now,Later,OUTPUT\nSummer,Autumn,Autumn\n"
+    );
+}
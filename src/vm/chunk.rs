@@ -1,21 +1,90 @@
-use crate::vm::instr::Instr;
-use super::value::Value;
+use crate::{ir::hlir::Type, vm::instr::Instr};
+use super::value::{Value, ValueKind};
 
 pub struct Chunk {
     pub instructions: Vec<Instr>,
-    pub constants: Vec<Value>
+    /// Zero-based source line of each instruction, parallel to `instructions`.
+    pub lines: Vec<usize>,
+    pub constants: Vec<Value>,
+    /// Runtime tag of each constant, parallel to `constants`.
+    pub constant_kinds: Vec<ValueKind>,
+    /// Names of the global slots, indexed by `LoadGlobal`/`StoreGlobal` operands.
+    /// Only populated for the top-level script, like `globals`.
+    pub global_names: Vec<String>,
+    /// Value of each global slot before the program runs, which the VM copies into its
+    /// own globals table.
+    pub globals: Vec<Value>,
+    /// Runtime tag of each global, parallel to `globals`.
+    pub global_kinds: Vec<ValueKind>,
+    /// Type of each global, parallel to `globals`. PROCEDUREs and FUNCTIONs have none.
+    pub global_types: Vec<Option<Type>>,
+    /// Index of the first instruction of every statement, in ascending order.
+    pub stmt_starts: Vec<usize>,
+    /// Local variables and parameters, used by the debugger and disassembler.
+    pub locals: Vec<LocalInfo>,
+}
+
+/// Debug information about a local variable slot, emitted by `codegen_bytecode`.
+#[derive(Debug, Clone)]
+pub struct LocalInfo {
+    pub name: String,
+    pub slot: usize,
+    pub pseudo_type: Type,
+    /// The slot holds this variable for instructions in `start..end`.
+    pub start: usize,
+    pub end: usize,
 }
 
 impl Chunk {
     pub fn new() -> Self {
         Self {
             instructions: Vec::new(),
+            lines: Vec::new(),
             constants: Vec::new(),
+            constant_kinds: Vec::new(),
+            global_names: Vec::new(),
+            globals: Vec::new(),
+            global_kinds: Vec::new(),
+            global_types: Vec::new(),
+            stmt_starts: Vec::new(),
+            locals: Vec::new(),
         }
     }
 
-    pub fn add_constant(&mut self, value: Value) -> usize {
+    pub fn write(&mut self, instr: Instr, line: usize) {
+        self.instructions.push(instr);
+        self.lines.push(line);
+    }
+
+    /// The local variable occupying `slot` when the instruction at `instr_idx` runs.
+    pub fn local_at(&self, slot: usize, instr_idx: usize) -> Option<&LocalInfo> {
+        self.local_idx_at(slot, instr_idx).map(|idx| &self.locals[idx])
+    }
+
+    /// Index in `locals` of the variable `local_at` finds.
+    pub fn local_idx_at(&self, slot: usize, instr_idx: usize) -> Option<usize> {
+        self.locals
+            .iter()
+            .position(|local| local.slot == slot && local.start <= instr_idx && instr_idx < local.end)
+    }
+
+    pub fn add_constant(&mut self, value: Value, kind: ValueKind) -> usize {
         self.constants.push(value);
+        self.constant_kinds.push(kind);
         self.constants.len() - 1
     }
+
+    pub fn add_global(
+        &mut self,
+        name: String,
+        value: Value,
+        kind: ValueKind,
+        pseudo_type: Option<Type>,
+    ) -> usize {
+        self.global_names.push(name);
+        self.globals.push(value);
+        self.global_kinds.push(kind);
+        self.global_types.push(pseudo_type);
+        self.globals.len() - 1
+    }
 }
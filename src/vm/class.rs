@@ -0,0 +1,59 @@
+//! Objects of CLASSes, made with NEW.
+//!
+//! A CLASS is a constant holding the types of its attributes and its table of methods:
+//! for each method, inherited ones first, the global holding the version to call on
+//! objects of the CLASS. An overriding method takes the place of the one it overrides, so
+//! calling a method is looking up the same place in whichever table the object has.
+//!
+//! Like values allocated with NEW, objects live until the program ends.
+
+use super::{
+    obj::{Obj, ObjKind},
+    value::Value,
+};
+use crate::ir::hlir::{ClassType, Type};
+
+#[repr(C)]
+pub struct ObjClass {
+    pub obj: Obj,
+    pub class_type: &'static ClassType,
+    /// Type of each attribute, by index.
+    pub fields: Vec<Type>,
+    /// Global holding each method, by slot.
+    pub methods: Vec<usize>,
+}
+
+#[repr(C)]
+pub struct ObjInstance {
+    pub obj: Obj,
+    pub class: *mut ObjClass,
+    pub fields: Vec<Value>,
+}
+
+impl ObjClass {
+    pub fn new(class_type: &'static ClassType, fields: Vec<Type>, methods: Vec<usize>) -> ObjClass {
+        ObjClass {
+            obj: Obj {
+                kind: ObjKind::Class,
+                next: std::ptr::null_mut(),
+            },
+            class_type,
+            fields,
+            methods,
+        }
+    }
+}
+
+impl ObjInstance {
+    /// An object of `class`, with its attributes holding `fields`.
+    pub fn new(class: *mut ObjClass, fields: Vec<Value>) -> ObjInstance {
+        ObjInstance {
+            obj: Obj {
+                kind: ObjKind::Instance,
+                next: std::ptr::null_mut(),
+            },
+            class,
+            fields,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use super::{
+    chunk::Chunk,
+    instr::Instr,
+    obj::{as_class, as_instance, as_set, ObjFn, ObjKind, ObjString},
+    pointer::format_pointer,
+    value::{Value, ValueKind},
+};
+use crate::{
+    as_rs_string,
+    ir::hlir::{EnumType, Type},
+};
+use std::fmt::Write;
+
+/// Disassembles `script` and every function reachable through its constant pool.
+/// When `src` is given, the text of each originating source line is printed alongside.
+pub fn disassemble(script: &ObjFn, src: Option<&str>) -> String {
+    let mut functions = vec![script as *const ObjFn];
+    let mut idx = 0;
+    while idx < functions.len() {
+        let chunk = unsafe { &(*functions[idx]).chunk };
+        for (value, kind) in chunk.constants.iter().zip(chunk.constant_kinds.iter()) {
+            if let Some(function) = as_function(value, *kind) {
+                functions.push(function);
+            }
+        }
+        idx += 1;
+    }
+
+    let src_lines: Vec<&str> = src.map(|src| src.lines().collect()).unwrap_or_default();
+    let global_names = &script.chunk.global_names;
+    let mut out = String::new();
+    for function in functions.into_iter() {
+        let function = unsafe { &*function };
+        disassemble_function(&mut out, function, global_names, &src_lines);
+        out.push('\n');
+    }
+    out
+}
+
+fn disassemble_function(
+    out: &mut String,
+    function: &ObjFn,
+    global_names: &[String],
+    src_lines: &[&str],
+) {
+    let _ = writeln!(
+        out,
+        "== {} (arity {}) ==",
+        function_name(function),
+        function.arity
+    );
+
+    let chunk = &function.chunk;
+    let labels = jump_labels(chunk);
+    let mut previous_line = None;
+    for idx in 0..chunk.instructions.len() {
+        if let Some(label) = labels.iter().position(|&target| target == idx) {
+            let _ = writeln!(out, "L{}:", label);
+        }
+
+        let line = chunk.lines.get(idx).copied();
+        if line != previous_line {
+            if let Some(text) = line.and_then(|line| src_lines.get(line)) {
+                let _ = writeln!(out, "{:>12}; {}", "", text.trim());
+            }
+        }
+        let line_column = match line {
+            Some(line) if line == previous_line.unwrap_or(usize::MAX) => String::from("|"),
+            Some(line) => (line + 1).to_string(),
+            None => String::from("?"),
+        };
+        previous_line = line;
+
+        let _ = writeln!(
+            out,
+            "{:04} {:>5}  {}",
+            idx,
+            line_column,
+            format_instr(idx, chunk, global_names, &labels)
+        );
+    }
+    if let Some(label) = labels
+        .iter()
+        .position(|&target| target == chunk.instructions.len())
+    {
+        let _ = writeln!(out, "L{}:", label);
+    }
+}
+
+/// Formats the instruction at `instr_idx` with its operands resolved against `chunk`.
+pub fn format_instr(
+    instr_idx: usize,
+    chunk: &Chunk,
+    global_names: &[String],
+    labels: &[usize],
+) -> String {
+    use Instr::*;
+    let instr = &chunk.instructions[instr_idx];
+    let local = |slot: &usize| match chunk.local_at(*slot, instr_idx) {
+        Some(local) => format!("{:<4} {}", slot, local.name),
+        None => slot.to_string(),
+    };
+    let global = |idx: &usize| match global_names.get(*idx) {
+        Some(name) => format!("{:<4} {}", idx, name),
+        None => format!("{:<4} <unknown global>", idx),
+    };
+    let label = |idx: &usize| match labels.iter().position(|target| target == idx) {
+        Some(label) => format!("L{}", label),
+        None => format!("{}", idx),
+    };
+    let (mnemonic, operand) = match instr {
+        Const(idx) => (
+            "Const",
+            format!(
+                "{:<4} {}",
+                idx,
+                match (chunk.constants.get(*idx), chunk.constant_kinds.get(*idx)) {
+                    (Some(value), Some(kind)) => format_value(value, *kind),
+                    _ => String::from("<invalid constant>"),
+                }
+            ),
+        ),
+        Pop => ("Pop", String::new()),
+        Dup => ("Dup", String::new()),
+        LoadLocal(idx) => ("LoadLocal", local(idx)),
+        StoreLocal(idx) => ("StoreLocal", local(idx)),
+        LoadGlobal(idx) => ("LoadGlobal", global(idx)),
+        StoreGlobal(idx) => ("StoreGlobal", global(idx)),
+        Call(args) => ("Call", args.to_string()),
+        Ret(args) => ("Ret", args.to_string()),
+        Input(pseudo_type) => ("Input", format!("{:?}", pseudo_type)),
+        Output(pseudo_type) => ("Output", format!("{:?}", pseudo_type)),
+        OutputLn => ("OutputLn", String::new()),
+        OutputSpace => ("OutputSpace", String::new()),
+        Concat => ("Concat", String::new()),
+        CharToString => ("CharToString", String::new()),
+        Add(pseudo_type) => ("Add", format!("{:?}", pseudo_type)),
+        Sub(pseudo_type) => ("Sub", format!("{:?}", pseudo_type)),
+        Mul(pseudo_type) => ("Mul", format!("{:?}", pseudo_type)),
+        Div(pseudo_type) => ("Div", format!("{:?}", pseudo_type)),
+        Mod(pseudo_type) => ("Mod", format!("{:?}", pseudo_type)),
+        Gt(pseudo_type) => ("Gt", format!("{:?}", pseudo_type)),
+        GtEq(pseudo_type) => ("GtEq", format!("{:?}", pseudo_type)),
+        Eq(pseudo_type) => ("Eq", format!("{:?}", pseudo_type)),
+        Neg(pseudo_type) => ("Neg", format!("{:?}", pseudo_type)),
+        Not => ("Not", String::new()),
+        True => ("True", String::new()),
+        False => ("False", String::new()),
+        Null => ("Null", String::new()),
+        JumpFalse(idx) => ("JumpFalse", label(idx)),
+        JumpTrue(idx) => ("JumpTrue", label(idx)),
+        Jump(idx) => ("Jump", label(idx)),
+        AddrLocal(idx) => ("AddrLocal", local(idx)),
+        AddrGlobal(idx) => ("AddrGlobal", global(idx)),
+        Alloc(pseudo_type) => ("Alloc", format!("{:?}", pseudo_type)),
+        Deref => ("Deref", String::new()),
+        StoreDeref => ("StoreDeref", String::new()),
+        EmptySet(element_type) => ("EmptySet", format!("{:?}", element_type)),
+        SetInsert => ("SetInsert", String::new()),
+        In => ("In", String::new()),
+        Instantiate(idx) => (
+            "Instantiate",
+            format!(
+                "{:<4} {}",
+                idx,
+                match (chunk.constants.get(*idx), chunk.constant_kinds.get(*idx)) {
+                    (Some(value), Some(kind)) => format_value(value, *kind),
+                    _ => String::from("<invalid constant>"),
+                }
+            ),
+        ),
+        GetField(idx) => ("GetField", idx.to_string()),
+        SetField(idx) => ("SetField", idx.to_string()),
+        Method(slot) => ("Method", slot.to_string()),
+        Try(idx) => ("Try", label(idx)),
+        EndTry => ("EndTry", String::new()),
+    };
+    format!("{:<12} {}", mnemonic, operand).trim_end().to_string()
+}
+
+/// Sorted, de-duplicated jump targets of `chunk`; a target's position is its label number.
+pub fn jump_labels(chunk: &Chunk) -> Vec<usize> {
+    let mut labels: Vec<usize> = chunk
+        .instructions
+        .iter()
+        .filter_map(|instr| match instr {
+            Instr::Jump(idx) | Instr::JumpFalse(idx) | Instr::JumpTrue(idx) | Instr::Try(idx) => {
+                Some(*idx)
+            }
+            _ => None,
+        })
+        .collect();
+    labels.sort();
+    labels.dedup();
+    labels
+}
+
+pub fn format_value(value: &Value, kind: ValueKind) -> String {
+    unsafe {
+        match kind {
+            ValueKind::Integer => value.integer.to_string(),
+            ValueKind::Real => format!("{:?}", value.real),
+            ValueKind::Boolean => String::from(if value.boolean { "TRUE" } else { "FALSE" }),
+            ValueKind::Char => format!("'{}'", value.char),
+            ValueKind::Obj if value.obj.is_null() => String::from("NULL"),
+            ValueKind::Obj => match (*value.obj).kind {
+                ObjKind::String => format!("\"{}\"", (*(value.obj as *mut ObjString)).string),
+                ObjKind::Fn => format!("<fn {}>", function_name(&*(value.obj as *mut ObjFn))),
+                ObjKind::Set => as_set(value.obj).to_string(),
+                ObjKind::Class => format!("<class {}>", as_class(value.obj).class_type.name),
+                ObjKind::Instance => format!("<{} object>", (*as_instance(value.obj).class).class_type.name),
+            },
+            ValueKind::Pointer => format_pointer(value),
+        }
+    }
+}
+
+/// Formats the value of a variable of type `pseudo_type`, naming values of enumerated
+/// types, which are stored as their position.
+pub fn format_variable(value: &Value, pseudo_type: Type) -> String {
+    match pseudo_type {
+        Type::Enum(enum_type) => enum_value(value, enum_type),
+        _ => format_value(value, pseudo_type.into()),
+    }
+}
+
+/// The name of the value of `enum_type` stored in `value`, or its position if it has none.
+pub fn enum_value(value: &Value, enum_type: &EnumType) -> String {
+    let position = unsafe { value.integer };
+    enum_type
+        .value(position)
+        .map_or_else(|| position.to_string(), String::from)
+}
+
+pub fn function_name(function: &ObjFn) -> &str {
+    match function.name.is_null() {
+        true => "<script>",
+        false => unsafe { as_rs_string!(function.name) },
+    }
+}
+
+pub fn as_function(value: &Value, kind: ValueKind) -> Option<*const ObjFn> {
+    if kind != ValueKind::Obj || unsafe { value.obj.is_null() } {
+        return None;
+    }
+    unsafe {
+        match (*value.obj).kind {
+            ObjKind::Fn => Some(value.obj as *const ObjFn),
+            _ => None,
+        }
+    }
+}
@@ -5,18 +5,23 @@ pub enum Instr {
     /// Push constant to the stack with an index of the value
     Const(usize),
     Pop,
+    /// Push a copy of the value on top of the stack.
+    Dup,
     LoadLocal(usize),
     StoreLocal(usize),
     LoadGlobal(usize),
     StoreGlobal(usize),
     Call(usize),
     Ret(usize),
-    Input,
+    /// Push a line typed in by the user, read as a value of the type.
+    Input(Type),
     Output(Type),
     OutputLn,
     OutputSpace,
     /// Concatenates two strings
     Concat,
+    /// Replace the CHAR on top of the stack with a STRING of just that character.
+    CharToString,
     Add(Type),
     Sub(Type),
     Mul(Type),
@@ -33,4 +38,38 @@ pub enum Instr {
     JumpFalse(usize),
     JumpTrue(usize),
     Jump(usize),
+    /// Push a pointer to a local variable.
+    AddrLocal(usize),
+    /// Push a pointer to a global.
+    AddrGlobal(usize),
+    /// Move the value of the type on top of the stack to the heap, replacing it with a
+    /// pointer to it.
+    Alloc(Type),
+    /// Replace the pointer on top of the stack with the value it points to.
+    Deref,
+    /// Store the value on top of the stack through the pointer below it, leaving the value.
+    StoreDeref,
+    /// Push a new, empty set of values of the type.
+    EmptySet(Type),
+    /// Add the value on top of the stack to the set below it, leaving the set. Only used
+    /// on a set just made by `EmptySet`, which nothing else can see yet.
+    SetInsert,
+    /// Replace a value and the set on top of it with whether the set holds the value.
+    In,
+    /// Push a new object of the CLASS held by the constant, its attributes holding the
+    /// default values of their types.
+    Instantiate(usize),
+    /// Replace the object on top of the stack with the value of its attribute.
+    GetField(usize),
+    /// Store the value on top of the stack in the attribute of the object below it, leaving
+    /// the value.
+    SetField(usize),
+    /// Push the version of the method in the slot for the object on top of the stack below
+    /// the object, ready to be called with it as the first argument.
+    Method(usize),
+    /// Start a TRY block: until the matching `EndTry`, a runtime error jumps to the
+    /// instruction instead of stopping the program.
+    Try(usize),
+    /// End the innermost TRY block of the frame.
+    EndTry,
 }
@@ -1,32 +1,145 @@
 pub mod chunk;
+pub mod class;
+pub mod disassembler;
 pub mod instr;
 pub mod obj;
+pub mod pointer;
+pub mod serialize;
+pub mod set;
 pub mod value;
+pub mod verifier;
+#[cfg(test)] mod tests;
 
 use self::{
     chunk::Chunk,
-    obj::{free_object, Obj, ObjFn},
+    class::{ObjClass, ObjInstance},
+    disassembler::function_name,
+    obj::{allocate_instance, allocate_set, as_instance, as_set, free_object, Obj, ObjFn, ObjKind},
+    pointer::Pointee,
+    set::ObjSet,
+    value::{scalar_to_str, ValueKind},
+    verifier::VerifyError,
 };
 use crate::{
     as_rs_string,
-    ir::hlir::Type,
+    error::RuntimeError,
+    ir::hlir::{EnumType, Type},
     vm::{
         instr::Instr::*,
         obj::{allocate_string, store_function},
         value::Value,
     },
 };
+use std::{io::Write, time::Instant};
 
 struct CallFrame {
+    /// Tells frames apart, for pointers to local variables.
+    id: u32,
     function: *mut ObjFn,
     ret_instr_idx: usize,
     window_start_idx: usize,
+    /// TRY blocks the frame is in, innermost last.
+    handlers: Vec<Handler>,
+}
+
+/// Where a runtime error in a TRY block continues.
+struct Handler {
+    /// The first instruction of the EXCEPT block.
+    instr_idx: usize,
+    /// Height of the stack when the TRY block started, which the EXCEPT block starts with.
+    stack_len: usize,
+}
+
+/// A checked `Pointee`.
+#[derive(Clone, Copy)]
+enum Target {
+    Stack(usize),
+    Global(usize),
+    Heap(usize),
+}
+
+/// Observer called by the dispatch loop before every instruction is executed.
+pub trait Hook {
+    fn before_instr(&mut self, vm: &Vm, instr_idx: usize);
+
+    /// Whether to stop the program, asked after each `before_instr`.
+    fn stopped(&self) -> bool {
+        false
+    }
+}
+
+/// A view of one active call frame, as seen from a `Hook`.
+pub struct Frame<'a> {
+    pub function: &'a ObjFn,
+    /// Index of the instruction the frame is currently executing.
+    pub instr_idx: usize,
+    /// The frame's parameters, locals and temporaries, indexed by slot.
+    pub slots: &'a [Value],
+}
+
+/// Most calls that can be nested before the program stops with a stack overflow.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+/// Most values the stack can hold before the program stops with a stack overflow.
+pub const DEFAULT_MAX_STACK_LEN: usize = 1 << 20;
+/// Frames of the call stack shown by a stack overflow error.
+const SHOWN_FRAMES: usize = 5;
+/// Instructions executed between checks of `VmLimits::deadline`.
+const DEADLINE_INTERVAL: u64 = 1024;
+
+/// Bounds on a run, for running programs that can't be trusted to finish, such as when
+/// grading them. A program that goes over one stops with a `RuntimeError` naming it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VmLimits {
+    /// Most instructions that can be executed.
+    pub max_instructions: Option<u64>,
+    /// When to stop, checked every few instructions.
+    pub deadline: Option<Instant>,
+    /// Most bytes OUTPUT can print.
+    pub max_output_len: Option<usize>,
+    /// Most objects, such as STRINGs and instances, and values made with NEW that can be
+    /// allocated while running.
+    pub max_heap_objects: Option<usize>,
+}
+
+/// The bound of `VmLimits` that stopped a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Instructions,
+    Deadline,
+    Output,
+    HeapObjects,
+}
+
+/// Why `Vm::execute` didn't run a program to its end.
+#[derive(Debug)]
+pub enum ExecuteError {
+    /// The verifier rejected the bytecode, so none of it was run.
+    Invalid(VerifyError),
+    Runtime(RuntimeError),
+    /// The `Hook` stopped the program, such as when the debugger is quit.
+    Stopped,
 }
 
 pub struct Vm {
     stack: Vec<Value>,
     frames: Vec<CallFrame>,
     objects: *mut Obj,
+    /// Objects allocated so far, including those made before running such as constants.
+    objects_len: usize,
+    /// Values of the global slots, copied from the script's `globals` when it starts.
+    globals: Vec<Value>,
+    /// Values allocated with NEW. Like objects, they live until the program ends.
+    heap: Vec<Value>,
+    next_frame_id: u32,
+    /// Let INTEGER arithmetic wrap around on overflow instead of stopping with an error.
+    wrapping: bool,
+    max_call_depth: usize,
+    max_stack_len: usize,
+    limits: VmLimits,
+    /// Bytes printed by OUTPUT so far.
+    output_len: usize,
+    /// Where OUTPUT prints to, stdout unless set with `set_output`.
+    output: Box<dyn Write>,
 }
 
 impl Vm {
@@ -35,11 +148,79 @@ impl Vm {
             stack: Vec::new(),
             frames: Vec::new(),
             objects: std::ptr::null_mut(),
+            objects_len: 0,
+            globals: Vec::new(),
+            heap: Vec::new(),
+            next_frame_id: 0,
+            wrapping: false,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_stack_len: DEFAULT_MAX_STACK_LEN,
+            limits: VmLimits::default(),
+            output_len: 0,
+            output: Box::new(std::io::stdout()),
         }
     }
 
-    pub fn free_objects(&self) {
-        let mut obj = self.objects;
+    pub fn set_wrapping(&mut self, wrapping: bool) {
+        self.wrapping = wrapping;
+    }
+
+    /// Stop with a stack overflow when a call would nest more than `depth` calls.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Stop with a stack overflow when a call is made with more than `len` values on the
+    /// stack. Only calls grow the stack without bound, so it is checked there.
+    pub fn set_max_stack_len(&mut self, len: usize) {
+        self.max_stack_len = len;
+    }
+
+    pub fn set_limits(&mut self, limits: VmLimits) {
+        self.limits = limits;
+    }
+
+    /// Print what OUTPUT prints to `output` instead of stdout.
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+
+    /// The value of a variable of `pseudo_type` before it is assigned, also used for
+    /// values allocated with NEW and the attributes of new objects.
+    pub fn default_value(&mut self, pseudo_type: Type) -> (Value, ValueKind) {
+        match pseudo_type {
+            // The first value of an enumerated type.
+            Type::Integer | Type::Enum(_) => (Value { integer: 0 }, ValueKind::Integer),
+            Type::Real => (Value { real: 0.0 }, ValueKind::Real),
+            Type::Boolean => (Value { boolean: false }, ValueKind::Boolean),
+            Type::Char => (Value { char: ' ' }, ValueKind::Char),
+            Type::String => (
+                Value {
+                    obj: allocate_string(self, String::new()),
+                },
+                ValueKind::Obj,
+            ),
+            Type::Pointer(_) | Type::Null => (Pointee::Null.encode(), ValueKind::Pointer),
+            Type::Set(element_type) => (
+                Value {
+                    obj: allocate_set(self, ObjSet::new(*element_type)),
+                },
+                ValueKind::Obj,
+            ),
+            // An object that was never made with NEW.
+            Type::Class(_) => (
+                Value {
+                    obj: std::ptr::null_mut(),
+                },
+                ValueKind::Obj,
+            ),
+        }
+    }
+
+    /// Frees every object allocated so far. The list is emptied, so calling it again, such
+    /// as after `execute` has stopped with an error, frees nothing twice.
+    pub fn free_objects(&mut self) {
+        let mut obj = std::mem::replace(&mut self.objects, std::ptr::null_mut());
         while !obj.is_null() {
             unsafe {
                 let next = (*obj).next;
@@ -49,17 +230,138 @@ impl Vm {
         }
     }
 
-    pub fn execute(&mut self, script: ObjFn) {
+    pub fn execute(&mut self, script: ObjFn) -> Result<(), ExecuteError> {
+        self.run(script, None)
+    }
+
+    pub fn execute_with_hook(
+        &mut self,
+        script: ObjFn,
+        hook: &mut dyn Hook,
+    ) -> Result<(), ExecuteError> {
+        self.run(script, Some(hook))
+    }
+
+    /// Active call frames, outermost (the script) first. `instr_idx` is the index of the
+    /// instruction about to be executed in the innermost frame.
+    pub fn frames(&self, instr_idx: usize) -> Vec<Frame<'_>> {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(idx, frame)| {
+                let (instr_idx, slots_end) = match self.frames.get(idx + 1) {
+                    Some(callee) => (callee.ret_instr_idx, callee.window_start_idx - 1),
+                    None => (instr_idx, self.stack.len()),
+                };
+                Frame {
+                    function: unsafe { &*frame.function },
+                    instr_idx,
+                    slots: &self.stack[frame.window_start_idx..slots_end],
+                }
+            })
+            .collect()
+    }
+
+    /// Values of the global slots, named by the script chunk's `global_names`.
+    pub fn globals(&self) -> &[Value] {
+        &self.globals
+    }
+
+    fn run(&mut self, script: ObjFn, mut hook: Option<&mut dyn Hook>) -> Result<(), ExecuteError> {
+        if let Err(error) = verifier::verify(&script) {
+            self.free_objects();
+            return Err(ExecuteError::Invalid(error));
+        }
+
+        // Stops execution with an error at the line of the current instruction.
+        macro_rules! fatal_error {
+            ($instr_idx:expr, $($msg:tt)*) => {
+                {
+                    let line = self.get_current_chunk().lines[$instr_idx];
+                    self.free_objects();
+                    return Err(ExecuteError::Runtime(RuntimeError::new(format!($($msg)*), line)));
+                }
+            };
+        }
+
+        // Stops execution like `fatal_error` because the program went over `$limit`.
+        macro_rules! limit_error {
+            ($instr_idx:expr, $limit:expr, $($msg:tt)*) => {
+                {
+                    let line = self.get_current_chunk().lines[$instr_idx];
+                    self.free_objects();
+                    let mut error = RuntimeError::new(format!($($msg)*), line);
+                    error.limit = Some($limit);
+                    return Err(ExecuteError::Runtime(error));
+                }
+            };
+        }
+
+        // Prints `$text` for OUTPUT unless that goes over the output limit.
+        macro_rules! output {
+            ($instr_idx:ident, $text:expr) => {
+                {
+                    let text = $text;
+                    self.output_len += text.len();
+                    match self.limits.max_output_len {
+                        Some(max) if self.output_len > max => limit_error!(
+                            $instr_idx,
+                            Limit::Output,
+                            "Output limit reached, the program printed more than {} bytes.",
+                            max
+                        ),
+                        _ => write!(self.output, "{}", text).expect("Unable to write OUTPUT."),
+                    }
+                }
+            };
+        }
+
+        // Continues in the EXCEPT block of the innermost TRY block, or stops execution like
+        // `fatal_error` when there is none.
+        macro_rules! runtime_error {
+            ($instr_idx:ident, $($msg:tt)*) => {
+                {
+                    match self.catch() {
+                        Some(Ok(handler_idx)) => {
+                            $instr_idx = handler_idx;
+                            continue;
+                        }
+                        Some(Err(msg)) => fatal_error!($instr_idx, "{}", msg),
+                        None => fatal_error!($instr_idx, $($msg)*),
+                    }
+                }
+            };
+        }
+
+        // INTEGER overflow is an error unless the VM wraps around.
+        macro_rules! checked_integer {
+            ($instr_idx:ident, $overflowing:expr, $($msg:tt)*) => {
+                match $overflowing {
+                    (result, false) => result,
+                    (result, true) if self.wrapping => result,
+                    _ => runtime_error!($instr_idx, $($msg)*),
+                }
+            };
+        }
+
         macro_rules! binary_op {
-            ($op:tt, $type:expr) => {
+            ($instr_idx:ident, $op:tt, $overflowing:ident, $type:expr) => {
                 {
                     let b = self.stack.pop().unwrap();
                     let a = self.stack.pop().unwrap();
                     unsafe {
                         match $type {
-                            Type::Integer => self.stack.push(Value {
-                                integer: a.integer $op b.integer,
-                            }),
+                            Type::Integer | Type::Enum(_) => {
+                                let integer = checked_integer!(
+                                    $instr_idx,
+                                    a.integer.$overflowing(b.integer),
+                                    "INTEGER overflow, {} {} {} is out of range.",
+                                    a.integer,
+                                    stringify!($op),
+                                    b.integer
+                                );
+                                self.stack.push(Value { integer })
+                            }
                             Type::Real => self.stack.push(Value {
                                 real: a.real $op b.real,
                             }),
@@ -77,7 +379,7 @@ impl Vm {
                     let a = self.stack.pop().unwrap();
                     unsafe {
                         match $type {
-                            Type::Integer => self.stack.push(Value {
+                            Type::Integer | Type::Enum(_) => self.stack.push(Value {
                                 boolean: a.integer $op b.integer,
                             }),
                             Type::Real => self.stack.push(Value {
@@ -91,15 +393,55 @@ impl Vm {
             };
         }
 
+        self.globals = script.chunk.globals.clone();
         let script = store_function(self, script);
+        let id = self.frame_id();
         self.frames.push(CallFrame {
+            id,
             function: script as *mut ObjFn,
             ret_instr_idx: 0,
             window_start_idx: 0,
+            handlers: Vec::new(),
         });
 
+        let objects_at_start = self.objects_len;
+        let mut executed: u64 = 0;
         let mut instr_idx = 0;
         while instr_idx < self.get_current_chunk().instructions.len() {
+            if let Some(max) = self.limits.max_instructions {
+                if executed >= max {
+                    limit_error!(
+                        instr_idx,
+                        Limit::Instructions,
+                        "Instruction limit reached, the program ran more than {} instructions.",
+                        max
+                    );
+                }
+            }
+            if let Some(deadline) = self.limits.deadline {
+                if executed.is_multiple_of(DEADLINE_INTERVAL) && Instant::now() >= deadline {
+                    limit_error!(instr_idx, Limit::Deadline, "Time limit reached.");
+                }
+            }
+            if let Some(max) = self.limits.max_heap_objects {
+                if self.objects_len - objects_at_start + self.heap.len() > max {
+                    limit_error!(
+                        instr_idx,
+                        Limit::HeapObjects,
+                        "Heap limit reached, the program allocated more than {} objects.",
+                        max
+                    );
+                }
+            }
+            executed += 1;
+
+            if let Some(hook) = &mut hook {
+                hook.before_instr(self, instr_idx);
+                if hook.stopped() {
+                    self.free_objects();
+                    return Err(ExecuteError::Stopped);
+                }
+            }
             let mut instr_inc = 1;
             match self.get_current_chunk().instructions[instr_idx] {
                 Const(index) => {
@@ -109,6 +451,10 @@ impl Vm {
                 Pop => {
                     self.stack.pop();
                 }
+                Dup => {
+                    let value = *self.stack.last().unwrap();
+                    self.stack.push(value);
+                }
                 LoadLocal(idx) => unsafe {
                     let window_start_idx = self.frames.last().unwrap().window_start_idx;
                     let value = self.stack.get_unchecked(window_start_idx + idx);
@@ -119,18 +465,39 @@ impl Vm {
                     self.stack[window_start_idx + idx] = self.stack.last().unwrap().clone();
                 }
                 LoadGlobal(idx) => unsafe {
-                    let value = (*(script as *mut ObjFn)).chunk.constants.get_unchecked(idx);
+                    let value = self.globals.get_unchecked(idx);
                     self.stack.push(value.clone());
                 },
-                StoreGlobal(idx) => unsafe {
+                StoreGlobal(idx) => {
                     let value = self.stack.last().unwrap();
-                    (*(script as *mut ObjFn)).chunk.constants[idx] = *value;
-                },
+                    self.globals[idx] = *value;
+                }
                 Call(args_amount) => unsafe {
                     let function = self
                         .stack
                         .get_unchecked(self.stack.len() - args_amount - 1)
                         .obj as *mut ObjFn;
+                    // Methods are looked up at runtime, so the verifier can't check them.
+                    if function.is_null() || (*function).arity != args_amount {
+                        fatal_error!(instr_idx, "invalid bytecode: wrong number of arguments in call.");
+                    }
+                    // The message is made before `fatal_error` frees the function names.
+                    let overflow = if self.frames.len() > self.max_call_depth {
+                        Some(format!("would nest more than {} calls", self.max_call_depth))
+                    } else if self.stack.len() > self.max_stack_len {
+                        Some(format!("with more than {} values on the stack", self.max_stack_len))
+                    } else {
+                        None
+                    };
+                    if let Some(overflow) = overflow {
+                        let msg = format!(
+                            "Stack overflow, calling {} {}.\n{}",
+                            function_name(&*function),
+                            overflow,
+                            self.call_stack(instr_idx)
+                        );
+                        fatal_error!(instr_idx, "{}", msg);
+                    }
 
                     // println!(
                     //     "{}: {:?}",
@@ -140,50 +507,73 @@ impl Vm {
 
                     // self.stack.iter().for_each(|value| println!("{}", value.integer));
 
+                    let id = self.frame_id();
                     self.frames.push(CallFrame {
+                        id,
                         function,
                         ret_instr_idx: instr_idx,
                         window_start_idx: self.stack.len() - args_amount,
+                        handlers: Vec::new(),
                     });
                     instr_idx = 0;
                     instr_inc = 0;
                 },
-                Ret(args_amount) => {
+                Ret(_) => {
                     let return_value = self.stack.pop().unwrap();
+                    let call_frame = self.frames.pop().unwrap();
 
-                    // pop args and function reference off stack.
-                    for _ in 0..(args_amount + 1) {
-                        self.stack.pop();
-                    }
+                    // pop locals, args and function reference off stack.
+                    self.stack.truncate(call_frame.window_start_idx - 1);
 
-                    let call_frame = self.frames.pop().unwrap();
                     instr_idx = call_frame.ret_instr_idx;
                     self.stack.push(return_value);
                 }
-                Input => {
+                Input(pseudo_type) => {
                     let mut input = String::new();
                     match std::io::stdin().read_line(&mut input) {
                         Ok(_) => (),
                         Err(error) => println!("error reading user input: {error}"),
                     };
                     input = String::from(input.trim_end());
-                    let input = allocate_string(self, input);
-                    self.stack.push(Value { obj: input });
+                    let value = match pseudo_type {
+                        Type::String => Value {
+                            obj: allocate_string(self, input),
+                        },
+                        _ => match read_value(&input, pseudo_type) {
+                            Ok(value) => value,
+                            Err(msg) => runtime_error!(instr_idx, "{}", msg),
+                        },
+                    };
+                    self.stack.push(value);
                 }
                 Output(pseudo_type) => unsafe {
                     let value = self.stack.pop().unwrap();
-                    match pseudo_type {
-                        Type::Integer => print!("{}", value.integer),
-                        Type::Real => print!("{}", value.real),
-                        Type::Char => print!("{}", value.char),
-                        Type::Boolean => {
-                            print!("{}", if value.boolean { "TRUE" } else { "FALSE" })
+                    let text = match pseudo_type {
+                        Type::Integer | Type::Real | Type::Char | Type::Boolean => {
+                            scalar_to_str(value, pseudo_type)
                         }
-                        Type::String => print!("{}", as_rs_string!(value.obj)),
-                    }
+                        Type::String => as_rs_string!(value.obj).clone(),
+                        Type::Pointer(_) | Type::Null => unreachable!(),
+                        Type::Enum(enum_type) => match enum_type.value(value.integer) {
+                            Some(name) => name.to_string(),
+                            None => runtime_error!(
+                                instr_idx,
+                                "{}",
+                                out_of_range(enum_type, value.integer)
+                            ),
+                        },
+                        Type::Set(_) => as_set(value.obj).to_string(),
+                        Type::Class(_) => unreachable!(),
+                    };
+                    output!(instr_idx, text);
                 },
-                OutputLn => println!(),
-                OutputSpace => print!(" "),
+                OutputLn => output!(instr_idx, "\n"),
+                OutputSpace => output!(instr_idx, " "),
+                CharToString => {
+                    let value = self.stack.pop().unwrap();
+                    let text = allocate_string(self, scalar_to_str(value, Type::Char));
+                    self.stack.push(Value { obj: text });
+                }
                 Concat => unsafe {
                     let b = as_rs_string!(self.stack.pop().unwrap().obj);
                     let a = as_rs_string!(self.stack.pop().unwrap().obj);
@@ -193,14 +583,29 @@ impl Vm {
                     let result = allocate_string(self, result);
                     self.stack.push(Value { obj: result });
                 },
-                Add(pseudo_type) => binary_op!(+, pseudo_type),
-                Sub(pseudo_type) => binary_op!(-, pseudo_type),
-                Mul(pseudo_type) => binary_op!(*, pseudo_type),
+                Add(Type::Set(_)) => self.set_op(ObjSet::union),
+                Sub(Type::Set(_)) => self.set_op(ObjSet::difference),
+                Mul(Type::Set(_)) => self.set_op(ObjSet::intersection),
+                Add(pseudo_type) => binary_op!(instr_idx, +, overflowing_add, pseudo_type),
+                Sub(pseudo_type) => binary_op!(instr_idx, -, overflowing_sub, pseudo_type),
+                Mul(pseudo_type) => binary_op!(instr_idx, *, overflowing_mul, pseudo_type),
                 Div(pseudo_type) => unsafe {
                     let b = self.stack.pop().unwrap();
                     let a = self.stack.pop().unwrap();
                     match pseudo_type {
-                        Type::Integer => self.stack.push(Value {integer: a.integer / b.integer}),
+                        Type::Integer => {
+                            if b.integer == 0 {
+                                runtime_error!(instr_idx, "Division by zero, {} DIV 0.", a.integer);
+                            }
+                            let integer = checked_integer!(
+                                instr_idx,
+                                a.integer.overflowing_div(b.integer),
+                                "INTEGER overflow, {} DIV {} is out of range.",
+                                a.integer,
+                                b.integer
+                            );
+                            self.stack.push(Value { integer })
+                        }
                         Type::Real => self.stack.push(Value {real: a.real / b.real}),
                         _ => unreachable!(),
                     };
@@ -209,7 +614,14 @@ impl Vm {
                     let b = self.stack.pop().unwrap();
                     let a = self.stack.pop().unwrap();
                     match pseudo_type {
-                        Type::Integer => self.stack.push(Value {integer: a.integer % b.integer}),
+                        Type::Integer => {
+                            if b.integer == 0 {
+                                runtime_error!(instr_idx, "Division by zero, {} MOD 0.", a.integer);
+                            }
+                            // Only `i64::MIN MOD -1` overflows, and its remainder is 0 anyway.
+                            let integer = a.integer.wrapping_rem(b.integer);
+                            self.stack.push(Value { integer })
+                        }
                         Type::Real => self.stack.push(Value {real: a.real % b.real}),
                         _ => unreachable!(),
                     };
@@ -220,7 +632,7 @@ impl Vm {
                     let b = self.stack.pop().unwrap();
                     let a = self.stack.pop().unwrap();
                     let equality = match pseudo_type {
-                        Type::Integer => a.integer == b.integer,
+                        Type::Integer | Type::Enum(_) => a.integer == b.integer,
                         Type::Real => a.real == b.real,
                         Type::Char => a.char == b.char,
                         Type::Boolean => a.boolean == b.boolean,
@@ -229,6 +641,10 @@ impl Vm {
                             let b = as_rs_string!(b.obj);
                             a == b
                         }
+                        Type::Pointer(_) | Type::Null => a.integer == b.integer,
+                        Type::Set(_) => as_set(a.obj).elements == as_set(b.obj).elements,
+                        // The same object, not objects holding the same values.
+                        Type::Class(_) => a.obj == b.obj,
                     };
                     self.stack.push(Value { boolean: equality });
                 },
@@ -236,9 +652,15 @@ impl Vm {
                     let value = self.stack.pop().unwrap();
                     unsafe {
                         match pseudo_type {
-                            Type::Integer => self.stack.push(Value {
-                                integer: -value.integer,
-                            }),
+                            Type::Integer => {
+                                let integer = checked_integer!(
+                                    instr_idx,
+                                    value.integer.overflowing_neg(),
+                                    "INTEGER overflow, -({}) is out of range.",
+                                    value.integer
+                                );
+                                self.stack.push(Value { integer })
+                            }
                             Type::Real => self.stack.push(Value { real: -value.real }),
                             _ => unreachable!(),
                         };
@@ -253,23 +675,332 @@ impl Vm {
                 Null => self.stack.push(Value { integer: 0 }),
                 JumpFalse(idx) => unsafe {
                     if !self.stack.last().unwrap().boolean {
-                        instr_idx = idx - 1;
+                        instr_idx = idx;
+                        instr_inc = 0;
                     }
                 },
                 JumpTrue(idx) => unsafe {
                     if self.stack.last().unwrap().boolean {
-                        instr_idx = idx - 1;
+                        instr_idx = idx;
+                        instr_inc = 0;
                     }
                 },
-                Jump(idx) => instr_idx = idx - 1,
+                Jump(idx) => {
+                    instr_idx = idx;
+                    instr_inc = 0;
+                }
+                AddrLocal(idx) => {
+                    let frame = self.frames.last().unwrap();
+                    // The verifier checks that the slot holds a variable.
+                    let local = self
+                        .get_current_chunk()
+                        .local_idx_at(idx, instr_idx)
+                        .unwrap();
+                    let pointer = Pointee::Local {
+                        frame_id: frame.id,
+                        local,
+                    };
+                    self.stack.push(pointer.encode());
+                }
+                AddrGlobal(idx) => self.stack.push(Pointee::Global(idx).encode()),
+                Alloc(_) => {
+                    let value = self.stack.pop().unwrap();
+                    self.heap.push(value);
+                    let pointer = Pointee::Heap(self.heap.len() - 1);
+                    self.stack.push(pointer.encode());
+                }
+                Deref => {
+                    let pointer = self.stack.pop().unwrap();
+                    match self.resolve(pointer, instr_idx) {
+                        Ok(target) => {
+                            let value = *self.target_mut(target);
+                            self.stack.push(value);
+                        }
+                        Err(msg) => runtime_error!(instr_idx, "{}", msg),
+                    }
+                }
+                StoreDeref => {
+                    let value = self.stack.pop().unwrap();
+                    let pointer = self.stack.pop().unwrap();
+                    match self.resolve(pointer, instr_idx) {
+                        Ok(target) => *self.target_mut(target) = value,
+                        Err(msg) => runtime_error!(instr_idx, "{}", msg),
+                    }
+                    self.stack.push(value);
+                }
+                EmptySet(element_type) => {
+                    let set = allocate_set(self, ObjSet::new(element_type));
+                    self.stack.push(Value { obj: set });
+                }
+                SetInsert => unsafe {
+                    let value = self.stack.pop().unwrap();
+                    let set = as_set(self.stack.last().unwrap().obj);
+                    if !set.insert(value) {
+                        match set.element_type {
+                            Type::Enum(enum_type) => runtime_error!(
+                                instr_idx,
+                                "{}",
+                                out_of_range(enum_type, value.integer)
+                            ),
+                            _ => unreachable!(),
+                        }
+                    }
+                },
+                In => unsafe {
+                    let set = as_set(self.stack.pop().unwrap().obj);
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(Value {
+                        boolean: set.contains(value),
+                    });
+                },
+                Instantiate(index) => unsafe {
+                    let class = self.get_current_chunk().constants[index].obj as *mut ObjClass;
+                    let fields = (*class)
+                        .fields
+                        .iter()
+                        .map(|field| self.default_value(*field).0)
+                        .collect();
+                    let instance = allocate_instance(self, ObjInstance::new(class, fields));
+                    self.stack.push(Value { obj: instance });
+                },
+                GetField(index) => {
+                    let object = self.stack.pop().unwrap();
+                    match instance(object) {
+                        Some(instance) => match instance.fields.get(index) {
+                            Some(value) => {
+                                let value = *value;
+                                self.stack.push(value);
+                            }
+                            None => fatal_error!(instr_idx, "invalid bytecode: attribute out of range."),
+                        },
+                        None => runtime_error!(instr_idx, "{}", NEVER_MADE),
+                    }
+                }
+                SetField(index) => {
+                    let value = self.stack.pop().unwrap();
+                    let object = self.stack.pop().unwrap();
+                    match instance(object) {
+                        Some(instance) => match instance.fields.get_mut(index) {
+                            Some(field) => *field = value,
+                            None => fatal_error!(instr_idx, "invalid bytecode: attribute out of range."),
+                        },
+                        None => runtime_error!(instr_idx, "{}", NEVER_MADE),
+                    }
+                    self.stack.push(value);
+                }
+                Method(slot) => unsafe {
+                    let object = self.stack.pop().unwrap();
+                    let global = match instance(object) {
+                        Some(instance) => {
+                            let class = &*instance.class;
+                            match class.methods.get(slot) {
+                                Some(global) => *global,
+                                None => fatal_error!(instr_idx, "invalid bytecode: method out of range."),
+                            }
+                        }
+                        None => runtime_error!(instr_idx, "{}", NEVER_MADE),
+                    };
+                    let method = self.globals[global];
+                    self.stack.push(method);
+                    self.stack.push(object);
+                },
+                Try(handler_idx) => {
+                    let stack_len = self.stack.len();
+                    let frame = self.frames.last_mut().unwrap();
+                    frame.handlers.push(Handler {
+                        instr_idx: handler_idx,
+                        stack_len,
+                    });
+                }
+                EndTry => {
+                    self.frames.last_mut().unwrap().handlers.pop();
+                }
             };
             instr_idx += instr_inc;
         }
         self.free_objects(); // todo: free objects for now after executing chunk. later, change
                              // this to deallocate objects when necessary.
+        Ok(())
+    }
+
+    /// Replaces the two sets on top of the stack with a new one made from them.
+    fn set_op(&mut self, op: fn(&ObjSet, &ObjSet) -> ObjSet) {
+        let (a, b) = unsafe {
+            let b = as_set(self.stack.pop().unwrap().obj);
+            let a = as_set(self.stack.pop().unwrap().obj);
+            (a, b)
+        };
+        let set = allocate_set(self, op(a, b));
+        self.stack.push(Value { obj: set });
+    }
+
+    /// Unwinds to the innermost TRY block, returning the start of its EXCEPT block, or
+    /// `None` when no TRY block is active.
+    fn catch(&mut self) -> Option<Result<usize, &'static str>> {
+        let depth = self
+            .frames
+            .iter()
+            .rposition(|frame| !frame.handlers.is_empty())?;
+        self.frames.truncate(depth + 1);
+        let handler = self.frames[depth].handlers.pop().unwrap();
+        if self.stack.len() < handler.stack_len {
+            return Some(Err("invalid bytecode: stack below the start of a TRY block."));
+        }
+        self.stack.truncate(handler.stack_len);
+        Some(Ok(handler.instr_idx))
+    }
+
+    /// The innermost frames of the call stack with the line each is on, runs of the same
+    /// call from recursion shown once.
+    fn call_stack(&self, instr_idx: usize) -> String {
+        let mut calls: Vec<(&str, usize, usize)> = Vec::new();
+        for frame in self.frames(instr_idx).iter().rev() {
+            let name = function_name(frame.function);
+            let line = frame.function.chunk.lines[frame.instr_idx] + 1;
+            match calls.last_mut() {
+                Some((last_name, last_line, times)) if *last_name == name && *last_line == line => {
+                    *times += 1;
+                }
+                _ => calls.push((name, line, 1)),
+            }
+        }
+
+        let mut text = String::from("Call stack, innermost first:");
+        for (name, line, times) in calls.iter().take(SHOWN_FRAMES) {
+            text.push_str(&format!("\n    {} on line {}", name, line));
+            if *times > 1 {
+                text.push_str(&format!(", {} times", times));
+            }
+        }
+        if calls.len() > SHOWN_FRAMES {
+            text.push_str(&format!("\n    and {} more", calls.len() - SHOWN_FRAMES));
+        }
+        text
+    }
+
+    fn frame_id(&mut self) -> u32 {
+        let id = self.next_frame_id;
+        self.next_frame_id = self.next_frame_id.wrapping_add(1);
+        id
+    }
+
+    /// Where the value `pointer` points to is stored, checking that it is still there.
+    /// `instr_idx` is the instruction being executed in the innermost frame.
+    fn resolve(&self, pointer: Value, instr_idx: usize) -> Result<Target, &'static str> {
+        let dangling = "Dereferencing a pointer to a variable that no longer exists.";
+        match Pointee::decode(pointer) {
+            Some(Pointee::Null) => Err("Dereferencing a NULL pointer."),
+            Some(Pointee::Local { frame_id, local }) => {
+                let idx = match self.frames.iter().position(|frame| frame.id == frame_id) {
+                    Some(idx) => idx,
+                    None => return Err(dangling),
+                };
+                let frame = &self.frames[idx];
+                // The variable may also have gone out of scope without the frame returning,
+                // and its slot been given to another.
+                let frame_instr_idx = match self.frames.get(idx + 1) {
+                    Some(callee) => callee.ret_instr_idx,
+                    None => instr_idx,
+                };
+                let chunk = unsafe { &(*frame.function).chunk };
+                match chunk.locals.get(local) {
+                    Some(info)
+                        if chunk.local_idx_at(info.slot, frame_instr_idx) == Some(local)
+                            && frame.window_start_idx + info.slot < self.stack.len() =>
+                    {
+                        Ok(Target::Stack(frame.window_start_idx + info.slot))
+                    }
+                    _ => Err(dangling),
+                }
+            }
+            Some(Pointee::Global(idx)) => {
+                let script = unsafe { &(*self.frames[0].function).chunk };
+                let is_function = script.global_kinds.get(idx) == Some(&ValueKind::Obj)
+                    && unsafe {
+                        let obj = self.globals[idx].obj;
+                        !obj.is_null() && matches!((*obj).kind, ObjKind::Fn)
+                    };
+                match idx < self.globals.len() && !is_function {
+                    true => Ok(Target::Global(idx)),
+                    false => Err("Dereferencing an invalid pointer."),
+                }
+            }
+            Some(Pointee::Heap(idx)) if idx < self.heap.len() => Ok(Target::Heap(idx)),
+            _ => Err("Dereferencing an invalid pointer."),
+        }
+    }
+
+    fn target_mut(&mut self, target: Target) -> &mut Value {
+        match target {
+            Target::Stack(slot) => &mut self.stack[slot],
+            Target::Global(idx) => &mut self.globals[idx],
+            Target::Heap(idx) => &mut self.heap[idx],
+        }
     }
 
     fn get_current_chunk(&self) -> &Chunk {
         unsafe { &(*self.frames.last().unwrap().function).chunk }
     }
 }
+
+const NEVER_MADE: &str = "Using an object that was never made with NEW.";
+
+/// The object `object` refers to, or `None` for one that was never made with NEW.
+fn instance<'a>(object: Value) -> Option<&'a mut ObjInstance> {
+    unsafe { (!object.obj.is_null()).then(|| as_instance(object.obj)) }
+}
+
+/// `input` read as a value of `pseudo_type`, which isn't a STRING, or why it can't be.
+fn read_value(input: &str, pseudo_type: Type) -> Result<Value, String> {
+    let input = input.trim();
+    let value = match pseudo_type {
+        Type::Integer => input.parse().ok().map(|integer| Value { integer }),
+        Type::Real => input.parse().ok().map(|real| Value { real }),
+        Type::Boolean => match input.to_uppercase().as_str() {
+            "TRUE" => Some(Value { boolean: true }),
+            "FALSE" => Some(Value { boolean: false }),
+            _ => None,
+        },
+        Type::Char => {
+            let mut chars = input.chars();
+            match (chars.next(), chars.next()) {
+                (Some(char), None) => Some(Value { char }),
+                _ => None,
+            }
+        }
+        Type::Enum(enum_type) => enum_type
+            .values
+            .iter()
+            .position(|value| value == input)
+            .map(|position| Value {
+                integer: position as i64,
+            }),
+        _ => unreachable!("only INPUT into these types is type checked"),
+    };
+    value.ok_or_else(|| {
+        let expected = match pseudo_type {
+            Type::Integer => String::from("an INTEGER"),
+            Type::Real => String::from("a REAL"),
+            Type::Boolean => String::from("TRUE or FALSE"),
+            Type::Char => String::from("a single CHAR"),
+            Type::Enum(enum_type) => format!("a value of {}", enum_type.name),
+            _ => unreachable!(),
+        };
+        format!("Expected {} as input, not \"{}\".", expected, input)
+    })
+}
+
+/// The error for a value of `enum_type` stepped past either end with `+` or `-`.
+fn out_of_range(enum_type: &EnumType, position: i64) -> String {
+    match position < 0 {
+        true => format!(
+            "There is no {} value before {}.",
+            enum_type.name, enum_type.values[0]
+        ),
+        false => format!(
+            "There is no {} value after {}.",
+            enum_type.name,
+            enum_type.values.last().unwrap()
+        ),
+    }
+}
@@ -1,4 +1,5 @@
-use super::{Vm, chunk::Chunk};
+use super::{Vm, chunk::Chunk, class::{ObjClass, ObjInstance}, set::ObjSet};
+use crate::ir::hlir::Type;
 
 #[derive(Debug)]
 #[repr(C)]
@@ -11,6 +12,9 @@ pub struct Obj {
 pub enum ObjKind {
     String,
     Fn,
+    Set,
+    Class,
+    Instance,
 }
 
 #[derive(Debug)]
@@ -25,6 +29,9 @@ pub struct ObjFn {
     pub obj: Obj,
     pub chunk: Chunk,
     pub name: *mut Obj, // ObjString name
+    pub arity: usize,
+    /// Type of the value `Ret` returns, NULL for PROCEDUREs and the script.
+    pub ret_type: Type,
 }
 
 #[inline]
@@ -36,6 +43,9 @@ pub fn free_object(obj: *mut Obj) {
                 // todo: make sure ObjFn name is being freed by GC
                 let _ = Box::from_raw(obj as *mut ObjFn); 
             },
+            ObjKind::Set => { let _ = Box::from_raw(obj as *mut ObjSet); },
+            ObjKind::Class => { let _ = Box::from_raw(obj as *mut ObjClass); },
+            ObjKind::Instance => { let _ = Box::from_raw(obj as *mut ObjInstance); },
         }
     }
 }
@@ -47,6 +57,21 @@ pub fn allocate_string(vm: &mut Vm, string: String) -> *mut Obj{
     add_obj_to_linked_list(vm, obj_string)
 }
 
+pub fn allocate_set(vm: &mut Vm, set: ObjSet) -> *mut Obj {
+    let set = Box::into_raw(Box::new(set)) as *mut Obj;
+    add_obj_to_linked_list(vm, set)
+}
+
+pub fn allocate_class(vm: &mut Vm, class: ObjClass) -> *mut Obj {
+    let class = Box::into_raw(Box::new(class)) as *mut Obj;
+    add_obj_to_linked_list(vm, class)
+}
+
+pub fn allocate_instance(vm: &mut Vm, instance: ObjInstance) -> *mut Obj {
+    let instance = Box::into_raw(Box::new(instance)) as *mut Obj;
+    add_obj_to_linked_list(vm, instance)
+}
+
 pub fn store_function(vm: &mut Vm, function: ObjFn) -> *mut Obj{
     let function = Box::into_raw(Box::new(function)) as *mut Obj;
     add_obj_to_linked_list(vm, function)
@@ -57,14 +82,42 @@ fn add_obj_to_linked_list(vm: &mut Vm, obj: *mut Obj) -> *mut Obj {
         (*obj).next = vm.objects;
     }
     vm.objects = obj;
+    vm.objects_len += 1;
     obj
 }
 
+/// # Safety
+///
+/// `obj` must point to a live `ObjString`, one whose kind is `ObjKind::String`.
 #[inline]
 pub unsafe fn as_rust_string(obj: *mut Obj) -> *mut String {
     &mut (*(obj as *mut ObjString)).string
 }
 
+/// # Safety
+///
+/// `obj` must point to a live `ObjSet`, one whose kind is `ObjKind::Set`.
+#[inline]
+pub unsafe fn as_set<'a>(obj: *mut Obj) -> &'a mut ObjSet {
+    &mut *(obj as *mut ObjSet)
+}
+
+/// # Safety
+///
+/// `obj` must point to a live `ObjClass`, one whose kind is `ObjKind::Class`.
+#[inline]
+pub unsafe fn as_class<'a>(obj: *mut Obj) -> &'a mut ObjClass {
+    &mut *(obj as *mut ObjClass)
+}
+
+/// # Safety
+///
+/// `obj` must point to a live `ObjInstance`, one whose kind is `ObjKind::Instance`.
+#[inline]
+pub unsafe fn as_instance<'a>(obj: *mut Obj) -> &'a mut ObjInstance {
+    &mut *(obj as *mut ObjInstance)
+}
+
 #[macro_export]
 macro_rules! as_rs_string {
     ($obj:expr) => {
@@ -0,0 +1,79 @@
+//! Pointers are INTEGER-sized values, tagged in their two lowest bits with what they
+//! point into: a variable on the stack, a global or a value allocated with NEW. NULL is 0.
+//!
+//! A pointer to a local variable records the call frame the variable belongs to and which
+//! of its function's variables it is, so following it after the frame has returned or the
+//! variable has gone out of scope is caught rather than reading whatever has since taken
+//! the variable's place on the stack.
+
+use super::value::Value;
+use std::fmt;
+
+const TAG_MASK: i64 = 0b11;
+const TAG_LOCAL: i64 = 1;
+const TAG_GLOBAL: i64 = 2;
+const TAG_HEAP: i64 = 3;
+
+/// Variable indices have to fit between the tag and the frame id.
+const LOCAL_BITS: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pointee {
+    Null,
+    /// A variable of the call frame with id `frame_id`, by its index in the `locals` of
+    /// the frame's function.
+    Local { frame_id: u32, local: usize },
+    Global(usize),
+    /// A value allocated with NEW, by its index in the VM's heap.
+    Heap(usize),
+}
+
+impl Pointee {
+    pub fn encode(self) -> Value {
+        let integer = match self {
+            Pointee::Null => 0,
+            Pointee::Local { frame_id, local } => {
+                assert!(local < 1 << LOCAL_BITS, "too many variables to point into");
+                ((frame_id as i64) << (LOCAL_BITS + 2)) | ((local as i64) << 2) | TAG_LOCAL
+            }
+            Pointee::Global(idx) => ((idx as i64) << 2) | TAG_GLOBAL,
+            Pointee::Heap(idx) => ((idx as i64) << 2) | TAG_HEAP,
+        };
+        Value { integer }
+    }
+
+    /// `None` for values that were never made by `encode`, such as from corrupt bytecode.
+    pub fn decode(value: Value) -> Option<Pointee> {
+        let integer = unsafe { value.integer };
+        let payload = (integer as u64 >> 2) as usize;
+        match integer & TAG_MASK {
+            0 if integer == 0 => Some(Pointee::Null),
+            TAG_LOCAL => Some(Pointee::Local {
+                frame_id: (integer as u64 >> (LOCAL_BITS + 2)) as u32,
+                local: payload & ((1 << LOCAL_BITS) - 1),
+            }),
+            TAG_GLOBAL => Some(Pointee::Global(payload)),
+            TAG_HEAP => Some(Pointee::Heap(payload)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Pointee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pointee::Null => f.write_str("NULL"),
+            Pointee::Local { local, .. } => write!(f, "@local[{}]", local),
+            Pointee::Global(idx) => write!(f, "@global[{}]", idx),
+            Pointee::Heap(idx) => write!(f, "@heap[{}]", idx),
+        }
+    }
+}
+
+/// Formats a pointer value for debugging output.
+pub fn format_pointer(value: &Value) -> String {
+    match Pointee::decode(*value) {
+        Some(pointee) => pointee.to_string(),
+        None => String::from("<invalid pointer>"),
+    }
+}
@@ -0,0 +1,757 @@
+//! Binary `.pbc` format for compiled bytecode.
+//!
+//! Layout (all integers little-endian):
+//!
+//! ```text
+//! file     := MAGIC version:u16 function global_count:u32 global*
+//! function := has_name:u8 [string] arity:u32 ret_type:type instr_count:u32 instr*
+//!             line:u32{instr_count} stmt_count:u32 stmt_start:u32* local_count:u32 local*
+//!             const_count:u32 constant*
+//! global   := name:string has_type:u8 [type] constant
+//! local    := name:string slot:u32 type start:u32 end:u32
+//! instr    := opcode:u8 [operand:u32 | type]
+//! type     := (TYPE_POINTER | TYPE_SET)* (code:u8 | TYPE_ENUM name:string value_count:u32 string*
+//!             | TYPE_CLASS class_type)
+//! class_type := name:string (0:u8 | 1:u8 class_type)
+//! constant := tag:u8 payload
+//! string   := len:u32 utf8-bytes
+//! ```
+//!
+//! Function constants nest recursively, so a whole program is a single script function
+//! followed by the name, type and starting value of each of its global slots. A class
+//! constant is its CLASS type, the types of its attributes and the global of each of its
+//! methods.
+
+use super::{
+    chunk::{Chunk, LocalInfo},
+    instr::Instr,
+    class::ObjClass,
+    obj::{
+        allocate_class, allocate_set, allocate_string, as_class, as_set, store_function, Obj, ObjFn,
+        ObjKind, ObjString,
+    },
+    pointer::Pointee,
+    set::ObjSet,
+    value::{Value, ValueKind},
+    Vm,
+};
+use crate::{as_rs_string, ir::hlir::Type};
+
+pub const MAGIC: &[u8; 4] = b"PBC\0";
+pub const FORMAT_VERSION: u16 = 14;
+
+/// Deepest nesting of function constants accepted when loading.
+const MAX_FN_DEPTH: usize = 64;
+
+/// Most `^`s and `SET OF`s accepted in a type, and CLASSes inherited from, when loading.
+const MAX_TYPE_DEPTH: usize = 64;
+
+const TAG_INTEGER: u8 = 0;
+const TAG_REAL: u8 = 1;
+const TAG_BOOLEAN: u8 = 2;
+const TAG_CHAR: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_FN: u8 = 5;
+const TAG_NULL: u8 = 6;
+/// Pointers are only ever NULL before the program runs, so carry no payload.
+const TAG_NULL_POINTER: u8 = 7;
+/// Sets are only ever empty before the program runs, so carry just their element type.
+const TAG_EMPTY_SET: u8 = 8;
+const TAG_CLASS: u8 = 9;
+
+/// Prefixes the type pointed to.
+const TYPE_POINTER: u8 = 5;
+const TYPE_NULL: u8 = 6;
+/// Followed by the type's name and values.
+const TYPE_ENUM: u8 = 7;
+/// Prefixes the type of the set's values.
+const TYPE_SET: u8 = 8;
+/// Followed by the CLASS's name and, if it has one, its parent.
+const TYPE_CLASS: u8 = 9;
+
+#[derive(Debug)]
+pub struct LoadError {
+    pub msg: &'static str,
+    /// Byte offset into the file where the problem was found.
+    pub offset: usize,
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at byte {})", self.msg, self.offset)
+    }
+}
+
+pub fn is_bytecode(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn serialize(script: &ObjFn) -> Vec<u8> {
+    let mut writer = Writer { bytes: Vec::new() };
+    writer.bytes.extend_from_slice(MAGIC);
+    writer.bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    writer.function(script);
+    let chunk = &script.chunk;
+    writer.u32(chunk.globals.len());
+    for (((name, value), kind), pseudo_type) in chunk
+        .global_names
+        .iter()
+        .zip(chunk.globals.iter())
+        .zip(chunk.global_kinds.iter())
+        .zip(chunk.global_types.iter())
+    {
+        writer.string(name);
+        match pseudo_type {
+            Some(pseudo_type) => {
+                writer.u8(1);
+                writer.pseudo_type(*pseudo_type);
+            }
+            None => writer.u8(0),
+        }
+        writer.constant(value, *kind);
+    }
+    writer.bytes
+}
+
+pub fn deserialize(vm: &mut Vm, bytes: &[u8]) -> Result<ObjFn, LoadError> {
+    let mut reader = Reader {
+        bytes,
+        offset: 0,
+        depth: 0,
+    };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return reader.error("not a pseudo_rs bytecode file");
+    }
+    if reader.u16()? != FORMAT_VERSION {
+        return reader.error("unsupported bytecode format version");
+    }
+    let mut script = reader.function(vm)?;
+    let global_count = reader.u32()?;
+    if global_count > bytes.len() - reader.offset {
+        return reader.error("global count exceeds file size");
+    }
+    for _ in 0..global_count {
+        let name = reader.string()?;
+        let pseudo_type = match reader.u8()? {
+            0 => None,
+            1 => Some(reader.pseudo_type()?),
+            _ => return reader.error("invalid global type flag"),
+        };
+        let (value, kind) = reader.constant(vm)?;
+        script.chunk.add_global(name, value, kind, pseudo_type);
+    }
+    if reader.offset != bytes.len() {
+        return reader.error("trailing bytes after script");
+    }
+
+    let globals_len = script.chunk.globals.len();
+    validate_globals(&script.chunk, globals_len).map_err(|msg| LoadError {
+        msg,
+        offset: bytes.len(),
+    })?;
+    Ok(script)
+}
+
+/// Checks that every global used, by instructions and by the method tables of classes, is
+/// in range.
+fn validate_globals(chunk: &Chunk, globals_len: usize) -> Result<(), &'static str> {
+    for instr in chunk.instructions.iter() {
+        match instr {
+            Instr::LoadGlobal(idx) | Instr::StoreGlobal(idx) | Instr::AddrGlobal(idx)
+                if *idx >= globals_len =>
+            {
+                return Err("global index out of range")
+            }
+            _ => (),
+        }
+    }
+    for (value, kind) in chunk.constants.iter().zip(chunk.constant_kinds.iter()) {
+        if *kind != ValueKind::Obj || unsafe { value.obj.is_null() } {
+            continue;
+        }
+        unsafe {
+            match (*value.obj).kind {
+                ObjKind::Fn => validate_globals(&(*(value.obj as *mut ObjFn)).chunk, globals_len)?,
+                ObjKind::Class if as_class(value.obj).methods.iter().any(|idx| *idx >= globals_len) => {
+                    return Err("method global index out of range")
+                }
+                _ => (),
+            }
+        }
+    }
+    Ok(())
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: usize) {
+        let value = u32::try_from(value).expect("bytecode operand does not fit in 32 bits");
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn string(&mut self, string: &str) {
+        self.u32(string.len());
+        self.bytes.extend_from_slice(string.as_bytes());
+    }
+
+    fn function(&mut self, function: &ObjFn) {
+        if function.name.is_null() {
+            self.u8(0);
+        } else {
+            self.u8(1);
+            self.string(unsafe { as_rs_string!(function.name) });
+        }
+        self.u32(function.arity);
+        self.pseudo_type(function.ret_type);
+
+        let chunk = &function.chunk;
+        self.u32(chunk.instructions.len());
+        chunk.instructions.iter().for_each(|instr| self.instr(instr));
+        chunk.lines.iter().for_each(|line| self.u32(*line));
+
+        self.u32(chunk.stmt_starts.len());
+        chunk.stmt_starts.iter().for_each(|start| self.u32(*start));
+        self.u32(chunk.locals.len());
+        for local in chunk.locals.iter() {
+            self.string(&local.name);
+            self.u32(local.slot);
+            self.pseudo_type(local.pseudo_type);
+            self.u32(local.start);
+            self.u32(local.end);
+        }
+
+        self.u32(chunk.constants.len());
+        for (value, kind) in chunk.constants.iter().zip(chunk.constant_kinds.iter()) {
+            self.constant(value, *kind);
+        }
+    }
+
+    fn constant(&mut self, value: &Value, kind: ValueKind) {
+        unsafe {
+            match kind {
+                ValueKind::Integer => {
+                    self.u8(TAG_INTEGER);
+                    self.bytes.extend_from_slice(&value.integer.to_le_bytes());
+                }
+                ValueKind::Real => {
+                    self.u8(TAG_REAL);
+                    self.bytes.extend_from_slice(&value.real.to_bits().to_le_bytes());
+                }
+                ValueKind::Boolean => {
+                    self.u8(TAG_BOOLEAN);
+                    self.u8(value.boolean as u8);
+                }
+                ValueKind::Char => {
+                    self.u8(TAG_CHAR);
+                    self.bytes.extend_from_slice(&(value.char as u32).to_le_bytes());
+                }
+                ValueKind::Obj if value.obj.is_null() => self.u8(TAG_NULL),
+                ValueKind::Pointer => self.u8(TAG_NULL_POINTER),
+                ValueKind::Obj => match (*value.obj).kind {
+                    ObjKind::String => {
+                        self.u8(TAG_STRING);
+                        self.string(&(*(value.obj as *mut ObjString)).string);
+                    }
+                    ObjKind::Fn => {
+                        self.u8(TAG_FN);
+                        self.function(&*(value.obj as *mut ObjFn));
+                    }
+                    ObjKind::Set => {
+                        self.u8(TAG_EMPTY_SET);
+                        self.pseudo_type(as_set(value.obj).element_type);
+                    }
+                    ObjKind::Class => {
+                        let class = as_class(value.obj);
+                        self.u8(TAG_CLASS);
+                        self.pseudo_type(Type::Class(class.class_type));
+                        self.u32(class.fields.len());
+                        class.fields.iter().for_each(|field| self.pseudo_type(*field));
+                        self.u32(class.methods.len());
+                        class.methods.iter().for_each(|method| self.u32(*method));
+                    }
+                    ObjKind::Instance => unreachable!("objects are only made by running the program"),
+                },
+            }
+        }
+    }
+
+    fn instr(&mut self, instr: &Instr) {
+        use Instr::*;
+        match instr {
+            Const(idx) => self.operand(0, *idx),
+            Pop => self.u8(1),
+            LoadLocal(idx) => self.operand(2, *idx),
+            StoreLocal(idx) => self.operand(3, *idx),
+            LoadGlobal(idx) => self.operand(4, *idx),
+            StoreGlobal(idx) => self.operand(5, *idx),
+            Call(args) => self.operand(6, *args),
+            Ret(args) => self.operand(7, *args),
+            Input(pseudo_type) => self.typed(8, *pseudo_type),
+            Output(pseudo_type) => self.typed(9, *pseudo_type),
+            OutputLn => self.u8(10),
+            OutputSpace => self.u8(11),
+            Concat => self.u8(12),
+            Add(pseudo_type) => self.typed(13, *pseudo_type),
+            Sub(pseudo_type) => self.typed(14, *pseudo_type),
+            Mul(pseudo_type) => self.typed(15, *pseudo_type),
+            Div(pseudo_type) => self.typed(16, *pseudo_type),
+            Mod(pseudo_type) => self.typed(17, *pseudo_type),
+            Gt(pseudo_type) => self.typed(18, *pseudo_type),
+            GtEq(pseudo_type) => self.typed(19, *pseudo_type),
+            Eq(pseudo_type) => self.typed(20, *pseudo_type),
+            Neg(pseudo_type) => self.typed(21, *pseudo_type),
+            Not => self.u8(22),
+            True => self.u8(23),
+            False => self.u8(24),
+            Null => self.u8(25),
+            JumpFalse(idx) => self.operand(26, *idx),
+            JumpTrue(idx) => self.operand(27, *idx),
+            Jump(idx) => self.operand(28, *idx),
+            AddrLocal(idx) => self.operand(29, *idx),
+            AddrGlobal(idx) => self.operand(30, *idx),
+            Alloc(pseudo_type) => self.typed(31, *pseudo_type),
+            Deref => self.u8(32),
+            StoreDeref => self.u8(33),
+            Dup => self.u8(34),
+            EmptySet(element_type) => self.typed(35, *element_type),
+            SetInsert => self.u8(36),
+            In => self.u8(37),
+            Instantiate(idx) => self.operand(38, *idx),
+            GetField(idx) => self.operand(39, *idx),
+            SetField(idx) => self.operand(40, *idx),
+            Method(slot) => self.operand(41, *slot),
+            Try(idx) => self.operand(42, *idx),
+            EndTry => self.u8(43),
+            CharToString => self.u8(44),
+        }
+    }
+
+    fn operand(&mut self, opcode: u8, operand: usize) {
+        self.u8(opcode);
+        self.u32(operand);
+    }
+
+    fn typed(&mut self, opcode: u8, pseudo_type: Type) {
+        self.u8(opcode);
+        self.pseudo_type(pseudo_type);
+    }
+
+    fn pseudo_type(&mut self, pseudo_type: Type) {
+        self.u8(match pseudo_type {
+            Type::Integer => 0,
+            Type::Real => 1,
+            Type::Char => 2,
+            Type::Boolean => 3,
+            Type::String => 4,
+            Type::Pointer(pointee) => {
+                self.u8(TYPE_POINTER);
+                return self.pseudo_type(*pointee);
+            }
+            Type::Null => TYPE_NULL,
+            Type::Set(element) => {
+                self.u8(TYPE_SET);
+                return self.pseudo_type(*element);
+            }
+            Type::Enum(enum_type) => {
+                self.u8(TYPE_ENUM);
+                self.string(&enum_type.name);
+                self.u32(enum_type.values.len());
+                enum_type.values.iter().for_each(|value| self.string(value));
+                return;
+            }
+            Type::Class(mut class_type) => {
+                self.u8(TYPE_CLASS);
+                loop {
+                    self.string(&class_type.name);
+                    match class_type.parent {
+                        Some(parent) => {
+                            self.u8(1);
+                            class_type = parent;
+                        }
+                        None => return self.u8(0),
+                    }
+                }
+            }
+        });
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error<T>(&self, msg: &'static str) -> Result<T, LoadError> {
+        Err(LoadError {
+            msg,
+            offset: self.offset,
+        })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], LoadError> {
+        match self.bytes.get(self.offset..self.offset.saturating_add(len)) {
+            Some(slice) => {
+                self.offset += len;
+                Ok(slice)
+            }
+            None => self.error("unexpected end of file"),
+        }
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, LoadError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<usize, LoadError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn u64(&mut self) -> Result<u64, LoadError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, LoadError> {
+        let len = self.u32()?;
+        let start = self.offset;
+        match std::str::from_utf8(self.take(len)?) {
+            Ok(string) => Ok(string.to_string()),
+            Err(_) => Err(LoadError {
+                msg: "string is not valid UTF-8",
+                offset: start,
+            }),
+        }
+    }
+
+    fn function(&mut self, vm: &mut Vm) -> Result<ObjFn, LoadError> {
+        self.depth += 1;
+        if self.depth > MAX_FN_DEPTH {
+            return self.error("function constants nested too deeply");
+        }
+
+        let name = match self.u8()? {
+            0 => std::ptr::null_mut(),
+            1 => {
+                let name = self.string()?;
+                allocate_string(vm, name)
+            }
+            _ => return self.error("invalid function name flag"),
+        };
+        let arity = self.u32()?;
+        let ret_type = self.pseudo_type()?;
+
+        let mut chunk = Chunk::new();
+
+        // Every instruction takes at least one byte, which bounds the count before allocating.
+        let instr_count = self.u32()?;
+        if instr_count > self.bytes.len() - self.offset {
+            return self.error("instruction count exceeds file size");
+        }
+        for _ in 0..instr_count {
+            let instr = self.instr()?;
+            chunk.instructions.push(instr);
+        }
+        for _ in 0..instr_count {
+            let line = self.u32()?;
+            chunk.lines.push(line);
+        }
+
+        let stmt_count = self.u32()?;
+        for _ in 0..stmt_count {
+            let start = self.u32()?;
+            if start >= instr_count || chunk.stmt_starts.last().is_some_and(|&last| last > start) {
+                return self.error("invalid statement start");
+            }
+            chunk.stmt_starts.push(start);
+        }
+        let local_count = self.u32()?;
+        for _ in 0..local_count {
+            let name = self.string()?;
+            let slot = self.u32()?;
+            let pseudo_type = self.pseudo_type()?;
+            let start = self.u32()?;
+            let end = self.u32()?;
+            if start > end || end > instr_count {
+                return self.error("invalid local variable range");
+            }
+            chunk.locals.push(LocalInfo {
+                name,
+                slot,
+                pseudo_type,
+                start,
+                end,
+            });
+        }
+
+        let const_count = self.u32()?;
+        if const_count > self.bytes.len() - self.offset {
+            return self.error("constant count exceeds file size");
+        }
+        for _ in 0..const_count {
+            let (value, kind) = self.constant(vm)?;
+            chunk.add_constant(value, kind);
+        }
+
+        let instr_len = chunk.instructions.len();
+        for instr in chunk.instructions.iter() {
+            match instr {
+                Instr::Const(idx) | Instr::Instantiate(idx) if *idx >= chunk.constants.len() => {
+                    return self.error("constant index out of range")
+                }
+                Instr::Jump(idx) | Instr::JumpFalse(idx) | Instr::JumpTrue(idx) | Instr::Try(idx)
+                    if *idx > instr_len =>
+                {
+                    return self.error("jump target out of range")
+                }
+                _ => (),
+            }
+        }
+
+        self.depth -= 1;
+        Ok(ObjFn {
+            obj: Obj {
+                kind: ObjKind::Fn,
+                next: std::ptr::null_mut(),
+            },
+            chunk,
+            name,
+            arity,
+            ret_type,
+        })
+    }
+
+    fn constant(&mut self, vm: &mut Vm) -> Result<(Value, ValueKind), LoadError> {
+        Ok(match self.u8()? {
+            TAG_INTEGER => (
+                Value {
+                    integer: self.u64()? as i64,
+                },
+                ValueKind::Integer,
+            ),
+            TAG_REAL => (
+                Value {
+                    real: f64::from_bits(self.u64()?),
+                },
+                ValueKind::Real,
+            ),
+            TAG_BOOLEAN => {
+                let boolean = match self.u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return self.error("invalid BOOLEAN constant"),
+                };
+                (Value { boolean }, ValueKind::Boolean)
+            }
+            TAG_CHAR => {
+                let char = match char::from_u32(self.u32()? as u32) {
+                    Some(char) => char,
+                    None => return self.error("invalid CHAR constant"),
+                };
+                (Value { char }, ValueKind::Char)
+            }
+            TAG_STRING => {
+                let string = self.string()?;
+                (
+                    Value {
+                        obj: allocate_string(vm, string),
+                    },
+                    ValueKind::Obj,
+                )
+            }
+            TAG_FN => {
+                let function = self.function(vm)?;
+                (
+                    Value {
+                        obj: store_function(vm, function),
+                    },
+                    ValueKind::Obj,
+                )
+            }
+            TAG_NULL => (
+                Value {
+                    obj: std::ptr::null_mut(),
+                },
+                ValueKind::Obj,
+            ),
+            TAG_NULL_POINTER => (Pointee::Null.encode(), ValueKind::Pointer),
+            TAG_EMPTY_SET => {
+                let element_type = self.set_element_type()?;
+                (
+                    Value {
+                        obj: allocate_set(vm, ObjSet::new(element_type)),
+                    },
+                    ValueKind::Obj,
+                )
+            }
+            TAG_CLASS => {
+                let class_type = match self.pseudo_type()? {
+                    Type::Class(class_type) => class_type,
+                    _ => return self.error("class constant without a CLASS type"),
+                };
+                let field_count = self.u32()?;
+                if field_count > self.bytes.len() - self.offset {
+                    return self.error("attribute count exceeds file size");
+                }
+                let fields = (0..field_count)
+                    .map(|_| self.pseudo_type())
+                    .collect::<Result<_, _>>()?;
+                let method_count = self.u32()?;
+                if method_count > self.bytes.len() - self.offset {
+                    return self.error("method count exceeds file size");
+                }
+                let methods = (0..method_count)
+                    .map(|_| self.u32())
+                    .collect::<Result<_, _>>()?;
+                (
+                    Value {
+                        obj: allocate_class(vm, ObjClass::new(class_type, fields, methods)),
+                    },
+                    ValueKind::Obj,
+                )
+            }
+            _ => return self.error("unknown constant tag"),
+        })
+    }
+
+    fn instr(&mut self) -> Result<Instr, LoadError> {
+        use Instr::*;
+        Ok(match self.u8()? {
+            0 => Const(self.u32()?),
+            1 => Pop,
+            2 => LoadLocal(self.u32()?),
+            3 => StoreLocal(self.u32()?),
+            4 => LoadGlobal(self.u32()?),
+            5 => StoreGlobal(self.u32()?),
+            6 => Call(self.u32()?),
+            7 => Ret(self.u32()?),
+            8 => Input(self.pseudo_type()?),
+            9 => Output(self.pseudo_type()?),
+            10 => OutputLn,
+            11 => OutputSpace,
+            12 => Concat,
+            13 => Add(self.pseudo_type()?),
+            14 => Sub(self.pseudo_type()?),
+            15 => Mul(self.pseudo_type()?),
+            16 => Div(self.pseudo_type()?),
+            17 => Mod(self.pseudo_type()?),
+            18 => Gt(self.pseudo_type()?),
+            19 => GtEq(self.pseudo_type()?),
+            20 => Eq(self.pseudo_type()?),
+            21 => Neg(self.pseudo_type()?),
+            22 => Not,
+            23 => True,
+            24 => False,
+            25 => Null,
+            26 => JumpFalse(self.u32()?),
+            27 => JumpTrue(self.u32()?),
+            28 => Jump(self.u32()?),
+            29 => AddrLocal(self.u32()?),
+            30 => AddrGlobal(self.u32()?),
+            31 => Alloc(self.pseudo_type()?),
+            32 => Deref,
+            33 => StoreDeref,
+            34 => Dup,
+            35 => EmptySet(self.set_element_type()?),
+            36 => SetInsert,
+            37 => In,
+            38 => Instantiate(self.u32()?),
+            39 => GetField(self.u32()?),
+            40 => SetField(self.u32()?),
+            41 => Method(self.u32()?),
+            42 => Try(self.u32()?),
+            43 => EndTry,
+            44 => CharToString,
+            _ => return self.error("unknown opcode"),
+        })
+    }
+
+    fn pseudo_type(&mut self) -> Result<Type, LoadError> {
+        let mut wrappers = Vec::new();
+        let mut pseudo_type = loop {
+            match self.u8()? {
+                0 => break Type::Integer,
+                1 => break Type::Real,
+                2 => break Type::Char,
+                3 => break Type::Boolean,
+                4 => break Type::String,
+                wrapper @ (TYPE_POINTER | TYPE_SET) if wrappers.len() < MAX_TYPE_DEPTH => {
+                    wrappers.push(wrapper)
+                }
+                TYPE_POINTER | TYPE_SET => return self.error("type nested too deeply"),
+                TYPE_NULL => break Type::Null,
+                TYPE_ENUM => {
+                    let name = self.string()?;
+                    let value_count = self.u32()?;
+                    if value_count == 0 {
+                        return self.error("enumerated type without values");
+                    }
+                    let values = (0..value_count)
+                        .map(|_| self.string())
+                        .collect::<Result<_, _>>()?;
+                    break Type::enumeration(name, values);
+                }
+                TYPE_CLASS => break self.class_type()?,
+                _ => return self.error("unknown type operand"),
+            }
+        };
+        for wrapper in wrappers.into_iter().rev() {
+            pseudo_type = match wrapper {
+                TYPE_SET if !is_set_element(pseudo_type) => {
+                    return self.error("invalid set element type")
+                }
+                TYPE_SET => Type::set(pseudo_type),
+                _ => Type::pointer(pseudo_type),
+            };
+        }
+        Ok(pseudo_type)
+    }
+
+    /// A CLASS, after the names of the CLASSes it inherits from, nearest first.
+    fn class_type(&mut self) -> Result<Type, LoadError> {
+        let mut names = vec![self.string()?];
+        loop {
+            match self.u8()? {
+                0 => break,
+                1 if names.len() < MAX_TYPE_DEPTH => names.push(self.string()?),
+                1 => return self.error("type nested too deeply"),
+                _ => return self.error("invalid parent CLASS flag"),
+            }
+        }
+        let mut parent = None;
+        for name in names.into_iter().rev() {
+            parent = match Type::class(name, parent) {
+                Type::Class(class_type) => Some(class_type),
+                _ => unreachable!(),
+            };
+        }
+        Ok(Type::Class(parent.unwrap()))
+    }
+
+    /// The values of a set are hashed or kept as bits by their type, so it can't be just
+    /// any type.
+    fn set_element_type(&mut self) -> Result<Type, LoadError> {
+        match self.pseudo_type()? {
+            element_type if is_set_element(element_type) => Ok(element_type),
+            _ => self.error("invalid set element type"),
+        }
+    }
+}
+
+fn is_set_element(pseudo_type: Type) -> bool {
+    matches!(
+        pseudo_type,
+        Type::Integer | Type::Char | Type::String | Type::Boolean | Type::Enum(_)
+    )
+}
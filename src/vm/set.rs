@@ -0,0 +1,172 @@
+//! Sets, made with DEFINE and combined with `+` (union), `-` (difference) and `*`
+//! (intersection).
+//!
+//! A set is only changed while DEFINE fills it in, before anything else can see it, and
+//! the operators always make a new set. So like STRINGs, sets can be shared between
+//! variables without copying.
+//!
+//! Sets of BOOLEANs, and of enumerated types with at most 64 values, are a bit per value.
+//! Anything else is kept in a hash set.
+
+use super::{
+    obj::{Obj, ObjKind},
+    value::Value,
+};
+use crate::{as_rs_string, ir::hlir::Type};
+use std::{collections::HashSet, fmt};
+
+#[repr(C)]
+pub struct ObjSet {
+    pub obj: Obj,
+    pub element_type: Type,
+    pub elements: Elements,
+}
+
+#[derive(PartialEq)]
+pub enum Elements {
+    /// Bit `n` is set when the value with ordinal `n` is in the set.
+    Bits(u64),
+    Hashed(HashSet<Element>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Element {
+    /// Also the position of a value of a large enumerated type.
+    Integer(i64),
+    Char(char),
+    String(String),
+}
+
+impl ObjSet {
+    pub fn new(element_type: Type) -> ObjSet {
+        let elements = match element_type {
+            Type::Boolean => Elements::Bits(0),
+            Type::Enum(enum_type) if enum_type.values.len() <= 64 => Elements::Bits(0),
+            _ => Elements::Hashed(HashSet::new()),
+        };
+        ObjSet {
+            obj: header(),
+            element_type,
+            elements,
+        }
+    }
+
+    /// Adds `value`, unless it is a value of an enumerated type that is out of range.
+    pub fn insert(&mut self, value: Value) -> bool {
+        match self.ordinal(value) {
+            Some(ordinal) => match &mut self.elements {
+                Elements::Bits(bits) => *bits |= 1 << ordinal,
+                Elements::Hashed(elements) => {
+                    elements.insert(element(self.element_type, value));
+                }
+            },
+            None => return false,
+        }
+        true
+    }
+
+    pub fn contains(&self, value: Value) -> bool {
+        match (self.ordinal(value), &self.elements) {
+            (None, _) => false,
+            (Some(ordinal), Elements::Bits(bits)) => bits & (1 << ordinal) != 0,
+            (Some(_), Elements::Hashed(elements)) => elements.contains(&element(self.element_type, value)),
+        }
+    }
+
+    pub fn union(&self, other: &ObjSet) -> ObjSet {
+        self.combine(other, |a, b| a | b, |a, b| a.union(b).cloned().collect())
+    }
+
+    pub fn intersection(&self, other: &ObjSet) -> ObjSet {
+        self.combine(other, |a, b| a & b, |a, b| {
+            a.intersection(b).cloned().collect()
+        })
+    }
+
+    pub fn difference(&self, other: &ObjSet) -> ObjSet {
+        self.combine(other, |a, b| a & !b, |a, b| {
+            a.difference(b).cloned().collect()
+        })
+    }
+
+    fn combine(
+        &self,
+        other: &ObjSet,
+        bits: fn(u64, u64) -> u64,
+        hashed: fn(&HashSet<Element>, &HashSet<Element>) -> HashSet<Element>,
+    ) -> ObjSet {
+        let elements = match (&self.elements, &other.elements) {
+            (Elements::Bits(a), Elements::Bits(b)) => Elements::Bits(bits(*a, *b)),
+            (Elements::Hashed(a), Elements::Hashed(b)) => Elements::Hashed(hashed(a, b)),
+            _ => unreachable!("sets of the same type are stored the same way"),
+        };
+        ObjSet {
+            obj: header(),
+            element_type: self.element_type,
+            elements,
+        }
+    }
+
+    /// Where `value` goes in `Elements::Bits`, or `None` when it has no place in the set.
+    /// Always `Some` for hashed sets, other than for an out of range enumerated value.
+    fn ordinal(&self, value: Value) -> Option<u32> {
+        unsafe {
+            match self.element_type {
+                Type::Boolean => Some(value.boolean as u32),
+                Type::Enum(enum_type) => {
+                    enum_type.value(value.integer)?;
+                    Some(value.integer as u32)
+                }
+                _ => Some(0),
+            }
+        }
+    }
+}
+
+fn element(element_type: Type, value: Value) -> Element {
+    unsafe {
+        match element_type {
+            Type::Char => Element::Char(value.char),
+            Type::String => Element::String(as_rs_string!(value.obj).clone()),
+            _ => Element::Integer(value.integer),
+        }
+    }
+}
+
+fn header() -> Obj {
+    Obj {
+        kind: ObjKind::Set,
+        next: std::ptr::null_mut(),
+    }
+}
+
+impl fmt::Display for ObjSet {
+    /// The values in order, as in `(A, E, I)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = match &self.elements {
+            Elements::Bits(bits) => (0..64)
+                .filter(|ordinal| bits & (1 << ordinal) != 0)
+                .map(|ordinal| match self.element_type {
+                    Type::Enum(enum_type) => enum_type.values[ordinal].clone(),
+                    _ => String::from(if ordinal == 1 { "TRUE" } else { "FALSE" }),
+                })
+                .collect(),
+            Elements::Hashed(elements) => {
+                let mut elements: Vec<&Element> = elements.iter().collect();
+                elements.sort();
+                elements
+                    .into_iter()
+                    .map(|element| match (element, self.element_type) {
+                        (Element::Integer(position), Type::Enum(enum_type)) => {
+                            enum_type.values[*position as usize].clone()
+                        }
+                        (Element::Integer(integer), _) => integer.to_string(),
+                        (Element::Char(char), _) => char.to_string(),
+                        (Element::String(string), _) => string.clone(),
+                    })
+                    .collect()
+            }
+        };
+        write!(f, "({})", values.join(", "))
+    }
+}
@@ -0,0 +1,702 @@
+use crate::{
+    codegen_bytecode::emit,
+    error::RuntimeError,
+    ir::hlir::{Decl, Stmt, StmtKind, Type},
+    lexer::Lexer,
+    naive_tc,
+    parser::program,
+};
+use super::{
+    chunk::{Chunk, LocalInfo},
+    disassembler::disassemble,
+    instr::Instr::{self, *},
+    obj::{Obj, ObjFn, ObjKind},
+    serialize::{deserialize, serialize},
+    value::{real_to_str, Value, ValueKind},
+    read_value,
+    verifier::verify,
+    ExecuteError, Limit, Vm, VmLimits,
+};
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+const SRC: &str = "FUNCTION Square(N : INTEGER) RETURNS INTEGER
+    RETURN N * N
+ENDFUNCTION
+
+PROCEDURE Main
+    DECLARE Name : STRING
+    Name <- \"pseudo\"
+    OUTPUT Name, 'c', 2.5, TRUE, Square(4)
+ENDPROCEDURE
+";
+
+fn compile(vm: &mut Vm) -> Vec<u8> {
+    let decls = program(Lexer::new(SRC).peekable()).unwrap();
+    serialize(&emit(naive_tc::typecheck(decls).unwrap(), vm).unwrap())
+}
+
+#[test]
+fn serialize_round_trip() {
+    let mut vm = Vm::new();
+    let bytes = compile(&mut vm);
+    let script = deserialize(&mut vm, &bytes).unwrap();
+    assert_eq!(serialize(&script), bytes);
+    assert_eq!(script.chunk.lines.len(), script.chunk.instructions.len());
+    vm.free_objects();
+}
+
+#[test]
+fn globals_round_trip() {
+    let src = "DECLARE Total : INTEGER
+PROCEDURE Add(N : INTEGER)
+    Total <- Total + N
+ENDPROCEDURE
+Total <- 1
+CALL Add(2)
+";
+    let mut vm = Vm::new();
+    let decls = program(Lexer::new(src).peekable()).unwrap();
+    let script = emit(naive_tc::typecheck(decls).unwrap(), &mut vm).unwrap();
+    assert!(verify(&script).is_ok());
+    assert_eq!(script.chunk.global_names, vec!["Total", "Add"]);
+    assert_eq!(script.chunk.global_kinds, vec![ValueKind::Integer, ValueKind::Obj]);
+    // Only the literals and `Add` itself are constants.
+    assert_eq!(script.chunk.constants.len(), 3);
+
+    // `Add` is NULL until its declaration is executed.
+    let bytes = serialize(&script);
+    let loaded = deserialize(&mut vm, &bytes).unwrap();
+    assert_eq!(serialize(&loaded), bytes);
+    vm.free_objects();
+}
+
+#[test]
+fn undefined_global() {
+    let mut vm = Vm::new();
+    let call = Stmt {
+        stmt_kind: StmtKind::Call {
+            name: String::from("Missing"),
+            args: Vec::new(),
+        },
+        line: 2,
+    };
+    let error = emit(vec![Decl::Stmt(call)], &mut vm).err().unwrap();
+    assert_eq!(error.msg, "Undefined global `Missing`.");
+    assert_eq!(error.line, 2);
+    vm.free_objects();
+}
+
+#[test]
+fn deserialize_rejects_truncated_files() {
+    let mut vm = Vm::new();
+    let bytes = compile(&mut vm);
+    for len in 0..bytes.len() {
+        assert!(deserialize(&mut vm, &bytes[..len]).is_err());
+    }
+    vm.free_objects();
+}
+
+#[test]
+fn deserialize_rejects_malformed_files() {
+    let mut vm = Vm::new();
+    let bytes = compile(&mut vm);
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert!(deserialize(&mut vm, &bad_magic).is_err());
+
+    let mut bad_version = bytes.clone();
+    bad_version[4] = 0xff;
+    assert!(deserialize(&mut vm, &bad_version).is_err());
+
+    // The first instruction of the script follows the name flag, arity, return type and
+    // instruction count.
+    let mut bad_opcode = bytes.clone();
+    bad_opcode[16] = 0xff;
+    assert!(deserialize(&mut vm, &bad_opcode).is_err());
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(deserialize(&mut vm, &trailing).is_err());
+    vm.free_objects();
+}
+
+#[test]
+fn deserialize_leaves_type_confusion_to_the_verifier() {
+    let src = "DECLARE N : INTEGER\nN <- 1094795585\nOUTPUT N\n";
+    let mut vm = Vm::new();
+    let decls = program(Lexer::new(src).peekable()).unwrap();
+    let mut script = emit(naive_tc::typecheck(decls).unwrap(), &mut vm).unwrap();
+    // Print the INTEGER as if it were a STRING.
+    for instr in script.chunk.instructions.iter_mut() {
+        if let Output(Type::Integer) = instr {
+            *instr = Output(Type::String);
+        }
+    }
+
+    // The file is well-formed, so it loads, but it doesn't verify.
+    let bytes = serialize(&script);
+    let loaded = deserialize(&mut vm, &bytes).unwrap();
+    assert!(verify(&loaded).is_err());
+    assert!(matches!(vm.execute(loaded), Err(ExecuteError::Invalid(_))));
+    vm.free_objects();
+}
+
+fn function(arity: usize, instructions: Vec<Instr>) -> ObjFn {
+    let mut chunk = Chunk::new();
+    instructions.into_iter().for_each(|instr| chunk.write(instr, 0));
+    ObjFn {
+        obj: Obj {
+            kind: ObjKind::Fn,
+            next: std::ptr::null_mut(),
+        },
+        chunk,
+        name: std::ptr::null_mut(),
+        arity,
+        ret_type: Type::Null,
+    }
+}
+
+#[test]
+fn disassembly() {
+    let src = "FUNCTION Half(N : INTEGER) RETURNS INTEGER
+    RETURN N DIV 2
+ENDFUNCTION
+IF Half(10) > 2 THEN
+    OUTPUT \"big\"
+ENDIF
+";
+    let mut vm = Vm::new();
+    let decls = program(Lexer::new(src).peekable()).unwrap();
+    let script = emit(naive_tc::typecheck(decls).unwrap(), &mut vm).unwrap();
+    assert_eq!(
+        disassemble(&script, Some(src)),
+        "\
+== <script> (arity 0) ==
+            ; FUNCTION Half(N : INTEGER) RETURNS INTEGER
+0000     1  Const        0    <fn Half>
+0001     |  StoreGlobal  0    Half
+0002     |  Pop
+            ; IF Half(10) > 2 THEN
+0003     4  LoadGlobal   0    Half
+0004     |  Const        1    10
+0005     |  Call         1
+0006     |  Const        2    2
+0007     |  Gt           Integer
+0008     |  JumpFalse    L0
+0009     |  Pop
+            ; OUTPUT \"big\"
+0010     5  Const        3    \"big\"
+0011     |  Output       String
+0012     |  OutputSpace
+0013     |  OutputLn
+0014     |  Jump         L1
+L0:
+0015     |  Pop
+L1:
+
+== Half (arity 1) ==
+            ; RETURN N DIV 2
+0000     2  LoadLocal    0    N
+0001     |  Const        0    2
+0002     |  Div          Integer
+0003     |  Ret          1
+
+"
+    );
+    vm.free_objects();
+}
+
+#[test]
+fn verifier_accepts_generated_code() {
+    let mut vm = Vm::new();
+    let decls = program(Lexer::new(SRC).peekable()).unwrap();
+    let script = emit(naive_tc::typecheck(decls).unwrap(), &mut vm).unwrap();
+    assert!(verify(&script).is_ok());
+    vm.free_objects();
+}
+
+#[test]
+fn verifier_rejects_malformed_chunks() {
+    let mut script = function(0, vec![Const(0)]);
+    assert!(verify(&script).is_err());
+    script.chunk.add_constant(Value { integer: 1 }, ValueKind::Integer);
+    assert!(verify(&script).is_ok());
+
+    assert!(verify(&function(0, vec![Jump(5)])).is_err());
+    assert!(verify(&function(0, vec![LoadLocal(0)])).is_err());
+    assert!(verify(&function(0, vec![Pop])).is_err());
+    assert!(verify(&function(0, vec![LoadGlobal(0)])).is_err());
+    assert!(verify(&function(0, vec![Ret(0)])).is_err());
+
+    // The false branch leaves one more value on the stack than the true branch.
+    let unbalanced = function(0, vec![True, JumpFalse(4), Pop, Jump(4), Null]);
+    assert!(verify(&unbalanced).is_err());
+
+    // Calling something that is not a function.
+    let bad_call = function(0, vec![True, Call(0)]);
+    assert!(verify(&bad_call).is_err());
+
+    // The VM runs nothing it can't verify, and says so.
+    let result = Vm::new().execute(function(0, vec![Pop]));
+    assert!(matches!(result, Err(ExecuteError::Invalid(_))));
+}
+
+#[test]
+fn verifier_checks_operand_types() {
+    // A well-formed file printing an INTEGER as if it were a pointer to a STRING.
+    let mut script = function(0, vec![Const(0), Output(Type::String)]);
+    script
+        .chunk
+        .add_constant(Value { integer: 0x41414141 }, ValueKind::Integer);
+    let bytes = serialize(&script);
+    let mut vm = Vm::new();
+    let script = deserialize(&mut vm, &bytes).unwrap();
+    assert!(matches!(vm.execute(script), Err(ExecuteError::Invalid(_))));
+
+    let mut concat = function(0, vec![Const(0), Const(0), Concat]);
+    concat.chunk.add_constant(Value { integer: 1 }, ValueKind::Integer);
+    assert!(verify(&concat).is_err());
+    let mut condition = function(0, vec![Const(0), JumpFalse(2), Pop]);
+    condition.chunk.add_constant(Value { integer: 1 }, ValueKind::Integer);
+    assert!(verify(&condition).is_err());
+    let field = function(0, vec![True, GetField(0)]);
+    assert!(verify(&field).is_err());
+    let deref = function(0, vec![True, Deref]);
+    assert!(verify(&deref).is_err());
+}
+
+#[test]
+fn verifier_checks_function_arity() {
+    let mut vm = Vm::new();
+    let mut callee = function(1, vec![LoadLocal(0), Ret(2)]);
+    callee.ret_type = Type::Integer;
+    callee.chunk.locals.push(LocalInfo {
+        name: String::from("N"),
+        slot: 0,
+        start: 0,
+        end: 2,
+        pseudo_type: Type::Integer,
+    });
+    let callee = super::obj::store_function(&mut vm, callee);
+
+    let mut script = function(0, vec![Const(0), Const(1), Call(1), Output(Type::Integer)]);
+    script.chunk.add_constant(Value { obj: callee }, ValueKind::Obj);
+    script.chunk.add_constant(Value { integer: 1 }, ValueKind::Integer);
+    assert!(verify(&script).is_err());
+
+    unsafe { (&mut (*(callee as *mut ObjFn)).chunk.instructions)[1] = Ret(1) };
+    assert!(verify(&script).is_ok());
+
+    script.chunk.instructions[2] = Call(0);
+    assert!(verify(&script).is_err());
+    vm.free_objects();
+}
+
+/// The runtime error a program stopped with, failing the test if it didn't verify.
+fn runtime_error(result: Result<(), ExecuteError>) -> Option<RuntimeError> {
+    match result {
+        Ok(()) => None,
+        Err(ExecuteError::Runtime(error)) => Some(error),
+        Err(ExecuteError::Invalid(error)) => panic!("invalid bytecode: {}", error),
+        Err(ExecuteError::Stopped) => unreachable!("no hook to stop the program"),
+    }
+}
+
+/// Runs `src`, returning the message and one-based line of the runtime error it stops with.
+fn run(src: &str, wrapping: bool) -> Option<(String, usize)> {
+    let mut vm = Vm::new();
+    vm.set_wrapping(wrapping);
+    let decls = program(Lexer::new(src).peekable()).unwrap();
+    let script = emit(naive_tc::typecheck(decls).unwrap(), &mut vm).unwrap();
+    runtime_error(vm.execute(script)).map(|error| (error.msg, error.line + 1))
+}
+
+/// Compiles `src` and loads it back from its `.pbc` form, checking that it verifies and
+/// saves to the same bytes again.
+fn load(vm: &mut Vm, src: &str) -> ObjFn {
+    let decls = program(Lexer::new(src).peekable()).unwrap();
+    let script = emit(naive_tc::typecheck(decls).unwrap(), vm).unwrap();
+    assert!(verify(&script).is_ok());
+    let bytes = serialize(&script);
+    let loaded = deserialize(vm, &bytes).unwrap();
+    assert_eq!(serialize(&loaded), bytes);
+    loaded
+}
+
+/// Runs `src` after a round trip through its `.pbc` form, returning the runtime error it
+/// stops with as `line <one-based line>: <message>`, or nothing if it runs to its end.
+fn round_trip(src: &str) -> String {
+    let mut vm = Vm::new();
+    let script = load(&mut vm, src);
+    match runtime_error(vm.execute(script)) {
+        Some(error) => format!("line {}: {}", error.line + 1, error.msg),
+        None => String::new(),
+    }
+}
+
+/// A writer for OUTPUT whose bytes can still be read after it is given to a `Vm`.
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `src` after a round trip through its `.pbc` form, returning what it printed.
+fn output(src: &str) -> String {
+    let mut vm = Vm::new();
+    let output = SharedOutput::default();
+    vm.set_output(Box::new(output.clone()));
+    let script = load(&mut vm, src);
+    assert!(vm.execute(script).is_ok());
+    let bytes = output.0.borrow().clone();
+    String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn integer_overflow() {
+    let src = "DECLARE X : INTEGER
+X <- 9223372036854775807
+X <- X - 1
+X <- X + 2
+";
+    assert_eq!(
+        run(src, false),
+        Some((
+            String::from("INTEGER overflow, 9223372036854775806 + 2 is out of range."),
+            4
+        ))
+    );
+    assert_eq!(run(src, true), None);
+
+    let negate = "DECLARE X : INTEGER\nX <- -9223372036854775807 - 1\nX <- -X\n";
+    assert_eq!(run(negate, false).map(|(_, line)| line), Some(3));
+    let multiply = "DECLARE X : INTEGER\nX <- 4294967296\nX <- X * X\n";
+    assert_eq!(run(multiply, false).map(|(_, line)| line), Some(3));
+}
+
+#[test]
+fn division_by_zero() {
+    let src = "DECLARE X : INTEGER\nX <- 0\nX <- 7 MOD X\n";
+    assert_eq!(
+        run(src, true),
+        Some((String::from("Division by zero, 7 MOD 0."), 3))
+    );
+}
+
+#[test]
+fn pointers() {
+    let src = "TYPE TIntPtr = ^INTEGER
+DECLARE X : INTEGER
+DECLARE P : TIntPtr
+DECLARE Q : ^^INTEGER
+P <- @X
+P^ <- 3
+Q <- NEW TIntPtr
+Q^ <- P
+Q^^ <- Q^^ + 1
+P <- NULL
+OUTPUT P^
+";
+    assert_eq!(round_trip(src), "line 11: Dereferencing a NULL pointer.");
+
+    let dangling = "FUNCTION Escape RETURNS ^INTEGER
+    DECLARE Local : INTEGER
+    Local <- 1
+    RETURN @Local
+ENDFUNCTION
+DECLARE P : ^INTEGER
+P <- Escape()
+OUTPUT P^
+";
+    assert_eq!(
+        round_trip(dangling),
+        "line 8: Dereferencing a pointer to a variable that no longer exists."
+    );
+}
+
+#[test]
+fn enumerated_types() {
+    let src = "TYPE Season = (Spring, Summer, Autumn, Winter)
+DECLARE Now : Season
+DECLARE Count : INTEGER
+Count <- 0
+FOR Now <- Spring TO Winter
+    CASE OF Now
+        Spring : Count <- Count + 1
+        Summer TO Autumn : Count <- Count + 10
+        OTHERWISE : Count <- Count + 100
+    ENDCASE
+NEXT Now
+IF Count <> 121 THEN
+    OUTPUT Spring - 1
+ENDIF
+OUTPUT Winter + 1
+";
+    // Enumerated types are written out in full where instructions carry a type.
+    assert_eq!(
+        round_trip(src),
+        "line 15: There is no Season value after Winter."
+    );
+}
+
+#[test]
+fn output_enumerated_values() {
+    let src = "TYPE Season = (Spring, Summer, Autumn, Winter)
+DECLARE Now : Season
+Now <- Summer
+OUTPUT Now, Now + 2, Spring
+";
+    assert_eq!(output(src), "Summer Winter Spring \n");
+}
+
+#[test]
+fn output_sets() {
+    let src = "TYPE Season = (Spring, Summer, Autumn, Winter)
+TYPE Seasons = SET OF Season
+DEFINE Warm (Summer, Spring) : Seasons
+DEFINE Neither () : Seasons
+DEFINE Vowels ('U', 'A', 'E') : SET OF CHAR
+OUTPUT Warm, Neither, Vowels
+";
+    // Sets print their values in order.
+    assert_eq!(output(src), "(Spring, Summer) () (A, E, U) \n");
+}
+
+#[test]
+fn sets() {
+    let src = "TYPE Season = (Spring, Summer, Autumn, Winter)
+TYPE Seasons = SET OF Season
+DEFINE Warm (Spring, Summer) : Seasons
+DEFINE Cold (Autumn, Winter) : Seasons
+DEFINE Neither () : Seasons
+DEFINE Vowels ('A', 'E', 'I', 'O', 'U') : SET OF CHAR
+DEFINE Letters ('A', 'B') : SET OF CHAR
+DEFINE Only ('A') : SET OF CHAR
+DECLARE Wrong : BOOLEAN
+Wrong <- Warm + Cold - Cold <> Warm OR Warm * Cold <> Neither OR Autumn IN Warm
+Wrong <- Wrong OR NOT ('E' IN Vowels) OR 'B' IN Vowels OR Vowels * Letters <> Only
+IF Wrong THEN
+    OUTPUT Spring - 1
+ENDIF
+DEFINE Beyond (Winter + 1) : Seasons
+";
+    // Globals start out as empty sets, which are saved with their element type.
+    assert_eq!(
+        round_trip(src),
+        "line 15: There is no Season value after Winter."
+    );
+}
+
+#[test]
+fn classes() {
+    let src = "CLASS Pet
+    PRIVATE Name : STRING
+    PUBLIC Legs : INTEGER
+    PUBLIC PROCEDURE NEW(GivenName : STRING)
+        Name <- GivenName
+        Legs <- 4
+    ENDPROCEDURE
+    PUBLIC FUNCTION Sound RETURNS STRING
+        RETURN Name
+    ENDFUNCTION
+ENDCLASS
+CLASS Cat INHERITS Pet
+    PUBLIC PROCEDURE NEW(GivenName : STRING)
+        SUPER.NEW(GivenName)
+        Legs <- Legs - 1
+    ENDPROCEDURE
+    PUBLIC FUNCTION Sound RETURNS STRING
+        RETURN SUPER.Sound() & \" meows\"
+    ENDFUNCTION
+ENDCLASS
+DECLARE Rex : Pet
+DECLARE Stray : Pet
+Rex <- NEW Cat(\"Tom\")
+IF Rex.Sound() <> \"Tom meows\" OR Rex.Legs <> 3 OR Rex = NEW Pet(\"Tom\") THEN
+    OUTPUT 1 DIV 0
+ENDIF
+PROCEDURE Walk
+    OUTPUT Rex.Legs, Stray.Legs
+ENDPROCEDURE
+CALL Walk
+";
+    assert_eq!(
+        round_trip(src),
+        "line 28: Using an object that was never made with NEW."
+    );
+}
+
+#[test]
+fn try_except() {
+    let src = "DECLARE Caught : INTEGER
+FUNCTION Halve(N : INTEGER) RETURNS INTEGER
+    TRY
+        RETURN 10 DIV N
+    EXCEPT
+        RETURN -1
+    ENDTRY
+ENDFUNCTION
+PROCEDURE Fail(N : INTEGER)
+    DECLARE Local : INTEGER
+    Local <- N DIV 0
+ENDPROCEDURE
+Caught <- 0
+TRY
+    CALL Fail(1)
+    Caught <- 100
+EXCEPT
+    Caught <- Caught + 1
+ENDTRY
+TRY
+    TRY
+        CALL Fail(2)
+    EXCEPT
+        Caught <- Caught + Halve(0)
+        CALL Fail(3)
+    ENDTRY
+EXCEPT
+    Caught <- Caught + 10
+ENDTRY
+Caught <- Caught DIV (Halve(5) - 2)
+";
+    // Returning from inside a TRY block leaves it, so the last error isn't caught.
+    assert_eq!(round_trip(src), "line 30: Division by zero, 10 DIV 0.");
+
+    // The EXCEPT block starts with the stack as it was at the `Try`.
+    let unbalanced = function(0, vec![Try(3), EndTry, Jump(4), Null]);
+    assert!(verify(&unbalanced).is_err());
+}
+
+#[test]
+fn input_values() {
+    unsafe {
+        assert_eq!(read_value(" -42 ", Type::Integer).unwrap().integer, -42);
+        assert_eq!(read_value("2.5", Type::Real).unwrap().real, 2.5);
+        assert!(read_value("true", Type::Boolean).unwrap().boolean);
+        assert_eq!(read_value("x", Type::Char).unwrap().char, 'x');
+    }
+    assert_eq!(
+        read_value("4.5", Type::Integer).err(),
+        Some(String::from("Expected an INTEGER as input, not \"4.5\"."))
+    );
+    assert!(read_value("yes", Type::Boolean).is_err());
+    assert!(read_value("xy", Type::Char).is_err());
+    assert!(read_value("", Type::Real).is_err());
+}
+
+#[test]
+fn real_formatting() {
+    assert_eq!(real_to_str(2.0), "2.0");
+    assert_eq!(real_to_str(0.1), "0.1");
+    assert_eq!(real_to_str(-87.5), "-87.5");
+    assert_eq!(real_to_str(0.1 + 0.2), "0.30000000000000004");
+    assert_eq!(real_to_str(1e21), "1000000000000000000000.0");
+    assert_eq!(real_to_str(1.5e-7), "0.00000015");
+    assert_eq!(real_to_str(f64::INFINITY), "inf");
+}
+
+#[test]
+fn concatenate_chars() {
+    let src = "DECLARE S : STRING
+S <- 'a' & \"bc\" & 'd'
+IF S = \"abcd\" THEN
+    S <- S & 'e' & 'f'
+ENDIF
+";
+    assert_eq!(run(src, false), None);
+}
+
+#[test]
+fn stack_overflow() {
+    let src = "FUNCTION Count(N : INTEGER) RETURNS INTEGER
+    RETURN Count(N + 1)
+ENDFUNCTION
+OUTPUT Count(0)
+";
+    let overflow = |configure: fn(&mut Vm)| {
+        let mut vm = Vm::new();
+        configure(&mut vm);
+        let script = load(&mut vm, src);
+        let error = runtime_error(vm.execute(script)).unwrap();
+        (error.msg, error.line + 1)
+    };
+
+    assert_eq!(
+        overflow(|vm| vm.set_max_call_depth(50)),
+        (
+            String::from(
+                "Stack overflow, calling Count would nest more than 50 calls.
+Call stack, innermost first:
+    Count on line 2, 50 times
+    <script> on line 4"
+            ),
+            2
+        )
+    );
+    let (msg, _) = overflow(|vm| vm.set_max_stack_len(100));
+    assert!(msg.starts_with("Stack overflow, calling Count with more than 100 values on the stack."));
+}
+
+#[test]
+fn limits() {
+    let forever = "DECLARE S : STRING
+S <- \"\"
+WHILE TRUE
+    S <- S & 'x'
+    OUTPUT \"x\"
+ENDWHILE
+";
+    let stop = |limits: VmLimits| {
+        let mut vm = Vm::new();
+        vm.set_limits(limits);
+        let script = load(&mut vm, forever);
+        let error = runtime_error(vm.execute(script)).unwrap();
+        (error.limit, error.msg)
+    };
+
+    assert_eq!(
+        stop(VmLimits {
+            max_instructions: Some(1000),
+            ..VmLimits::default()
+        }),
+        (
+            Some(Limit::Instructions),
+            String::from("Instruction limit reached, the program ran more than 1000 instructions.")
+        )
+    );
+    assert_eq!(
+        stop(VmLimits {
+            deadline: Some(std::time::Instant::now()),
+            ..VmLimits::default()
+        })
+        .0,
+        Some(Limit::Deadline)
+    );
+    assert_eq!(
+        stop(VmLimits {
+            max_output_len: Some(10),
+            ..VmLimits::default()
+        }),
+        (
+            Some(Limit::Output),
+            String::from("Output limit reached, the program printed more than 10 bytes.")
+        )
+    );
+    assert_eq!(
+        stop(VmLimits {
+            max_heap_objects: Some(20),
+            ..VmLimits::default()
+        })
+        .0,
+        Some(Limit::HeapObjects)
+    );
+}
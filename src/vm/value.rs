@@ -1,4 +1,5 @@
 use super::obj::Obj;
+use crate::ir::hlir::Type;
 
 #[derive(Clone, Copy)]
 pub union Value {
@@ -9,6 +10,55 @@ pub union Value {
     pub obj: *mut Obj,
 }
 
+/// Tag for a `Value`, for places that need to inspect the untagged union without
+/// static type information, such as when serialising a chunk's constants.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueKind {
+    Integer,
+    Real,
+    Boolean,
+    Char,
+    Obj,
+    Pointer,
+}
+
+impl From<Type> for ValueKind {
+    fn from(pseudo_type: Type) -> Self {
+        match pseudo_type {
+            Type::Integer | Type::Enum(_) => ValueKind::Integer,
+            Type::Real => ValueKind::Real,
+            Type::Char => ValueKind::Char,
+            Type::Boolean => ValueKind::Boolean,
+            Type::String | Type::Set(_) | Type::Class(_) => ValueKind::Obj,
+            Type::Pointer(_) | Type::Null => ValueKind::Pointer,
+        }
+    }
+}
+
+/// The text of a REAL as OUTPUT prints it: the fewest digits that read back as the same
+/// REAL, never in scientific notation, and with a `.0` on whole numbers to tell them apart
+/// from INTEGERs. The C runtime's `pseudo_real_to_str` formats REALs the same way.
+pub fn real_to_str(real: f64) -> String {
+    let mut text = real.to_string();
+    if real.is_finite() && !text.contains('.') {
+        text.push_str(".0");
+    }
+    text
+}
+
+/// An INTEGER, REAL, CHAR or BOOLEAN `value` as OUTPUT prints it.
+pub fn scalar_to_str(value: Value, pseudo_type: Type) -> String {
+    unsafe {
+        match pseudo_type {
+            Type::Integer => value.integer.to_string(),
+            Type::Real => real_to_str(value.real),
+            Type::Char => value.char.to_string(),
+            Type::Boolean => String::from(if value.boolean { "TRUE" } else { "FALSE" }),
+            _ => unreachable!("not a scalar type"),
+        }
+    }
+}
+
 impl Value {
     pub fn print_all_possible(&self) {
         unsafe {
@@ -0,0 +1,840 @@
+//! Static checks run over bytecode before it is executed.
+//!
+//! `Vm::execute` trusts the bytecode it runs: it indexes locals and globals without bounds
+//! checks, calls whatever is below the arguments and reads each value as the type the
+//! instruction says it is, following STRINGs, sets and objects through raw pointers. So
+//! every chunk is verified first, by running it over the types of the values on the stack
+//! rather than the values themselves: jump targets, constant, global and local indices
+//! are in range, the stack has the same depth wherever control merges, every typed
+//! instruction is given operands of its type, every `Call` targets a function of matching
+//! arity with arguments of its parameters' types and `Ret` returns a value of the
+//! function's type.
+//!
+//! Variables and globals are what let types be relied on across calls and pointers: a
+//! variable holds a value of its type for as long as it is in scope, a parameter starts
+//! out as the variable for it, and pointers are only made to variables, globals and values
+//! allocated with NEW, each with a type. Methods are looked up while the program runs, so
+//! the attributes and methods of a CLASS have to agree with those of the CLASSes it
+//! inherits from, which is what calling a method through an object of any of them expects.
+
+use super::{
+    chunk::Chunk,
+    class::ObjClass,
+    disassembler::function_name,
+    instr::Instr,
+    obj::{as_class, as_set, ObjFn, ObjKind},
+    value::{Value, ValueKind},
+};
+use crate::ir::hlir::{ClassType, Type};
+
+#[derive(Debug)]
+pub struct VerifyError {
+    pub msg: &'static str,
+    pub function: String,
+    pub instr_idx: usize,
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (in {} at instruction {})",
+            self.msg, self.function, self.instr_idx
+        )
+    }
+}
+
+/// What the verifier knows about a value on the stack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    /// A value of the type.
+    Value(Type),
+    /// A function, loaded from a constant or from a global that only ever holds it.
+    Fn(*const ObjFn),
+    /// The version of a method for the object above it, pushed by `Method`. Calling it is
+    /// checked against the method of the object's CLASS.
+    Method(*const ObjFn),
+    /// The object a method was looked up for, which is what it has to be called with.
+    Receiver,
+    /// A value that could be of different types, which typed instructions don't accept.
+    Unknown,
+}
+
+impl Slot {
+    fn join(self, other: Slot) -> Slot {
+        match (self, other) {
+            _ if self == other => self,
+            (Slot::Value(a), Slot::Value(b)) => join_types(a, b).map_or(Slot::Unknown, Slot::Value),
+            _ => Slot::Unknown,
+        }
+    }
+
+    /// Whether the slot can be copied or stored. A method and the object it was looked up
+    /// for have to stay together, as they were pushed.
+    fn is_movable(self) -> bool {
+        !matches!(self, Slot::Method(_) | Slot::Receiver)
+    }
+}
+
+/// Whether a value of type `from` can be used where a `to` is expected. Values of
+/// enumerated types are stored as INTEGERs, checked where they are printed or put in sets,
+/// and the zero bits of NULL are also a value of every type not kept behind a pointer.
+fn accepts(to: Type, from: Type) -> bool {
+    match (to, from) {
+        (Type::Integer | Type::Enum(_), Type::Integer | Type::Enum(_)) => true,
+        (Type::String | Type::Set(_), Type::Null) => false,
+        (_, Type::Null) => true,
+        _ => to.accepts(from),
+    }
+}
+
+/// The type values of both `a` and `b` can be used as, if there is one.
+fn join_types(a: Type, b: Type) -> Option<Type> {
+    if accepts(a, b) {
+        return Some(a);
+    }
+    if accepts(b, a) {
+        return Some(b);
+    }
+    match (a, b) {
+        (Type::Class(a), Type::Class(b)) => {
+            let mut ancestor = a.parent;
+            while let Some(class_type) = ancestor {
+                if b.inherits(class_type) {
+                    return Some(Type::Class(class_type));
+                }
+                ancestor = class_type.parent;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// What is known about the whole program, shared by the verifiers of its functions.
+struct Program<'a> {
+    global_types: &'a [Option<Type>],
+    /// The function held by each global that is only ever assigned function constants
+    /// (the `Const`, `StoreGlobal` sequence emitted for each declaration).
+    global_fns: Vec<Option<*const ObjFn>>,
+    /// Every CLASS of the program, which has to be a constant of one of its functions.
+    classes: Vec<&'a ObjClass>,
+}
+
+impl Program<'_> {
+    fn class(&self, class_type: &ClassType) -> Option<&ObjClass> {
+        self.classes
+            .iter()
+            .find(|class| *class.class_type == *class_type)
+            .copied()
+    }
+
+    fn method(&self, class: &ObjClass, slot: usize) -> &ObjFn {
+        // Every method is checked to be a function global by `verify_classes`.
+        unsafe { &*self.global_fns[class.methods[slot]].unwrap() }
+    }
+}
+
+pub fn verify(script: &ObjFn) -> Result<(), VerifyError> {
+    let functions = collect_functions(script);
+    let chunk = &script.chunk;
+    let error = |msg| {
+        Err(VerifyError {
+            msg,
+            function: function_name(script).to_string(),
+            instr_idx: 0,
+        })
+    };
+
+    if chunk.global_kinds.len() != chunk.globals.len()
+        || chunk.global_names.len() != chunk.globals.len()
+        || chunk.global_types.len() != chunk.globals.len()
+    {
+        return error("global tables have mismatched lengths");
+    }
+    for ((value, kind), pseudo_type) in chunk
+        .globals
+        .iter()
+        .zip(chunk.global_kinds.iter())
+        .zip(chunk.global_types.iter())
+    {
+        let is_valid = match pseudo_type {
+            Some(pseudo_type) => is_initial_value(*value, *kind, *pseudo_type),
+            // Functions only reach globals through `StoreGlobal`, where they are tracked.
+            None => {
+                *kind != ValueKind::Obj
+                    || unsafe { value.obj.is_null() }
+                    || unsafe { !matches!((*value.obj).kind, ObjKind::Fn | ObjKind::Class) }
+            }
+        };
+        if !is_valid {
+            return error("global doesn't start out holding a value of its type");
+        }
+    }
+
+    let mut classes: Vec<&ObjClass> = Vec::new();
+    for function in functions.iter() {
+        let chunk = unsafe { &(**function).chunk };
+        if chunk.constant_kinds.len() != chunk.constants.len() {
+            return error("chunk tables have mismatched lengths");
+        }
+        for idx in 0..chunk.constants.len() {
+            let class = match class_constant(chunk, idx) {
+                Some(class) => class,
+                None => continue,
+            };
+            match classes.iter().find(|other| *other.class_type == *class.class_type) {
+                Some(other) if other.fields != class.fields || other.methods != class.methods => {
+                    return error("CLASS constants of the same type disagree")
+                }
+                Some(_) => (),
+                None => classes.push(class),
+            }
+        }
+    }
+
+    let program = Program {
+        global_types: &chunk.global_types,
+        global_fns: function_globals(&functions, &chunk.global_types),
+        classes,
+    };
+    if let Err(msg) = verify_classes(&program) {
+        return error(msg);
+    }
+
+    for function in functions.iter() {
+        let function = unsafe { &**function };
+        Verifier {
+            function,
+            is_script: std::ptr::eq(function, script),
+            program: &program,
+        }
+        .verify()?;
+    }
+    Ok(())
+}
+
+/// Whether `value`, tagged `kind`, is a value of `pseudo_type` a global can start out as.
+fn is_initial_value(value: Value, kind: ValueKind, pseudo_type: Type) -> bool {
+    if kind != ValueKind::from(pseudo_type) {
+        return false;
+    }
+    unsafe {
+        match pseudo_type {
+            Type::String => !value.obj.is_null() && matches!((*value.obj).kind, ObjKind::String),
+            Type::Set(element_type) => {
+                !value.obj.is_null()
+                    && matches!((*value.obj).kind, ObjKind::Set)
+                    && as_set(value.obj).element_type == *element_type
+            }
+            // Objects are only made by running the program.
+            Type::Class(_) => value.obj.is_null(),
+            Type::Pointer(_) | Type::Null => value.integer == 0,
+            Type::Integer | Type::Real | Type::Char | Type::Boolean | Type::Enum(_) => true,
+        }
+    }
+}
+
+/// Checks that every method is a function global and that each CLASS stores the
+/// attributes and methods of the CLASSes it inherits from in the same places, the
+/// methods taking the same parameters and returning what the inherited ones do.
+fn verify_classes(program: &Program) -> Result<(), &'static str> {
+    for class in program.classes.iter() {
+        let is_function = |global: &usize| {
+            program.global_fns.get(*global).is_some_and(|function| function.is_some())
+        };
+        if !class.methods.iter().all(is_function) {
+            return Err("class method is not a function global");
+        }
+    }
+
+    for class in program.classes.iter() {
+        for slot in 0..class.methods.len() {
+            // Whichever object of the CLASS a method is called on, it's one it accepts.
+            let method = program.method(class, slot);
+            let receives = match param_type(method, 0) {
+                Some(Type::Class(receiver)) => class.class_type.inherits(receiver),
+                Some(_) => false,
+                None => true,
+            };
+            if method.arity == 0 || !receives {
+                return Err("class method doesn't take an object of the class");
+            }
+        }
+
+        let mut ancestor = class.class_type.parent;
+        while let Some(ancestor_type) = ancestor {
+            ancestor = ancestor_type.parent;
+            let inherited = match program.class(ancestor_type) {
+                Some(inherited) => inherited,
+                None => continue,
+            };
+            if !class.fields.starts_with(&inherited.fields)
+                || class.methods.len() < inherited.methods.len()
+            {
+                return Err("class doesn't extend the class it inherits from");
+            }
+            for slot in 0..inherited.methods.len() {
+                let method = program.method(class, slot);
+                let overridden = program.method(inherited, slot);
+                let same_params = (1..method.arity).all(|idx| {
+                    let param = param_type(method, idx);
+                    param.is_none() || param == param_type(overridden, idx)
+                });
+                if method.arity != overridden.arity
+                    || !same_params
+                    || !accepts(overridden.ret_type, method.ret_type)
+                {
+                    return Err("method doesn't match the method it overrides");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The type of parameter `idx` of `function`, from the variable it starts out as. Without
+/// one, arguments aren't checked and the function can't rely on the parameter's type.
+fn param_type(function: &ObjFn, idx: usize) -> Option<Type> {
+    function.chunk.local_at(idx, 0).map(|local| local.pseudo_type)
+}
+
+fn collect_functions(script: &ObjFn) -> Vec<*const ObjFn> {
+    let mut functions = vec![script as *const ObjFn];
+    let mut idx = 0;
+    while idx < functions.len() {
+        let chunk = unsafe { &(*functions[idx]).chunk };
+        for idx in 0..chunk.constants.len() {
+            if let Some(function) = function_constant(chunk, idx) {
+                functions.push(function);
+            }
+        }
+        idx += 1;
+    }
+    functions
+}
+
+fn class_constant(chunk: &Chunk, idx: usize) -> Option<&ObjClass> {
+    match chunk.constant_kinds.get(idx) {
+        Some(ValueKind::Obj) => unsafe {
+            let obj = chunk.constants[idx].obj;
+            if obj.is_null() {
+                return None;
+            }
+            match (*obj).kind {
+                ObjKind::Class => Some(as_class(obj)),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+fn function_constant(chunk: &Chunk, idx: usize) -> Option<*const ObjFn> {
+    match chunk.constant_kinds.get(idx) {
+        Some(ValueKind::Obj) => unsafe {
+            let obj = chunk.constants[idx].obj;
+            if obj.is_null() {
+                return None;
+            }
+            match (*obj).kind {
+                ObjKind::Fn => Some(obj as *const ObjFn),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// The function held by each global without a type that is only ever assigned the same
+/// function constant.
+fn function_globals(
+    functions: &[*const ObjFn],
+    global_types: &[Option<Type>],
+) -> Vec<Option<*const ObjFn>> {
+    let mut assigned: Vec<Option<Option<*const ObjFn>>> = vec![None; global_types.len()];
+    for function in functions.iter() {
+        let chunk = unsafe { &(**function).chunk };
+        for (idx, instr) in chunk.instructions.iter().enumerate() {
+            let global = match instr {
+                Instr::StoreGlobal(global) if global_types.get(*global) == Some(&None) => *global,
+                _ => continue,
+            };
+            let function = match idx.checked_sub(1).map(|idx| &chunk.instructions[idx]) {
+                Some(Instr::Const(constant)) => function_constant(chunk, *constant),
+                _ => None,
+            };
+            assigned[global] = match assigned[global] {
+                None => Some(function),
+                Some(previous) if previous == function => Some(function),
+                Some(_) => Some(None),
+            };
+        }
+    }
+    assigned.into_iter().map(Option::flatten).collect()
+}
+
+/// The abstract frame before an instruction.
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    /// The frame's parameters, locals and temporaries.
+    stack: Vec<Slot>,
+    /// TRY blocks the frame is in, innermost last: the first instruction of the EXCEPT
+    /// block and the height of the stack it starts with.
+    handlers: Vec<(usize, usize)>,
+}
+
+struct Verifier<'a> {
+    function: &'a ObjFn,
+    is_script: bool,
+    program: &'a Program<'a>,
+}
+
+impl Verifier<'_> {
+    fn error<T>(&self, msg: &'static str, instr_idx: usize) -> Result<T, VerifyError> {
+        Err(VerifyError {
+            msg,
+            function: function_name(self.function).to_string(),
+            instr_idx,
+        })
+    }
+
+    fn verify(&self) -> Result<(), VerifyError> {
+        let chunk = &self.function.chunk;
+        let len = chunk.instructions.len();
+        if chunk.lines.len() != len
+            || chunk.constant_kinds.len() != chunk.constants.len()
+            || chunk.global_kinds.len() != chunk.globals.len()
+            || chunk.global_names.len() != chunk.globals.len()
+            || chunk.global_types.len() != chunk.globals.len()
+        {
+            return self.error("chunk tables have mismatched lengths", 0);
+        }
+        for (idx, local) in chunk.locals.iter().enumerate() {
+            let overlaps = chunk.locals[..idx].iter().any(|other| {
+                other.slot == local.slot && other.start < local.end && local.start < other.end
+            });
+            if overlaps {
+                return self.error("two variables share a slot at once", local.start);
+            }
+        }
+
+        let params = (0..self.function.arity)
+            .map(|idx| param_type(self.function, idx).map_or(Slot::Unknown, Slot::Value))
+            .collect();
+        let mut states: Vec<Option<State>> = vec![None; len + 1];
+        states[0] = Some(State {
+            stack: params,
+            handlers: Vec::new(),
+        });
+        let mut worklist = vec![0];
+
+        while let Some(idx) = worklist.pop() {
+            if idx == len {
+                if !self.is_script {
+                    return self.error("control reaches the end of the function without Ret", idx);
+                }
+                continue;
+            }
+            let mut state = states[idx].clone().unwrap();
+            self.variables(idx, &mut state.stack)?;
+
+            let mut successors = Vec::new();
+            // A runtime error in a TRY block continues at its EXCEPT block.
+            if let Some((handler_idx, stack_len)) = state.handlers.last().copied() {
+                if state.stack.len() < stack_len {
+                    return self.error("stack below the start of a TRY block", idx);
+                }
+                let handlers = state.handlers[..state.handlers.len() - 1].to_vec();
+                let stack = state.stack[..stack_len].to_vec();
+                successors.push((handler_idx, State { stack, handlers }));
+            }
+            for successor in self.step(idx, &mut state)?.into_iter().flatten() {
+                successors.push((successor, state.clone()));
+            }
+
+            for (successor, state) in successors {
+                if successor > len {
+                    return self.error("jump target out of range", idx);
+                }
+                let merged = match &states[successor] {
+                    None => state,
+                    Some(existing) => {
+                        if existing.stack.len() != state.stack.len() {
+                            return self.error("inconsistent stack depth at merge point", successor);
+                        }
+                        if existing.handlers != state.handlers {
+                            return self.error("inconsistent TRY blocks at merge point", successor);
+                        }
+                        let stack = existing
+                            .stack
+                            .iter()
+                            .zip(state.stack.iter())
+                            .map(|(a, b)| a.join(*b))
+                            .collect();
+                        let merged = State {
+                            stack,
+                            handlers: state.handlers,
+                        };
+                        if &merged == existing {
+                            continue;
+                        }
+                        merged
+                    }
+                };
+                states[successor] = Some(merged);
+                worklist.push(successor);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every variable in scope at `idx` holds a value of its type, then widens
+    /// each to its type, as a pointer to it or a call may store any value of it there.
+    fn variables(&self, idx: usize, stack: &mut [Slot]) -> Result<(), VerifyError> {
+        let chunk = &self.function.chunk;
+        for local in chunk.locals.iter() {
+            if !(local.start <= idx && idx < local.end) {
+                continue;
+            }
+            match stack.get(local.slot) {
+                Some(Slot::Value(pseudo_type)) if accepts(local.pseudo_type, *pseudo_type) => {
+                    stack[local.slot] = Slot::Value(local.pseudo_type);
+                }
+                _ => return self.error("variable doesn't hold a value of its type", idx),
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the effect of the instruction at `idx` and returns its successors.
+    fn step(&self, idx: usize, state: &mut State) -> Result<[Option<usize>; 2], VerifyError> {
+        use Instr::*;
+        let chunk = &self.function.chunk;
+        let program = self.program;
+        let stack = &mut state.stack;
+        let next = Some(idx + 1);
+
+        macro_rules! pop {
+            () => {
+                match stack.pop() {
+                    Some(slot) => slot,
+                    None => return self.error("stack underflow", idx),
+                }
+            };
+        }
+
+        // Pops a value of a type `$pseudo_type` accepts.
+        macro_rules! pop_value {
+            ($pseudo_type:expr) => {
+                match pop!() {
+                    Slot::Value(pseudo_type) if accepts($pseudo_type, pseudo_type) => pseudo_type,
+                    _ => return self.error("operand of the wrong type", idx),
+                }
+            };
+        }
+
+        // Pops an object, returning its CLASS.
+        macro_rules! pop_object {
+            () => {
+                match pop!() {
+                    Slot::Value(Type::Class(class_type)) => match program.class(class_type) {
+                        Some(class) => class,
+                        None => return self.error("object of a CLASS that isn't a constant", idx),
+                    },
+                    _ => return self.error("operand isn't an object", idx),
+                }
+            };
+        }
+
+        match &chunk.instructions[idx] {
+            Const(constant) => {
+                if *constant >= chunk.constants.len() {
+                    return self.error("constant index out of range", idx);
+                }
+                let value = chunk.constants[*constant];
+                let slot = unsafe {
+                    match chunk.constant_kinds[*constant] {
+                        ValueKind::Integer => Slot::Value(Type::Integer),
+                        ValueKind::Real => Slot::Value(Type::Real),
+                        ValueKind::Boolean => Slot::Value(Type::Boolean),
+                        ValueKind::Char => Slot::Value(Type::Char),
+                        ValueKind::Pointer if value.integer == 0 => Slot::Value(Type::Null),
+                        ValueKind::Pointer => {
+                            return self.error("pointer constant that isn't NULL", idx)
+                        }
+                        ValueKind::Obj if value.obj.is_null() => Slot::Value(Type::Null),
+                        ValueKind::Obj => match (*value.obj).kind {
+                            ObjKind::String => Slot::Value(Type::String),
+                            ObjKind::Set => Slot::Value(Type::set(as_set(value.obj).element_type)),
+                            ObjKind::Fn => Slot::Fn(value.obj as *const ObjFn),
+                            ObjKind::Class | ObjKind::Instance => Slot::Unknown,
+                        },
+                    }
+                };
+                stack.push(slot);
+            }
+            Pop => {
+                pop!();
+            }
+            Dup => match stack.last() {
+                Some(slot) if slot.is_movable() => stack.push(*slot),
+                Some(_) => return self.error("method copied", idx),
+                None => return self.error("stack underflow", idx),
+            },
+            LoadLocal(local) => match stack.get(*local) {
+                Some(slot) if slot.is_movable() => stack.push(*slot),
+                Some(_) => return self.error("method copied", idx),
+                None => return self.error("local index out of range", idx),
+            },
+            StoreLocal(local) => {
+                if *local >= stack.len() {
+                    return self.error("local index out of range", idx);
+                }
+                let slot = *stack.last().unwrap();
+                stack[*local] = match (chunk.local_at(*local, idx), slot) {
+                    (Some(variable), Slot::Value(pseudo_type))
+                        if accepts(variable.pseudo_type, pseudo_type) =>
+                    {
+                        Slot::Value(variable.pseudo_type)
+                    }
+                    (Some(_), _) => return self.error("variable assigned the wrong type", idx),
+                    (None, slot) if slot.is_movable() => slot,
+                    (None, _) => return self.error("method copied", idx),
+                };
+            }
+            LoadGlobal(global) => {
+                if *global >= program.global_types.len() {
+                    return self.error("global index out of range", idx);
+                }
+                stack.push(match (program.global_fns[*global], program.global_types[*global]) {
+                    (Some(function), _) => Slot::Fn(function),
+                    (None, Some(pseudo_type)) => Slot::Value(pseudo_type),
+                    (None, None) => Slot::Unknown,
+                });
+            }
+            StoreGlobal(global) => {
+                if *global >= program.global_types.len() {
+                    return self.error("global index out of range", idx);
+                }
+                let slot = match stack.last() {
+                    Some(slot) => *slot,
+                    None => return self.error("stack underflow", idx),
+                };
+                let is_valid = match (program.global_fns[*global], program.global_types[*global]) {
+                    (Some(function), _) => slot == Slot::Fn(function),
+                    (None, Some(global_type)) => {
+                        matches!(slot, Slot::Value(pseudo_type) if accepts(global_type, pseudo_type))
+                    }
+                    (None, None) => slot.is_movable(),
+                };
+                if !is_valid {
+                    return self.error("global assigned the wrong type", idx);
+                }
+            }
+            Call(args) => {
+                if stack.len() < args + 1 {
+                    return self.error("stack underflow", idx);
+                }
+                let base = stack.len() - args;
+                let (function, first_param) = match stack[base - 1] {
+                    Slot::Fn(function) => (unsafe { &*function }, 0),
+                    Slot::Method(_) if *args == 0 || stack[base] != Slot::Receiver => {
+                        return self.error("method called without its object", idx)
+                    }
+                    // The object is the one the method was looked up for.
+                    Slot::Method(function) => (unsafe { &*function }, 1),
+                    _ => return self.error("callee is not a known function", idx),
+                };
+                if function.arity != *args {
+                    return self.error("wrong number of arguments in call", idx);
+                }
+                for param in first_param..*args {
+                    let is_valid = match (param_type(function, param), stack[base + param]) {
+                        (None, _) => true,
+                        (Some(param_type), Slot::Value(pseudo_type)) => {
+                            accepts(param_type, pseudo_type)
+                        }
+                        (Some(_), _) => false,
+                    };
+                    if !is_valid {
+                        return self.error("argument of the wrong type", idx);
+                    }
+                }
+                stack.truncate(base - 1);
+                stack.push(Slot::Value(function.ret_type));
+            }
+            Ret(args) => {
+                if self.is_script {
+                    return self.error("Ret outside of a function", idx);
+                }
+                if *args != self.function.arity {
+                    return self.error("Ret arity does not match function", idx);
+                }
+                pop_value!(self.function.ret_type);
+                return Ok([None, None]);
+            }
+            Input(pseudo_type) => match pseudo_type {
+                Type::Integer | Type::Real | Type::Char | Type::Boolean | Type::String
+                | Type::Enum(_) => stack.push(Slot::Value(*pseudo_type)),
+                _ => return self.error("INPUT of a type that can't be read", idx),
+            },
+            Output(pseudo_type) => match pseudo_type {
+                Type::Integer | Type::Real | Type::Char | Type::Boolean | Type::String
+                | Type::Enum(_) | Type::Set(_) => {
+                    pop_value!(*pseudo_type);
+                }
+                _ => return self.error("OUTPUT of a type that can't be printed", idx),
+            },
+            OutputLn | OutputSpace => (),
+            Concat => {
+                pop_value!(Type::String);
+                pop_value!(Type::String);
+                stack.push(Slot::Value(Type::String));
+            }
+            CharToString => {
+                pop_value!(Type::Char);
+                stack.push(Slot::Value(Type::String));
+            }
+            Add(pseudo_type) | Sub(pseudo_type) | Mul(pseudo_type) | Div(pseudo_type)
+            | Mod(pseudo_type) => {
+                let is_valid = matches!(
+                    (&chunk.instructions[idx], pseudo_type),
+                    (_, Type::Integer | Type::Real)
+                        | (Add(_) | Sub(_) | Mul(_), Type::Enum(_) | Type::Set(_))
+                );
+                if !is_valid {
+                    return self.error("arithmetic on a type without it", idx);
+                }
+                pop_value!(*pseudo_type);
+                pop_value!(*pseudo_type);
+                stack.push(Slot::Value(*pseudo_type));
+            }
+            Gt(pseudo_type) | GtEq(pseudo_type) => {
+                if !matches!(pseudo_type, Type::Integer | Type::Real | Type::Enum(_)) {
+                    return self.error("comparison of a type without an order", idx);
+                }
+                pop_value!(*pseudo_type);
+                pop_value!(*pseudo_type);
+                stack.push(Slot::Value(Type::Boolean));
+            }
+            Eq(pseudo_type) => {
+                pop_value!(*pseudo_type);
+                pop_value!(*pseudo_type);
+                stack.push(Slot::Value(Type::Boolean));
+            }
+            Neg(pseudo_type) => {
+                if !matches!(pseudo_type, Type::Integer | Type::Real) {
+                    return self.error("negation of a type without it", idx);
+                }
+                pop_value!(*pseudo_type);
+                stack.push(Slot::Value(*pseudo_type));
+            }
+            Not => {
+                pop_value!(Type::Boolean);
+                stack.push(Slot::Value(Type::Boolean));
+            }
+            True | False => stack.push(Slot::Value(Type::Boolean)),
+            Null => stack.push(Slot::Value(Type::Null)),
+            JumpFalse(target) | JumpTrue(target) => {
+                match stack.last() {
+                    Some(Slot::Value(pseudo_type)) if accepts(Type::Boolean, *pseudo_type) => (),
+                    _ => return self.error("condition isn't a BOOLEAN", idx),
+                }
+                return Ok([next, Some(*target)]);
+            }
+            Jump(target) => return Ok([Some(*target), None]),
+            Try(target) => state.handlers.push((*target, stack.len())),
+            EndTry => {
+                if state.handlers.pop().is_none() {
+                    return self.error("EndTry outside of a TRY block", idx);
+                }
+            }
+            AddrLocal(local) => match chunk.local_at(*local, idx) {
+                Some(variable) => stack.push(Slot::Value(Type::pointer(variable.pseudo_type))),
+                None => return self.error("address taken of a slot that isn't a variable", idx),
+            },
+            AddrGlobal(global) => match program.global_types.get(*global) {
+                Some(Some(pseudo_type)) => stack.push(Slot::Value(Type::pointer(*pseudo_type))),
+                Some(None) => return self.error("address taken of a function", idx),
+                None => return self.error("global index out of range", idx),
+            },
+            Alloc(pseudo_type) => {
+                pop_value!(*pseudo_type);
+                stack.push(Slot::Value(Type::pointer(*pseudo_type)));
+            }
+            Deref => match pop!() {
+                Slot::Value(Type::Pointer(pointee)) => stack.push(Slot::Value(*pointee)),
+                // Stops with a NULL pointer error.
+                Slot::Value(Type::Null) => stack.push(Slot::Unknown),
+                _ => return self.error("Deref of a value that isn't a pointer", idx),
+            },
+            StoreDeref => {
+                let slot = pop!();
+                let is_valid = match (pop!(), slot) {
+                    (Slot::Value(Type::Pointer(pointee)), Slot::Value(pseudo_type)) => {
+                        accepts(*pointee, pseudo_type)
+                    }
+                    (Slot::Value(Type::Null), slot) => slot.is_movable(),
+                    _ => false,
+                };
+                if !is_valid {
+                    return self.error("stored through a pointer of the wrong type", idx);
+                }
+                stack.push(slot);
+            }
+            EmptySet(element_type) => stack.push(Slot::Value(Type::set(*element_type))),
+            SetInsert => {
+                let value = pop!();
+                let is_valid = match (stack.last(), value) {
+                    (Some(Slot::Value(Type::Set(element_type))), Slot::Value(pseudo_type)) => {
+                        accepts(**element_type, pseudo_type)
+                    }
+                    _ => false,
+                };
+                if !is_valid {
+                    return self.error("set given a value of the wrong type", idx);
+                }
+            }
+            In => {
+                let element_type = match pop!() {
+                    Slot::Value(Type::Set(element_type)) => *element_type,
+                    _ => return self.error("operand isn't a set", idx),
+                };
+                pop_value!(element_type);
+                stack.push(Slot::Value(Type::Boolean));
+            }
+            Instantiate(constant) => match class_constant(chunk, *constant) {
+                Some(class) => stack.push(Slot::Value(Type::Class(class.class_type))),
+                None => return self.error("Instantiate of a constant that isn't a class", idx),
+            },
+            GetField(field) => {
+                let class = pop_object!();
+                match class.fields.get(*field) {
+                    Some(pseudo_type) => stack.push(Slot::Value(*pseudo_type)),
+                    None => return self.error("attribute out of range", idx),
+                }
+            }
+            SetField(field) => {
+                let slot = pop!();
+                let class = pop_object!();
+                match (class.fields.get(*field), slot) {
+                    (Some(field_type), Slot::Value(pseudo_type))
+                        if accepts(*field_type, pseudo_type) => {}
+                    (None, _) => return self.error("attribute out of range", idx),
+                    _ => return self.error("attribute assigned the wrong type", idx),
+                }
+                stack.push(slot);
+            }
+            Method(method) => {
+                let class = pop_object!();
+                if *method >= class.methods.len() {
+                    return self.error("method out of range", idx);
+                }
+                stack.push(Slot::Method(program.method(class, *method)));
+                stack.push(Slot::Receiver);
+            }
+        }
+        Ok([next, None])
+    }
+}